@@ -0,0 +1,658 @@
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::{Rc, Weak};
+use std::sync::Mutex;
+
+use datex_core::global::dxb_block::{DXBBlock, HeaderParsingError};
+use datex_core::network::com_interfaces::com_interface::{
+    ComInterface, ComInterfaceUUID,
+};
+use datex_core::network::com_interfaces::com_interface_socket::{
+    ComInterfaceSocket, ComInterfaceSocketUUID,
+};
+use datex_core::runtime::Runtime;
+use datex_core::stdlib::sync::Arc;
+use datex_core::task::spawn_local;
+use log::{debug, error};
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::js_sys::{Array, Function, Promise, Uint8Array};
+
+use crate::network::com_hub::JSComHub;
+use crate::network::com_hub_statistics::TrafficStatistics;
+
+/// Interface types of the DATEX core that send and receive their blocks
+/// without the interfaces of this crate. Their blocks are passed to the
+/// interceptors by the block hooks of the ComHub, which can only observe
+/// the blocks
+const CORE_INTERFACE_TYPES: &[&str] = &["matchbox"];
+
+type ReceiveQueue = Arc<Mutex<VecDeque<u8>>>;
+
+/// Returns true if the block was consumed
+type BlockHandler = Rc<dyn Fn(&DXBBlock) -> bool>;
+
+/// Implements ComInterface::send_block by passing the block through the
+/// outgoing interceptors of the ComHub before it is sent with the given
+/// send function of the interface, see [send_block_intercepted]
+#[macro_export]
+macro_rules! intercepted_send_block {
+    ($send:ident) => {
+        fn send_block<'a>(
+            &'a mut self,
+            block: &'a [u8],
+            socket_uuid: datex_core::network::com_interfaces::com_interface_socket::ComInterfaceSocketUUID,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = bool> + 'a>> {
+            $crate::network::block_interceptors::send_block_intercepted(
+                self,
+                block,
+                socket_uuid,
+                Self::$send,
+            )
+        }
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BlockDirection {
+    Incoming,
+    Outgoing,
+}
+
+struct BlockInterceptor {
+    id: u32,
+    direction: BlockDirection,
+    callback: Function,
+}
+
+#[derive(Default)]
+struct SocketReceiveState {
+    /// Received bytes of a block that is not complete yet
+    partial_block: Vec<u8>,
+    /// Complete blocks that wait until the interceptors of the previous
+    /// blocks resolved, so that the order is kept
+    pending_blocks: VecDeque<Vec<u8>>,
+    is_intercepting: bool,
+}
+
+impl SocketReceiveState {
+    fn is_idle(&self) -> bool {
+        self.partial_block.is_empty()
+            && self.pending_blocks.is_empty()
+            && !self.is_intercepting
+    }
+}
+
+struct BlockInterceptorsInner {
+    runtime: Runtime,
+    /// Traffic of the sockets, counted where the blocks are handed to the
+    /// transport or added to the receive queue
+    traffic: TrafficStatistics,
+    interceptors: RefCell<Vec<BlockInterceptor>>,
+    next_interceptor_id: Cell<u32>,
+    sockets: RefCell<HashMap<ComInterfaceSocketUUID, SocketReceiveState>>,
+    /// Whether a socket belongs to an interface of the DATEX core,
+    /// looked up once per socket
+    core_sockets: RefCell<HashMap<ComInterfaceSocketUUID, bool>>,
+    /// Handlers of this crate that can consume received blocks after they
+    /// passed the interceptors, e.g. for in-band WebRTC signaling
+    block_handlers: RefCell<Vec<BlockHandler>>,
+}
+
+/// Interceptors of a ComHub. All interfaces of this crate pass their
+/// received data and outgoing blocks through the interceptors of the
+/// ComHub they belong to, so each block is intercepted as a complete
+/// DXB block, regardless of how the transport delivers it
+#[derive(Clone)]
+pub struct BlockInterceptors {
+    inner: Rc<BlockInterceptorsInner>,
+}
+
+thread_local! {
+    /// Interceptors of all ComHubs, owned by their JSComHub
+    static COM_HUB_INTERCEPTORS: RefCell<Vec<Weak<BlockInterceptorsInner>>> =
+        RefCell::new(Vec::new());
+}
+
+impl BlockInterceptors {
+    pub fn new(
+        runtime: Runtime,
+        traffic: TrafficStatistics,
+    ) -> BlockInterceptors {
+        let inner = Rc::new(BlockInterceptorsInner {
+            runtime,
+            traffic,
+            interceptors: RefCell::new(Vec::new()),
+            next_interceptor_id: Cell::new(0),
+            sockets: RefCell::new(HashMap::new()),
+            core_sockets: RefCell::new(HashMap::new()),
+            block_handlers: RefCell::new(Vec::new()),
+        });
+        COM_HUB_INTERCEPTORS.with(|com_hubs| {
+            let mut com_hubs = com_hubs.borrow_mut();
+            com_hubs.retain(|com_hub| com_hub.strong_count() > 0);
+            com_hubs.push(Rc::downgrade(&inner));
+        });
+        Self::observe_core_interfaces(&inner);
+        BlockInterceptors { inner }
+    }
+
+    /// Passes the blocks of the interfaces of the DATEX core to the
+    /// interceptors via the block hooks of the ComHub. The hooks are
+    /// called for the blocks of all interfaces, the blocks of the
+    /// interfaces of this crate are already intercepted when they
+    /// are sent or received
+    fn observe_core_interfaces(inner: &Rc<BlockInterceptorsInner>) {
+        let com_hub = inner.runtime.com_hub();
+        // the hooks are owned by the ComHub, which outlives the interceptors
+        let weak_inner = Rc::downgrade(inner);
+        com_hub.register_incoming_block_interceptor(move |block, socket| {
+            if let Some(inner) = weak_inner.upgrade() {
+                BlockInterceptors { inner }.observe(block, socket, None);
+            }
+        });
+        let weak_inner = Rc::downgrade(inner);
+        com_hub.register_outgoing_block_interceptor(
+            move |block, socket, endpoints| {
+                if let Some(inner) = weak_inner.upgrade() {
+                    let endpoints = endpoints
+                        .iter()
+                        .map(|endpoint| {
+                            JsValue::from_str(&endpoint.to_string())
+                        })
+                        .collect();
+                    BlockInterceptors { inner }.observe(
+                        block,
+                        socket,
+                        Some(endpoints),
+                    );
+                }
+            },
+        );
+    }
+
+    /// Counts a block of an interface of the DATEX core in the traffic
+    /// statistics and calls the interceptors with it. The block has already
+    /// been sent or is processed anyway, so the return values of the
+    /// interceptors are ignored
+    fn observe(
+        &self,
+        block: &DXBBlock,
+        socket_uuid: &ComInterfaceSocketUUID,
+        endpoints: Option<Array>,
+    ) {
+        let direction = match endpoints {
+            Some(_) => BlockDirection::Outgoing,
+            None => BlockDirection::Incoming,
+        };
+        if !self.is_core_socket(socket_uuid) {
+            return;
+        }
+        let block = match block.to_bytes() {
+            Ok(block) => block,
+            Err(e) => {
+                error!("Failed to convert block to bytes: {e:?}");
+                return;
+            }
+        };
+        // the outgoing hook is called before the block is sent, so the
+        // block is counted as sent when it is handed to the interface
+        match direction {
+            BlockDirection::Outgoing => {
+                self.inner
+                    .traffic
+                    .record_sent(socket_uuid, block.len(), true)
+            }
+            BlockDirection::Incoming => {
+                self.inner.traffic.record_received(socket_uuid, block.len())
+            }
+        }
+        let callbacks = self.get_callbacks(direction);
+        if callbacks.is_empty() {
+            return;
+        }
+        let socket_uuid = JsValue::from_str(&socket_uuid.0.to_string());
+        for callback in callbacks {
+            let block_bytes = Uint8Array::from(&block[..]);
+            let result = match &endpoints {
+                Some(endpoints) => callback.call3(
+                    &JsValue::NULL,
+                    &block_bytes,
+                    &socket_uuid,
+                    endpoints,
+                ),
+                None => {
+                    callback.call2(&JsValue::NULL, &block_bytes, &socket_uuid)
+                }
+            };
+            if let Err(e) = result {
+                error!("Error in block interceptor callback: {e:?}");
+            }
+        }
+    }
+
+    /// Returns true if the socket belongs to an interface of the DATEX core
+    fn is_core_socket(&self, socket_uuid: &ComInterfaceSocketUUID) -> bool {
+        if let Some(is_core_socket) =
+            self.inner.core_sockets.borrow().get(socket_uuid)
+        {
+            return *is_core_socket;
+        }
+        let socket = socket_uuid.0.to_string();
+        let is_core_socket = self
+            .inner
+            .runtime
+            .com_hub()
+            .get_metadata()
+            .interfaces
+            .iter()
+            .find(|interface| {
+                interface.sockets.iter().any(|s| s.uuid == socket)
+            })
+            .is_some_and(|interface| {
+                CORE_INTERFACE_TYPES
+                    .contains(&interface.properties.interface_type.as_str())
+            });
+        let mut core_sockets = self.inner.core_sockets.borrow_mut();
+        // sockets of closed interfaces are no longer cached
+        if core_sockets.len() > 1024 {
+            core_sockets.clear();
+        }
+        core_sockets.insert(socket_uuid.clone(), is_core_socket);
+        is_core_socket
+    }
+
+    /// Removes the traffic of a closed socket. Socket UUIDs are unique,
+    /// so the socket is removed from all ComHubs without looking up the
+    /// ComHub of its interface, which may be borrowed while it is closed
+    pub(crate) fn remove_socket(socket_uuid: &ComInterfaceSocketUUID) {
+        COM_HUB_INTERCEPTORS.with(|com_hubs| {
+            for inner in com_hubs.borrow().iter().filter_map(Weak::upgrade) {
+                inner.traffic.remove_socket(socket_uuid);
+                inner.core_sockets.borrow_mut().remove(socket_uuid);
+            }
+        })
+    }
+
+    /// Returns the interceptors of the ComHub that the interface belongs to
+    fn find(interface_uuid: &ComInterfaceUUID) -> Option<BlockInterceptors> {
+        COM_HUB_INTERCEPTORS.with(|com_hubs| {
+            com_hubs
+                .borrow()
+                .iter()
+                .filter_map(Weak::upgrade)
+                .find(|inner| {
+                    inner.runtime.com_hub().has_interface(interface_uuid)
+                })
+                .map(|inner| BlockInterceptors { inner })
+        })
+    }
+
+    /// Adds a handler that is called with each received block after it
+    /// passed the interceptors. If the handler returns true, the block is
+    /// consumed and not processed by the ComHub
+    pub(crate) fn add_block_handler(
+        &self,
+        handler: impl Fn(&DXBBlock) -> bool + 'static,
+    ) {
+        self.inner
+            .block_handlers
+            .borrow_mut()
+            .push(Rc::new(handler));
+    }
+
+    fn register(&self, direction: BlockDirection, callback: Function) -> u32 {
+        let id = self.inner.next_interceptor_id.get();
+        self.inner.next_interceptor_id.set(id.wrapping_add(1));
+        self.inner.interceptors.borrow_mut().push(BlockInterceptor {
+            id,
+            direction,
+            callback,
+        });
+        id
+    }
+
+    fn unregister(&self, interceptor_id: u32) -> bool {
+        let mut interceptors = self.inner.interceptors.borrow_mut();
+        let count = interceptors.len();
+        interceptors.retain(|interceptor| interceptor.id != interceptor_id);
+        interceptors.len() != count
+    }
+
+    fn get_callbacks(&self, direction: BlockDirection) -> Vec<Function> {
+        self.inner
+            .interceptors
+            .borrow()
+            .iter()
+            .filter(|interceptor| interceptor.direction == direction)
+            .map(|interceptor| interceptor.callback.clone())
+            .collect()
+    }
+
+    /// Splits the received data of the socket into complete blocks. Data of
+    /// an incomplete block is kept until the rest of the block is received,
+    /// data that is not a DXB block is passed on as is
+    fn split_blocks(
+        &self,
+        socket_uuid: &ComInterfaceSocketUUID,
+        data: &[u8],
+    ) -> Vec<Vec<u8>> {
+        let mut sockets = self.inner.sockets.borrow_mut();
+        let state = sockets.entry(socket_uuid.clone()).or_default();
+        state.partial_block.extend_from_slice(data);
+        let mut blocks = vec![];
+        loop {
+            match DXBBlock::extract_dxb_block_length(&state.partial_block) {
+                Ok(length)
+                    if length > 0
+                        && state.partial_block.len() >= length as usize =>
+                {
+                    let rest = state.partial_block.split_off(length as usize);
+                    blocks.push(std::mem::replace(
+                        &mut state.partial_block,
+                        rest,
+                    ));
+                }
+                Ok(_) | Err(HeaderParsingError::InsufficientLength) => break,
+                Err(e) => {
+                    debug!("Received data is not a DXB block: {e:?}");
+                    blocks.push(std::mem::take(&mut state.partial_block));
+                    break;
+                }
+            }
+        }
+        blocks
+    }
+
+    /// Adds the received data to the receive queue of the socket after each
+    /// block passed the incoming interceptors
+    fn receive(
+        &self,
+        socket_uuid: ComInterfaceSocketUUID,
+        receive_queue: ReceiveQueue,
+        data: &[u8],
+    ) {
+        let blocks = self.split_blocks(&socket_uuid, data);
+        let is_intercepting = {
+            let mut sockets = self.inner.sockets.borrow_mut();
+            let state = sockets.entry(socket_uuid.clone()).or_default();
+            state.pending_blocks.extend(blocks);
+            state.is_intercepting
+        };
+        if is_intercepting {
+            return;
+        }
+        if self.get_callbacks(BlockDirection::Incoming).is_empty() {
+            while let Some(block) = self.next_pending_block(&socket_uuid) {
+                self.enqueue(&socket_uuid, &receive_queue, block);
+            }
+            return;
+        }
+        self.set_intercepting(&socket_uuid, true);
+        let interceptors = self.clone();
+        spawn_local(async move {
+            while let Some(block) =
+                interceptors.next_pending_block(&socket_uuid)
+            {
+                // interceptors may have been registered or removed meanwhile
+                let callbacks =
+                    interceptors.get_callbacks(BlockDirection::Incoming);
+                match intercept(callbacks, block, &socket_uuid, None).await {
+                    Some(block) => interceptors.enqueue(
+                        &socket_uuid,
+                        &receive_queue,
+                        block,
+                    ),
+                    None => {
+                        debug!("Incoming block was dropped by an interceptor")
+                    }
+                }
+            }
+            interceptors.set_intercepting(&socket_uuid, false);
+        });
+    }
+
+    /// Adds the block to the receive queue of the socket, unless it is
+    /// consumed by a block handler
+    fn enqueue(
+        &self,
+        socket_uuid: &ComInterfaceSocketUUID,
+        receive_queue: &ReceiveQueue,
+        block: Vec<u8>,
+    ) {
+        self.inner.traffic.record_received(socket_uuid, block.len());
+        if !self.is_consumed(&block) {
+            receive_queue.lock().unwrap().extend(block);
+        }
+    }
+
+    fn is_consumed(&self, block: &[u8]) -> bool {
+        // the handlers are called without borrowing the list
+        let handlers = self.inner.block_handlers.borrow().clone();
+        if handlers.is_empty() {
+            return false;
+        }
+        let Ok(block) = DXBBlock::from_bytes(block) else {
+            return false;
+        };
+        handlers.iter().any(|handler| handler(&block))
+    }
+
+    /// Counts the block in the traffic statistics of the socket once the
+    /// interface handed it to the transport
+    fn track_send<'a>(
+        &self,
+        socket_uuid: ComInterfaceSocketUUID,
+        size: usize,
+        send: Pin<Box<dyn Future<Output = bool> + 'a>>,
+    ) -> Pin<Box<dyn Future<Output = bool> + 'a>> {
+        let traffic = self.inner.traffic.clone();
+        Box::pin(async move {
+            let success = send.await;
+            traffic.record_sent(&socket_uuid, size, success);
+            success
+        })
+    }
+
+    fn next_pending_block(
+        &self,
+        socket_uuid: &ComInterfaceSocketUUID,
+    ) -> Option<Vec<u8>> {
+        let mut sockets = self.inner.sockets.borrow_mut();
+        let state = sockets.get_mut(socket_uuid)?;
+        let block = state.pending_blocks.pop_front();
+        if state.is_idle() {
+            sockets.remove(socket_uuid);
+        }
+        block
+    }
+
+    fn set_intercepting(
+        &self,
+        socket_uuid: &ComInterfaceSocketUUID,
+        is_intercepting: bool,
+    ) {
+        let mut sockets = self.inner.sockets.borrow_mut();
+        let state = sockets.entry(socket_uuid.clone()).or_default();
+        state.is_intercepting = is_intercepting;
+        if state.is_idle() {
+            sockets.remove(socket_uuid);
+        }
+    }
+}
+
+/// Passes the block through the interceptors in the order of registration.
+/// Each interceptor receives the block returned by the previous one, outgoing
+/// interceptors also receive the receiver endpoints of the block.
+/// Returns None if an interceptor dropped the block or replaced it with
+/// data that is not a valid block
+async fn intercept(
+    callbacks: Vec<Function>,
+    mut block: Vec<u8>,
+    socket_uuid: &ComInterfaceSocketUUID,
+    endpoints: Option<Array>,
+) -> Option<Vec<u8>> {
+    if let Err(e) = DXBBlock::from_bytes(&block) {
+        // data that is not a block is not processed by the ComHub
+        debug!("Not intercepting data that is not a DXB block: {e:?}");
+        return Some(block);
+    }
+    let socket_uuid = JsValue::from_str(&socket_uuid.0.to_string());
+    for callback in callbacks {
+        let block_bytes = Uint8Array::from(&block[..]);
+        let result = match &endpoints {
+            Some(endpoints) => callback.call3(
+                &JsValue::NULL,
+                &block_bytes,
+                &socket_uuid,
+                endpoints,
+            ),
+            None => callback.call2(&JsValue::NULL, &block_bytes, &socket_uuid),
+        }
+        .map(|result| JsFuture::from(Promise::resolve(&result)));
+        // async interceptors delay the block until they resolve
+        let result = match result {
+            Ok(result) => result.await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(result) if result.as_bool() == Some(false) => return None,
+            Ok(result) => {
+                if let Some(rewritten) = result.dyn_ref::<Uint8Array>() {
+                    let rewritten = rewritten.to_vec();
+                    if let Err(e) = DXBBlock::from_bytes(&rewritten) {
+                        error!(
+                            "Block interceptor returned an invalid block: {e:?}"
+                        );
+                        return None;
+                    }
+                    block = rewritten;
+                }
+            }
+            // a failing interceptor does not drop the block
+            Err(e) => error!("Error in block interceptor callback: {e:?}"),
+        }
+    }
+    Some(block)
+}
+
+/// Returns the receiver endpoints of the block as array of strings
+fn get_receiver_endpoints(block: &[u8]) -> Array {
+    match DXBBlock::from_bytes(block) {
+        Ok(block) => block
+            .receiver_endpoints()
+            .iter()
+            .map(|endpoint| JsValue::from_str(&endpoint.to_string()))
+            .collect(),
+        Err(_) => Array::new(),
+    }
+}
+
+/// Sends the block with the send function of the interface after it passed
+/// the outgoing interceptors of the ComHub. A block that was dropped by an
+/// interceptor is reported as handled, since it was not sent on purpose
+pub(crate) fn send_block_intercepted<'a, I: ComInterface>(
+    interface: &'a mut I,
+    block: &'a [u8],
+    socket_uuid: ComInterfaceSocketUUID,
+    send: for<'b> fn(
+        &'b mut I,
+        &'b [u8],
+        ComInterfaceSocketUUID,
+    ) -> Pin<Box<dyn Future<Output = bool> + 'b>>,
+) -> Pin<Box<dyn Future<Output = bool> + 'a>> {
+    let Some(interceptors) = BlockInterceptors::find(interface.get_uuid())
+    else {
+        return send(interface, block, socket_uuid);
+    };
+    let callbacks = interceptors.get_callbacks(BlockDirection::Outgoing);
+    if callbacks.is_empty() {
+        return interceptors.track_send(
+            socket_uuid.clone(),
+            block.len(),
+            send(interface, block, socket_uuid),
+        );
+    }
+    Box::pin(async move {
+        let endpoints = get_receiver_endpoints(block);
+        let Some(block) =
+            intercept(callbacks, block.to_vec(), &socket_uuid, Some(endpoints))
+                .await
+        else {
+            debug!("Outgoing block was dropped by an interceptor");
+            return true;
+        };
+        interceptors
+            .track_send(
+                socket_uuid.clone(),
+                block.len(),
+                send(interface, &block, socket_uuid),
+            )
+            .await
+    })
+}
+
+/// Adds the received data to the receive queue of the socket after each
+/// complete block passed the incoming interceptors of the ComHub. For
+/// stream-based transports, the data is not necessarily a complete block
+pub(crate) fn receive_block(
+    socket: &Arc<Mutex<ComInterfaceSocket>>,
+    data: &[u8],
+) {
+    let (socket_uuid, interface_uuid, receive_queue) = {
+        let socket = socket.lock().unwrap();
+        (
+            socket.uuid.clone(),
+            socket.interface_uuid.clone(),
+            socket.receive_queue.clone(),
+        )
+    };
+    match BlockInterceptors::find(&interface_uuid) {
+        Some(interceptors) => {
+            interceptors.receive(socket_uuid, receive_queue, data)
+        }
+        None => receive_queue.lock().unwrap().extend(data),
+    }
+}
+
+#[wasm_bindgen]
+impl JSComHub {
+    /// Registers a callback that is called with (block, socket_uuid) for
+    /// each complete block that is received by an interface, before it is
+    /// processed.
+    /// The callback can return false to drop the block, a Uint8Array to
+    /// replace it, or a Promise to delay it until the Promise resolves.
+    /// Blocks of matchbox interfaces can only be observed, the return value
+    /// is ignored for them. Returns the id of the interceptor
+    pub fn register_incoming_block_interceptor(
+        &self,
+        callback: Function,
+    ) -> u32 {
+        self.interceptors
+            .register(BlockDirection::Incoming, callback)
+    }
+
+    /// Registers a callback that is called with (block, socket_uuid,
+    /// endpoints) for each block before it is sent by an interface.
+    /// The callback can return false to drop the block, a Uint8Array to
+    /// replace it, or a Promise to delay it until the Promise resolves.
+    /// A dropped block is reported as handled, not as a failed send.
+    /// Blocks of matchbox interfaces can only be observed, the return value
+    /// is ignored for them. Returns the id of the interceptor
+    pub fn register_outgoing_block_interceptor(
+        &self,
+        callback: Function,
+    ) -> u32 {
+        self.interceptors
+            .register(BlockDirection::Outgoing, callback)
+    }
+
+    /// Removes the interceptor with the given id, returns false
+    /// if there is no such interceptor
+    pub fn unregister_block_interceptor(&self, interceptor_id: u32) -> bool {
+        self.interceptors.unregister(interceptor_id)
+    }
+}
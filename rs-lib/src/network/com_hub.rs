@@ -5,20 +5,117 @@ use datex_core::network::com_interfaces::com_interface::{
 };
 use datex_core::network::com_interfaces::com_interface_socket::ComInterfaceSocketUUID;
 use datex_core::runtime::Runtime;
+use datex_core::serde::serializer::to_value_container;
 use datex_core::stdlib::{cell::RefCell, rc::Rc};
 use datex_core::values::core_values::endpoint::Endpoint;
+use datex_core::values::value_container::ValueContainer;
 use datex_core::{network::com_hub::ComHub, utils::uuid::UUID};
 use log::error;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
 use std::str::FromStr;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::future_to_promise;
 use web_sys::js_sys::{self, Promise};
 
+use crate::network::com_interfaces::custom_js_interface::remove_custom_interface_request;
+#[cfg(feature = "wasm_webrtc")]
+use crate::network::com_interfaces::webrtc_js_interface::WebRTCJSInterface;
+
+/// Deserializes the setup data passed from JS into the setup data type
+/// of an interface factory and converts it into the value container
+/// that is passed to the factory
+type SetupDataValidator = fn(JsValue) -> Result<ValueContainer, String>;
+
+fn validate_setup_data<T: Serialize + DeserializeOwned>(
+    setup_data: JsValue,
+) -> Result<ValueContainer, String> {
+    // the path of the invalid field is included in the error message
+    let setup_data: T = serde_path_to_error::deserialize(
+        serde_wasm_bindgen::Deserializer::from(setup_data),
+    )
+    .map_err(|e| e.to_string())?;
+    setup_data_to_value_container(&setup_data)
+}
+
+/// Converts the setup data of an interface factory into a value container
+/// without going through DATEX source
+pub(crate) fn setup_data_to_value_container<T: Serialize>(
+    setup_data: &T,
+) -> Result<ValueContainer, String> {
+    to_value_container(setup_data).map_err(|e| format!("{e:?}"))
+}
+
+/// Creates an interface with setup data that was already converted for
+/// the factory and starts the tasks that the interface needs after it
+/// was opened
+pub(crate) async fn create_interface_with_setup_data(
+    runtime: &Runtime,
+    interface_type: &str,
+    setup_data: ValueContainer,
+    priority: Option<u16>,
+) -> Result<ComInterfaceUUID, String> {
+    let com_hub = runtime.com_hub();
+    let interface = com_hub
+        .create_interface(
+            interface_type,
+            setup_data,
+            priority
+                .map(InterfacePriority::Priority)
+                .unwrap_or_default(),
+        )
+        .await
+        .map_err(|e| format!("{e:?}"))?;
+    let interface_uuid = interface.borrow().get_uuid().clone();
+    #[cfg(feature = "wasm_webrtc")]
+    if let Some(interface) =
+        com_hub.get_interface_by_uuid::<WebRTCJSInterface>(&interface_uuid)
+    {
+        WebRTCJSInterface::collect_stats_periodically(&interface);
+    }
+    Ok(interface_uuid)
+}
+
+/// Registers an interface factory together with the validator
+/// for its setup data
+macro_rules! register_interface_factory {
+    ($js_com_hub:expr, $interface_type:literal, $interface:ty, $setup_data:ty) => {
+        $js_com_hub.com_hub().register_interface_factory(
+            $interface_type.to_string(),
+            <$interface as ComInterfaceFactory<$setup_data>>::factory,
+        );
+        $js_com_hub.setup_data_validators.borrow_mut().insert(
+            $interface_type.to_string(),
+            validate_setup_data::<$setup_data>,
+        );
+    };
+}
+
 #[wasm_bindgen]
 #[derive(Clone)]
 pub struct JSComHub {
     // ignore for wasm bindgen
-    runtime: Runtime,
+    pub(crate) runtime: Runtime,
+    /// JS factories registered via register_interface_factory
+    pub(crate) custom_interface_factories:
+        Rc<RefCell<HashMap<String, JsValue>>>,
+    /// Setup data validators of the registered Rust interface factories
+    setup_data_validators: Rc<RefCell<HashMap<String, SetupDataValidator>>>,
+    pub(crate) events: crate::network::com_hub_events::ComHubEvents,
+    /// Priorities that were passed to create_interface
+    pub(crate) interface_priorities:
+        Rc<RefCell<HashMap<ComInterfaceUUID, u16>>>,
+    pub(crate) traffic: crate::network::com_hub_statistics::TrafficStatistics,
+    pub(crate) interceptors:
+        crate::network::block_interceptors::BlockInterceptors,
+    #[cfg(feature = "wasm_webrtc")]
+    pub(crate) webrtc_signaling:
+        crate::network::webrtc_signaling::WebRTCSignaling,
+    /// Peer event callbacks of the matchbox interfaces
+    #[cfg(feature = "wasm_webrtc")]
+    pub(crate) matchbox_peer_event_callbacks:
+        Rc<RefCell<HashMap<ComInterfaceUUID, js_sys::Function>>>,
 }
 
 /**
@@ -26,13 +123,44 @@ pub struct JSComHub {
  */
 impl JSComHub {
     pub fn new(runtime: Runtime) -> JSComHub {
-        JSComHub { runtime }
+        let traffic =
+            crate::network::com_hub_statistics::TrafficStatistics::default();
+        let interceptors =
+            crate::network::block_interceptors::BlockInterceptors::new(
+                runtime.clone(),
+                traffic.clone(),
+            );
+        JSComHub {
+            #[cfg(feature = "wasm_webrtc")]
+            webrtc_signaling:
+                crate::network::webrtc_signaling::WebRTCSignaling::new(
+                    runtime.clone(),
+                    interceptors.clone(),
+                ),
+            #[cfg(feature = "wasm_webrtc")]
+            matchbox_peer_event_callbacks: Rc::new(
+                RefCell::new(HashMap::new()),
+            ),
+            custom_interface_factories: Rc::new(RefCell::new(HashMap::new())),
+            setup_data_validators: Rc::new(RefCell::new(HashMap::new())),
+            events: crate::network::com_hub_events::ComHubEvents::new(
+                runtime.clone(),
+            ),
+            interface_priorities: Rc::new(RefCell::new(HashMap::new())),
+            traffic,
+            interceptors,
+            runtime,
+        }
     }
 
     pub fn com_hub(&self) -> &ComHub {
         self.runtime.com_hub()
     }
 
+    pub(crate) fn endpoint(&self) -> Endpoint {
+        self.runtime.endpoint()
+    }
+
     pub fn get_interface_for_uuid<T: ComInterface>(
         &self,
         uuid: String,
@@ -46,6 +174,48 @@ impl JSComHub {
             Err(ComHubError::InterfaceDoesNotExist)
         }
     }
+
+    /// Returns true if a Rust interface factory is registered for the type
+    pub(crate) fn has_setup_data_validator(
+        &self,
+        interface_type: &str,
+    ) -> bool {
+        self.setup_data_validators
+            .borrow()
+            .contains_key(interface_type)
+    }
+
+    /// Returns the setup data for the interface factory, or a readable
+    /// error if the setup data does not match the setup data type.
+    /// For JS factories, the id of the pending request is returned as well
+    fn get_setup_data(
+        &self,
+        interface_type: &str,
+        setup_data: JsValue,
+    ) -> Result<(ValueContainer, Option<u32>), String> {
+        if let Some(setup_data) =
+            self.get_custom_interface_setup_data(interface_type, setup_data)
+        {
+            let request_id = setup_data.request_id;
+            return setup_data_to_value_container(&setup_data)
+                .map(|setup_data| (setup_data, Some(request_id)))
+                .inspect_err(|_| remove_custom_interface_request(request_id));
+        }
+        let validate = *self
+            .setup_data_validators
+            .borrow()
+            .get(interface_type)
+            .ok_or_else(|| {
+                format!("Interface type {interface_type} is not registered")
+            })?;
+        validate(setup_data)
+            .map(|setup_data| (setup_data, None))
+            .map_err(|e| {
+                format!(
+                    "Invalid setup data for {interface_type} interface: {e}"
+                )
+            })
+    }
 }
 
 /**
@@ -54,73 +224,163 @@ impl JSComHub {
 #[wasm_bindgen]
 impl JSComHub {
     pub fn register_default_interface_factories(&self) {
-        self.com_hub().register_interface_factory(
-            "base".to_string(),
-            crate::network::com_interfaces::base_interface::BaseJSInterface::factory
+        register_interface_factory!(
+            self,
+            "base",
+            crate::network::com_interfaces::base_interface::BaseJSInterface,
+            datex_core::network::com_interfaces::default_com_interfaces::base_interface::BaseInterfaceSetupData
         );
 
         #[cfg(feature = "wasm_websocket_client")]
-        self.com_hub().register_interface_factory(
-            "websocket-client".to_string(),
-            crate::network::com_interfaces::websocket_client_js_interface::WebSocketClientJSInterface::factory
+        register_interface_factory!(
+            self,
+            "websocket-client",
+            crate::network::com_interfaces::websocket_client_js_interface::WebSocketClientJSInterface,
+            crate::network::com_interfaces::websocket_client_js_interface::WebSocketClientJSInterfaceSetupData
         );
 
         #[cfg(feature = "wasm_websocket_server")]
-        self.com_hub().register_interface_factory(
-            "websocket-server".to_string(),
-            crate::network::com_interfaces::websocket_server_js_interface::WebSocketServerJSInterface::factory
+        register_interface_factory!(
+            self,
+            "websocket-server",
+            crate::network::com_interfaces::websocket_server_js_interface::WebSocketServerJSInterface,
+            crate::network::com_interfaces::websocket_server_js_interface::WebSocketServerJSInterfaceSetupData
         );
 
         //wasm_serial
         #[cfg(feature = "wasm_serial")]
-        self.com_hub().register_interface_factory(
-            "serial".to_string(),
-            crate::network::com_interfaces::serial_js_interface::SerialJSInterface::factory
+        register_interface_factory!(
+            self,
+            "serial",
+            crate::network::com_interfaces::serial_js_interface::SerialJSInterface,
+            crate::network::com_interfaces::serial_js_interface::SerialJSInterfaceSetupData
         );
 
         //wasm_webrtc
         #[cfg(feature = "wasm_webrtc")]
-        self.com_hub().register_interface_factory(
-            "webrtc".to_string(),
-            crate::network::com_interfaces::webrtc_js_interface::WebRTCJSInterface::factory
+        register_interface_factory!(
+            self,
+            "webrtc",
+            crate::network::com_interfaces::webrtc_js_interface::WebRTCJSInterface,
+            crate::network::com_interfaces::webrtc_js_interface::WebRTCJSInterfaceSetupData
+        );
+
+        #[cfg(feature = "wasm_webrtc")]
+        register_interface_factory!(
+            self,
+            "matchbox",
+            datex_core::network::com_interfaces::default_com_interfaces::webrtc::matchbox_client_interface::MatchboxClientInterface,
+            crate::network::com_interfaces::matchbox_js_interface::MatchboxJSInterfaceSetupData
+        );
+
+        #[cfg(feature = "wasm_webtransport")]
+        register_interface_factory!(
+            self,
+            "webtransport",
+            crate::network::com_interfaces::webtransport_js_interface::WebTransportClientJSInterface,
+            crate::network::com_interfaces::webtransport_js_interface::WebTransportClientInterfaceSetupData
+        );
+
+        #[cfg(feature = "wasm_broadcast_channel")]
+        register_interface_factory!(
+            self,
+            "broadcast-channel",
+            crate::network::com_interfaces::broadcast_channel_js_interface::BroadcastChannelJSInterface,
+            crate::network::com_interfaces::broadcast_channel_js_interface::BroadcastChannelInterfaceSetupData
+        );
+
+        #[cfg(feature = "wasm_message_port")]
+        register_interface_factory!(
+            self,
+            "message-port",
+            crate::network::com_interfaces::message_port_js_interface::MessagePortJSInterface,
+            crate::network::com_interfaces::message_port_js_interface::MessagePortInterfaceSetupData
+        );
+
+        #[cfg(feature = "wasm_http_client")]
+        register_interface_factory!(
+            self,
+            "http-client",
+            crate::network::com_interfaces::http_client_js_interface::HttpClientJSInterface,
+            crate::network::com_interfaces::http_client_js_interface::HttpClientInterfaceSetupData
+        );
+
+        #[cfg(feature = "wasm_http_server")]
+        register_interface_factory!(
+            self,
+            "http-server",
+            crate::network::com_interfaces::http_server_js_interface::HttpServerJSInterface,
+            crate::network::com_interfaces::http_server_js_interface::HttpServerJSInterfaceSetupData
+        );
+
+        #[cfg(feature = "wasm_loopback")]
+        register_interface_factory!(
+            self,
+            "loopback",
+            crate::network::com_interfaces::loopback_js_interface::LoopbackJSInterface,
+            crate::network::com_interfaces::loopback_js_interface::LoopbackInterfaceSetupData
         );
     }
 
+    /// Creates an interface from a plain JS object as setup data, which is
+    /// validated against the setup data type of the interface factory.
+    /// The priority is used for fallback routing, the default priority is
+    /// used if it is not set
     pub fn create_interface(
         &self,
         interface_type: String,
-        properties: String,
+        setup_data: JsValue,
+        priority: Option<u16>,
     ) -> Promise {
+        #[cfg(feature = "wasm_websocket_client")]
+        if interface_type == "websocket-client"
+            && let Err(e) = crate::network::com_interfaces::websocket_client_js_interface::check_handshake_endpoint(
+                &setup_data,
+                &self.runtime.endpoint(),
+            )
+        {
+            return Promise::reject(&JsError::new(&e).into());
+        }
+        let (setup_data, request_id) =
+            match self.get_setup_data(&interface_type, setup_data) {
+                Ok(setup_data) => setup_data,
+                Err(e) => return Promise::reject(&JsError::new(&e).into()),
+            };
+        let interface_priorities = self.interface_priorities.clone();
+        let events = self.events.clone();
         let runtime = self.runtime.clone();
         future_to_promise(async move {
-            let com_hub = runtime.com_hub();
-            let properties = runtime
-                .execute_sync(&properties, &[], None)
-                .map_err(|e| JsError::new(&format!("{e:?}")))?;
-            if let Some(properties) = properties {
-                let interface = com_hub
-                    .create_interface(
-                        &interface_type,
-                        properties,
-                        InterfacePriority::default(),
-                    )
-                    .await
-                    .map_err(|e| JsError::new(&format!("{e:?}")))?;
-                Ok(JsValue::from_str(
-                    &interface.borrow().get_uuid().0.to_string(),
-                ))
-            } else {
-                Err(JsError::new(
-                    "Failed to create interface: properties are empty",
-                )
-                .into())
+            let interface_uuid = create_interface_with_setup_data(
+                &runtime,
+                &interface_type,
+                setup_data,
+                priority,
+            )
+            .await;
+            // the request is not picked up if the creation failed before
+            // the JS factory was called
+            if let Some(request_id) = request_id {
+                remove_custom_interface_request(request_id);
             }
+            let interface_uuid =
+                interface_uuid.map_err(|e| JsError::new(&e))?;
+            if let Some(priority) = priority {
+                interface_priorities
+                    .borrow_mut()
+                    .insert(interface_uuid.clone(), priority);
+            }
+            events.check(runtime.com_hub());
+            Ok(JsValue::from_str(&interface_uuid.0.to_string()))
         })
     }
 
     pub fn close_interface(&self, interface_uuid: String) -> Promise {
         let interface_uuid =
             ComInterfaceUUID(UUID::from_string(interface_uuid));
+        let interface_priorities = self.interface_priorities.clone();
+        let events = self.events.clone();
+        #[cfg(feature = "wasm_webrtc")]
+        let webrtc_signaling = self.webrtc_signaling.clone();
         let runtime = self.runtime.clone();
         future_to_promise(async move {
             let com_hub = runtime.com_hub();
@@ -130,6 +390,10 @@ impl JSComHub {
                     .remove_interface(interface_uuid.clone())
                     .await
                     .map_err(|e| JsError::new(&format!("{e:?}")))?;
+                interface_priorities.borrow_mut().remove(&interface_uuid);
+                #[cfg(feature = "wasm_webrtc")]
+                webrtc_signaling.remove_interface(&interface_uuid);
+                events.check(com_hub);
                 Ok(JsValue::TRUE)
             } else {
                 error!("Failed to find interface");
@@ -214,68 +478,4 @@ impl JSComHub {
             None
         }
     }
-
-    pub fn register_outgoing_block_interceptor(
-        &self,
-        callback: js_sys::Function,
-    ) {
-        self.com_hub().register_outgoing_block_interceptor(
-            move |block, socket, endpoints| {
-                let this = JsValue::NULL;
-                let block_bytes = match block.to_bytes() {
-                    Ok(bytes) => js_sys::Uint8Array::from(&bytes[..]),
-                    Err(e) => {
-                        error!("Failed to convert block to bytes: {:?}", e);
-                        return;
-                    }
-                };
-                let socket_uuid = JsValue::from_str(&socket.0.to_string());
-                let endpoints_array = js_sys::Array::new();
-                for endpoint in endpoints {
-                    endpoints_array
-                        .push(&JsValue::from_str(&endpoint.to_string()));
-                }
-                if let Err(e) = callback.call3(
-                    &this,
-                    &JsValue::from(block_bytes),
-                    &socket_uuid,
-                    &endpoints_array,
-                ) {
-                    error!(
-                        "Error in outgoing block interceptor callback: {:?}",
-                        e
-                    );
-                }
-            },
-        );
-    }
-
-    pub fn register_incoming_block_interceptor(
-        &self,
-        callback: js_sys::Function,
-    ) {
-        self.com_hub().register_incoming_block_interceptor(
-            move |block, socket| {
-                let this = JsValue::NULL;
-                let block_bytes = match block.to_bytes() {
-                    Ok(bytes) => js_sys::Uint8Array::from(&bytes[..]),
-                    Err(e) => {
-                        error!("Failed to convert block to bytes: {:?}", e);
-                        return;
-                    }
-                };
-                let socket_uuid = JsValue::from_str(&socket.0.to_string());
-                if let Err(e) = callback.call2(
-                    &this,
-                    &JsValue::from(block_bytes),
-                    &socket_uuid,
-                ) {
-                    error!(
-                        "Error in incoming block interceptor callback: {:?}",
-                        e
-                    );
-                }
-            },
-        );
-    }
 }
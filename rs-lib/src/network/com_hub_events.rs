@@ -0,0 +1,644 @@
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap};
+use std::rc::{Rc, Weak};
+use std::time::Duration; // FIXME no-std
+
+use datex_core::network::com_hub::ComHub;
+use datex_core::network::com_interfaces::com_interface::{
+    ComInterface, ComInterfaceState, ComInterfaceUUID,
+};
+use datex_core::network::com_interfaces::com_interface_socket::{
+    ComInterfaceSocket, ComInterfaceSocketUUID,
+};
+use datex_core::runtime::Runtime;
+use datex_core::task::spawn_local;
+use js_sys::Function;
+use log::error;
+use serde::Serialize;
+use tsify::Tsify;
+use wasm_bindgen::JsValue;
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::network::block_interceptors::BlockInterceptors;
+use crate::network::com_hub::JSComHub;
+use crate::network::com_hub_metadata::get_com_hub_metadata;
+
+/// Interval in which the interfaces and sockets of the ComHub are
+/// checked for changes that are not notified by the interfaces of this
+/// crate, while there are subscribers
+const EVENT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Lifecycle event of an interface or socket of the ComHub
+#[derive(Serialize, Tsify, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum ComHubEvent {
+    InterfaceAdded {
+        interface_uuid: String,
+        interface_type: String,
+        name: Option<String>,
+        state: String,
+    },
+    InterfaceRemoved {
+        interface_uuid: String,
+    },
+    /// The state of the interface changed, e.g. from "Connected"
+    /// to "NotConnected"
+    InterfaceStateChanged {
+        interface_uuid: String,
+        state: String,
+    },
+    SocketRegistered {
+        interface_uuid: String,
+        socket_uuid: String,
+        direction: String,
+    },
+    SocketClosed {
+        interface_uuid: String,
+        socket_uuid: String,
+    },
+    /// The endpoint of the socket is known, either from the handshake
+    /// of the interface or from the first received block
+    SocketEndpointBound {
+        interface_uuid: String,
+        socket_uuid: String,
+        endpoint: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct SocketSnapshot {
+    direction: String,
+    endpoint: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct InterfaceSnapshot {
+    interface_type: String,
+    name: Option<String>,
+    state: String,
+    sockets: BTreeMap<String, SocketSnapshot>,
+}
+
+/// Interfaces and sockets are ordered by their UUIDs, so that
+/// the events of a change are always emitted in the same order
+type ComHubSnapshot = BTreeMap<String, InterfaceSnapshot>;
+
+fn take_snapshot(
+    com_hub: &ComHub,
+    previous: &ComHubSnapshot,
+) -> ComHubSnapshot {
+    get_com_hub_metadata(com_hub, &HashMap::new())
+        .interfaces
+        .into_iter()
+        .map(|interface| {
+            // the state is not available while the interface is busy,
+            // in which case it is checked again in the next round
+            let state = interface
+                .state
+                .or_else(|| {
+                    previous
+                        .get(&interface.uuid)
+                        .map(|snapshot| snapshot.state.clone())
+                })
+                .unwrap_or_default();
+            let sockets = interface
+                .sockets
+                .into_iter()
+                .map(|socket| {
+                    (
+                        socket.uuid,
+                        SocketSnapshot {
+                            direction: format!("{:?}", socket.direction),
+                            endpoint: socket.endpoint,
+                        },
+                    )
+                })
+                .collect();
+            (
+                interface.uuid,
+                InterfaceSnapshot {
+                    interface_type: interface.properties.interface_type,
+                    name: interface.properties.name,
+                    state,
+                    sockets,
+                },
+            )
+        })
+        .collect()
+}
+
+/// Returns the events that lead from the previous to the current snapshot.
+/// Sockets of removed interfaces are closed before the interface is removed,
+/// sockets of added interfaces are registered after the interface was added
+fn diff_snapshots(
+    previous: &ComHubSnapshot,
+    current: &ComHubSnapshot,
+) -> Vec<ComHubEvent> {
+    let mut events = Vec::new();
+    let empty = BTreeMap::new();
+    for (interface_uuid, interface) in previous {
+        if !current.contains_key(interface_uuid) {
+            for socket_uuid in interface.sockets.keys() {
+                events.push(ComHubEvent::SocketClosed {
+                    interface_uuid: interface_uuid.clone(),
+                    socket_uuid: socket_uuid.clone(),
+                });
+            }
+            events.push(ComHubEvent::InterfaceRemoved {
+                interface_uuid: interface_uuid.clone(),
+            });
+        }
+    }
+    for (interface_uuid, interface) in current {
+        let previous_sockets = match previous.get(interface_uuid) {
+            Some(previous) => {
+                if previous.state != interface.state {
+                    events.push(ComHubEvent::InterfaceStateChanged {
+                        interface_uuid: interface_uuid.clone(),
+                        state: interface.state.clone(),
+                    });
+                }
+                &previous.sockets
+            }
+            None => {
+                events.push(ComHubEvent::InterfaceAdded {
+                    interface_uuid: interface_uuid.clone(),
+                    interface_type: interface.interface_type.clone(),
+                    name: interface.name.clone(),
+                    state: interface.state.clone(),
+                });
+                &empty
+            }
+        };
+        for socket_uuid in previous_sockets.keys() {
+            if !interface.sockets.contains_key(socket_uuid) {
+                events.push(ComHubEvent::SocketClosed {
+                    interface_uuid: interface_uuid.clone(),
+                    socket_uuid: socket_uuid.clone(),
+                });
+            }
+        }
+        for (socket_uuid, socket) in &interface.sockets {
+            let previous_socket = previous_sockets.get(socket_uuid);
+            if previous_socket.is_none() {
+                events.push(ComHubEvent::SocketRegistered {
+                    interface_uuid: interface_uuid.clone(),
+                    socket_uuid: socket_uuid.clone(),
+                    direction: socket.direction.clone(),
+                });
+            }
+            let previous_endpoint =
+                previous_socket.and_then(|socket| socket.endpoint.as_ref());
+            if let Some(endpoint) = &socket.endpoint
+                && previous_endpoint != Some(endpoint)
+            {
+                events.push(ComHubEvent::SocketEndpointBound {
+                    interface_uuid: interface_uuid.clone(),
+                    socket_uuid: socket_uuid.clone(),
+                    endpoint: endpoint.clone(),
+                });
+            }
+        }
+    }
+    events
+}
+
+fn call_subscriber(callback: &Function, event: &ComHubEvent) {
+    let event = match serde_wasm_bindgen::to_value(event) {
+        Ok(event) => event,
+        Err(e) => {
+            error!("Failed to convert ComHub event: {e:?}");
+            return;
+        }
+    };
+    if let Err(e) = callback.call1(&JsValue::NULL, &event) {
+        error!("Error in ComHub event callback: {e:?}");
+    }
+}
+
+/// Change of an interface or socket, notified by the interfaces of
+/// this crate where the change is made
+#[derive(Debug, Clone)]
+enum Notification {
+    StateChanged {
+        interface_uuid: ComInterfaceUUID,
+        state: String,
+    },
+    SocketRegistered {
+        interface_uuid: ComInterfaceUUID,
+        socket_uuid: ComInterfaceSocketUUID,
+        direction: String,
+    },
+    SocketClosed {
+        interface_uuid: ComInterfaceUUID,
+        socket_uuid: ComInterfaceSocketUUID,
+    },
+}
+
+impl Notification {
+    fn interface_uuid(&self) -> &ComInterfaceUUID {
+        match self {
+            Notification::StateChanged { interface_uuid, .. }
+            | Notification::SocketRegistered { interface_uuid, .. }
+            | Notification::SocketClosed { interface_uuid, .. } => {
+                interface_uuid
+            }
+        }
+    }
+
+    /// Applies the change to the snapshot, returns the event if the
+    /// snapshot changed. Changes of interfaces that are not part of the
+    /// snapshot yet are emitted by the next check
+    fn apply(self, snapshot: &mut ComHubSnapshot) -> Option<ComHubEvent> {
+        let interface_uuid = self.interface_uuid().0.to_string();
+        let interface = snapshot.get_mut(&interface_uuid)?;
+        match self {
+            Notification::StateChanged { state, .. } => {
+                if interface.state == state {
+                    return None;
+                }
+                interface.state = state.clone();
+                Some(ComHubEvent::InterfaceStateChanged {
+                    interface_uuid,
+                    state,
+                })
+            }
+            Notification::SocketRegistered {
+                socket_uuid,
+                direction,
+                ..
+            } => {
+                let socket_uuid = socket_uuid.0.to_string();
+                if interface.sockets.contains_key(&socket_uuid) {
+                    return None;
+                }
+                interface.sockets.insert(
+                    socket_uuid.clone(),
+                    SocketSnapshot {
+                        direction: direction.clone(),
+                        endpoint: None,
+                    },
+                );
+                Some(ComHubEvent::SocketRegistered {
+                    interface_uuid,
+                    socket_uuid,
+                    direction,
+                })
+            }
+            Notification::SocketClosed { socket_uuid, .. } => {
+                let socket_uuid = socket_uuid.0.to_string();
+                interface.sockets.remove(&socket_uuid)?;
+                Some(ComHubEvent::SocketClosed {
+                    interface_uuid,
+                    socket_uuid,
+                })
+            }
+        }
+    }
+}
+
+thread_local! {
+    /// Events of all ComHubs, owned by their JSComHub
+    static COM_HUB_EVENTS: RefCell<Vec<Weak<ComHubEventsInner>>> =
+        RefCell::new(Vec::new());
+    /// Notifications that are not yet passed to the events of their ComHub.
+    /// The interfaces notify changes while their sockets may be locked, so
+    /// the subscribers are called afterwards
+    static PENDING_NOTIFICATIONS: RefCell<Vec<Notification>> =
+        RefCell::new(Vec::new());
+}
+
+/// Returns a function that sets the state of the interface and notifies
+/// the subscribers of the ComHub, for the connections of the interfaces
+/// that change the state from their event callbacks
+pub(crate) fn state_setter(
+    interface: &impl ComInterface,
+) -> Rc<dyn Fn(ComInterfaceState)> {
+    let state = interface.get_info().state.clone();
+    let interface_uuid = interface.get_uuid().clone();
+    Rc::new(move |new_state| {
+        notify_state_changed(&interface_uuid, &new_state);
+        state.lock().unwrap().set(new_state)
+    })
+}
+
+/// Notifies the subscribers of the ComHub that the state of the
+/// interface changed, called by the interfaces of this crate
+pub(crate) fn notify_state_changed(
+    interface_uuid: &ComInterfaceUUID,
+    state: &ComInterfaceState,
+) {
+    notify(Notification::StateChanged {
+        interface_uuid: interface_uuid.clone(),
+        state: format!("{state:?}"),
+    });
+}
+
+/// Notifies the subscribers of the ComHub that the socket was added
+/// to its interface, called by the interfaces of this crate
+pub(crate) fn notify_socket_registered(socket: &ComInterfaceSocket) {
+    notify(Notification::SocketRegistered {
+        interface_uuid: socket.interface_uuid.clone(),
+        socket_uuid: socket.uuid.clone(),
+        direction: format!("{:?}", socket.direction),
+    });
+}
+
+/// Notifies the subscribers of the ComHub that the socket was removed
+/// from its interface, called by the interfaces of this crate
+pub(crate) fn notify_socket_closed(
+    interface_uuid: &ComInterfaceUUID,
+    socket_uuid: &ComInterfaceSocketUUID,
+) {
+    BlockInterceptors::remove_socket(socket_uuid);
+    notify(Notification::SocketClosed {
+        interface_uuid: interface_uuid.clone(),
+        socket_uuid: socket_uuid.clone(),
+    });
+}
+
+fn notify(notification: Notification) {
+    let is_first = PENDING_NOTIFICATIONS.with(|pending| {
+        let mut pending = pending.borrow_mut();
+        pending.push(notification);
+        pending.len() == 1
+    });
+    if is_first {
+        spawn_local(async { flush_notifications() });
+    }
+}
+
+/// Passes the pending notifications to the events of their ComHub
+/// in the order in which they were notified
+fn flush_notifications() {
+    let notifications = PENDING_NOTIFICATIONS
+        .with(|pending| std::mem::take(&mut *pending.borrow_mut()));
+    if notifications.is_empty() {
+        return;
+    }
+    let com_hubs = COM_HUB_EVENTS.with(|com_hubs| {
+        com_hubs
+            .borrow()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|inner| ComHubEvents { inner })
+            .collect::<Vec<_>>()
+    });
+    for notification in notifications {
+        let interface_uuid = notification.interface_uuid();
+        if let Some(events) = com_hubs.iter().find(|events| {
+            events.inner.runtime.com_hub().has_interface(interface_uuid)
+        }) {
+            events.apply(notification);
+        }
+    }
+}
+
+/// Subscribers of the ComHub lifecycle events
+#[derive(Clone)]
+pub struct ComHubEvents {
+    inner: Rc<ComHubEventsInner>,
+}
+
+struct ComHubEventsInner {
+    runtime: Runtime,
+    subscribers: RefCell<BTreeMap<u32, Function>>,
+    next_subscriber_id: Cell<u32>,
+    is_polling: Cell<bool>,
+    /// State of the ComHub after the last emitted events
+    snapshot: RefCell<ComHubSnapshot>,
+}
+
+impl ComHubEvents {
+    pub fn new(runtime: Runtime) -> ComHubEvents {
+        let inner = Rc::new(ComHubEventsInner {
+            runtime,
+            subscribers: RefCell::new(BTreeMap::new()),
+            next_subscriber_id: Cell::new(0),
+            is_polling: Cell::new(false),
+            snapshot: RefCell::new(ComHubSnapshot::new()),
+        });
+        COM_HUB_EVENTS.with(|com_hubs| {
+            let mut com_hubs = com_hubs.borrow_mut();
+            com_hubs.retain(|com_hub| com_hub.strong_count() > 0);
+            com_hubs.push(Rc::downgrade(&inner));
+        });
+        ComHubEvents { inner }
+    }
+
+    /// Adds the subscriber and passes the existing interfaces and sockets
+    /// to it as added and registered events
+    fn subscribe(&self, callback: Function) -> u32 {
+        let com_hub = self.inner.runtime.com_hub();
+        if self.inner.is_polling.replace(true) {
+            self.check(com_hub);
+        } else {
+            self.inner
+                .snapshot
+                .replace(take_snapshot(com_hub, &BTreeMap::new()));
+            self.poll();
+        }
+        let initial_events =
+            diff_snapshots(&BTreeMap::new(), &self.inner.snapshot.borrow());
+        for event in &initial_events {
+            call_subscriber(&callback, event);
+        }
+        let id = self.inner.next_subscriber_id.get();
+        self.inner.next_subscriber_id.set(id.wrapping_add(1));
+        self.inner.subscribers.borrow_mut().insert(id, callback);
+        id
+    }
+
+    fn unsubscribe(&self, id: u32) -> bool {
+        self.inner.subscribers.borrow_mut().remove(&id).is_some()
+    }
+
+    fn emit(&self, event: &ComHubEvent) {
+        // subscribers may unsubscribe in the callback
+        let subscribers = self
+            .inner
+            .subscribers
+            .borrow()
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+        for callback in subscribers {
+            call_subscriber(&callback, event);
+        }
+    }
+
+    /// Emits the change that was notified by an interface
+    fn apply(&self, notification: Notification) {
+        if !self.inner.is_polling.get() {
+            return;
+        }
+        let event = notification.apply(&mut self.inner.snapshot.borrow_mut());
+        if let Some(event) = event {
+            self.emit(&event);
+        }
+    }
+
+    /// Emits the changes since the last check. Called by the ComHub
+    /// right after it added or removed an interface. Pending notifications
+    /// are emitted first, so that the events keep their order
+    pub(crate) fn check(&self, com_hub: &ComHub) {
+        if !self.inner.is_polling.get() {
+            return;
+        }
+        flush_notifications();
+        let current = take_snapshot(com_hub, &self.inner.snapshot.borrow());
+        let previous = self.inner.snapshot.replace(current);
+        for event in diff_snapshots(&previous, &self.inner.snapshot.borrow()) {
+            self.emit(&event);
+        }
+    }
+
+    /// Changes of the interfaces and sockets that are made by the DATEX
+    /// core instead of the interfaces of this crate (e.g. the sockets of
+    /// matchbox interfaces or endpoints that are bound when a block is
+    /// received) are detected by comparing snapshots of the ComHub
+    /// metadata until the last subscriber is removed
+    fn poll(&self) {
+        let events = self.clone();
+        spawn_local(async move {
+            loop {
+                futures_timer::Delay::new(EVENT_POLL_INTERVAL).await;
+                if events.inner.subscribers.borrow().is_empty() {
+                    events.inner.is_polling.set(false);
+                    break;
+                }
+                events.check(events.inner.runtime.com_hub());
+            }
+        });
+    }
+}
+
+#[wasm_bindgen]
+impl JSComHub {
+    /// Subscribes to the lifecycle events of interfaces and sockets,
+    /// returns the id of the subscription. The existing interfaces and
+    /// sockets are passed to the callback as added and registered events
+    pub fn subscribe_events(&self, callback: Function) -> u32 {
+        self.events.subscribe(callback)
+    }
+
+    /// Removes the subscription with the given id, returns false
+    /// if there is no such subscription
+    pub fn unsubscribe_events(&self, subscription_id: u32) -> bool {
+        self.events.unsubscribe(subscription_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interface(
+        state: &str,
+        sockets: &[(&str, Option<&str>)],
+    ) -> InterfaceSnapshot {
+        InterfaceSnapshot {
+            interface_type: "base".to_string(),
+            name: None,
+            state: state.to_string(),
+            sockets: sockets
+                .iter()
+                .map(|(uuid, endpoint)| {
+                    (
+                        uuid.to_string(),
+                        SocketSnapshot {
+                            direction: "InOut".to_string(),
+                            endpoint: endpoint.map(str::to_string),
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn interface_added_and_removed() {
+        let empty = ComHubSnapshot::new();
+        let added = ComHubSnapshot::from([(
+            "a".to_string(),
+            interface("Connected", &[("s", None)]),
+        )]);
+        assert_eq!(
+            diff_snapshots(&empty, &added),
+            vec![
+                ComHubEvent::InterfaceAdded {
+                    interface_uuid: "a".to_string(),
+                    interface_type: "base".to_string(),
+                    name: None,
+                    state: "Connected".to_string(),
+                },
+                ComHubEvent::SocketRegistered {
+                    interface_uuid: "a".to_string(),
+                    socket_uuid: "s".to_string(),
+                    direction: "InOut".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            diff_snapshots(&added, &empty),
+            vec![
+                ComHubEvent::SocketClosed {
+                    interface_uuid: "a".to_string(),
+                    socket_uuid: "s".to_string(),
+                },
+                ComHubEvent::InterfaceRemoved {
+                    interface_uuid: "a".to_string(),
+                },
+            ]
+        );
+        assert!(diff_snapshots(&added, &added).is_empty());
+    }
+
+    #[test]
+    fn events_are_ordered_by_uuid() {
+        let current = ["c", "a", "b"]
+            .into_iter()
+            .map(|uuid| (uuid.to_string(), interface("Connected", &[])))
+            .collect::<ComHubSnapshot>();
+        let added = diff_snapshots(&ComHubSnapshot::new(), &current)
+            .into_iter()
+            .map(|event| match event {
+                ComHubEvent::InterfaceAdded { interface_uuid, .. } => {
+                    interface_uuid
+                }
+                event => panic!("Unexpected event {event:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(added, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn state_and_endpoint_changes() {
+        let previous = ComHubSnapshot::from([(
+            "a".to_string(),
+            interface("Connected", &[("s", None), ("t", Some("@a"))]),
+        )]);
+        let current = ComHubSnapshot::from([(
+            "a".to_string(),
+            interface("NotConnected", &[("s", Some("@b"))]),
+        )]);
+        assert_eq!(
+            diff_snapshots(&previous, &current),
+            vec![
+                ComHubEvent::InterfaceStateChanged {
+                    interface_uuid: "a".to_string(),
+                    state: "NotConnected".to_string(),
+                },
+                ComHubEvent::SocketClosed {
+                    interface_uuid: "a".to_string(),
+                    socket_uuid: "t".to_string(),
+                },
+                ComHubEvent::SocketEndpointBound {
+                    interface_uuid: "a".to_string(),
+                    socket_uuid: "s".to_string(),
+                    endpoint: "@b".to_string(),
+                },
+            ]
+        );
+    }
+}
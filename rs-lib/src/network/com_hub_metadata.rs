@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use datex_core::network::com_hub::ComHub;
+use datex_core::network::com_interfaces::com_interface::{
+    ComInterface, ComInterfaceUUID,
+};
+use datex_core::network::com_interfaces::com_interface_properties::{
+    InterfaceDirection, InterfaceProperties,
+};
+use serde::Serialize;
+use serde_wasm_bindgen::to_value;
+use tsify::Tsify;
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::{JsError, JsValue};
+
+use crate::js_utils::js_error;
+use crate::network::com_hub::JSComHub;
+
+/// Socket of an interface in the ComHub metadata
+#[derive(Serialize, Tsify, Debug, Clone)]
+pub struct JSSocketMetadata {
+    pub uuid: String,
+    pub direction: InterfaceDirection,
+    /// Endpoint that is connected via the socket, if already known
+    pub endpoint: Option<String>,
+}
+
+/// Interface in the ComHub metadata
+#[derive(Serialize, Tsify, Debug, Clone)]
+pub struct JSInterfaceMetadata {
+    pub uuid: String,
+    /// State of the interface, e.g. "Connected". Not available while
+    /// the interface is busy sending a block
+    pub state: Option<String>,
+    /// Priority for fallback routing that was passed to create_interface
+    pub priority: Option<u16>,
+    pub properties: InterfaceProperties,
+    pub sockets: Vec<JSSocketMetadata>,
+}
+
+/// Interfaces and sockets of the ComHub
+#[derive(Serialize, Tsify, Debug, Clone)]
+pub struct JSComHubMetadata {
+    /// Endpoint of the runtime
+    pub endpoint: String,
+    pub interfaces: Vec<JSInterfaceMetadata>,
+}
+
+/// Collects the metadata of all interfaces of the ComHub
+pub(crate) fn get_com_hub_metadata(
+    com_hub: &ComHub,
+    priorities: &HashMap<ComInterfaceUUID, u16>,
+) -> JSComHubMetadata {
+    let metadata = com_hub.get_metadata();
+    let interfaces = metadata
+        .interfaces
+        .into_iter()
+        .map(|interface| {
+            let uuid = ComInterfaceUUID::from_string(interface.uuid.clone());
+            let state = com_hub.get_dyn_interface_by_uuid(&uuid).and_then(
+                |interface| {
+                    interface
+                        .try_borrow()
+                        .ok()
+                        .map(|interface| format!("{:?}", interface.get_state()))
+                },
+            );
+            JSInterfaceMetadata {
+                priority: priorities.get(&uuid).copied(),
+                uuid: interface.uuid,
+                state,
+                properties: interface.properties,
+                sockets: interface
+                    .sockets
+                    .into_iter()
+                    .map(|socket| JSSocketMetadata {
+                        uuid: socket.uuid,
+                        direction: socket.direction,
+                        endpoint: socket
+                            .endpoint
+                            .map(|endpoint| endpoint.to_string()),
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+    JSComHubMetadata {
+        endpoint: metadata.endpoint.to_string(),
+        interfaces,
+    }
+}
+
+#[wasm_bindgen]
+impl JSComHub {
+    /// Returns the interfaces and sockets of the ComHub
+    /// with their properties
+    pub fn get_metadata(&self) -> Result<JsValue, JsError> {
+        let metadata = get_com_hub_metadata(
+            self.com_hub(),
+            &self.interface_priorities.borrow(),
+        );
+        to_value(&metadata).map_err(js_error)
+    }
+}
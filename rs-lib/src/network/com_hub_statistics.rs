@@ -0,0 +1,349 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use datex_core::network::com_hub::ComHub;
+use datex_core::network::com_interfaces::com_interface::{
+    ComInterface, ComInterfaceUUID,
+};
+use datex_core::network::com_interfaces::com_interface_socket::ComInterfaceSocketUUID;
+use serde::Serialize;
+use serde_wasm_bindgen::to_value;
+use tsify::Tsify;
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::{JsError, JsValue};
+use web_sys::js_sys;
+
+use crate::js_utils::js_error;
+use crate::network::com_hub::JSComHub;
+use crate::network::com_hub_metadata::get_com_hub_metadata;
+
+/// Number of seconds over which the rolling rates are averaged
+const RATE_WINDOW_SECONDS: usize = 10;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct RateBucket {
+    second: u64,
+    blocks: u64,
+    bytes: u64,
+}
+
+/// Blocks and bytes per second, averaged over the last
+/// [RATE_WINDOW_SECONDS] seconds
+#[derive(Debug, Clone, Default)]
+struct RollingRate {
+    buckets: [RateBucket; RATE_WINDOW_SECONDS],
+}
+
+impl RollingRate {
+    fn record(&mut self, now: f64, bytes: usize) {
+        let second = (now / 1000.0) as u64;
+        let bucket = &mut self.buckets[second as usize % RATE_WINDOW_SECONDS];
+        if bucket.second != second {
+            *bucket = RateBucket {
+                second,
+                ..RateBucket::default()
+            };
+        }
+        bucket.blocks += 1;
+        bucket.bytes += bytes as u64;
+    }
+
+    /// Returns the blocks and bytes per second
+    fn rate(&self, now: f64) -> (f64, f64) {
+        let second = (now / 1000.0) as u64;
+        let (blocks, bytes) = self
+            .buckets
+            .iter()
+            .filter(|bucket| {
+                bucket.second <= second
+                    && second - bucket.second < RATE_WINDOW_SECONDS as u64
+            })
+            .fold((0, 0), |(blocks, bytes), bucket| {
+                (blocks + bucket.blocks, bytes + bucket.bytes)
+            });
+        let window = RATE_WINDOW_SECONDS as f64;
+        (blocks as f64 / window, bytes as f64 / window)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct SocketTraffic {
+    blocks_sent: u64,
+    bytes_sent: u64,
+    blocks_received: u64,
+    bytes_received: u64,
+    failed_sends: u64,
+    sent_rate: RollingRate,
+    received_rate: RollingRate,
+}
+
+/// Traffic of an interface or socket since it was created
+#[derive(Serialize, Tsify, Debug, Clone, Default, PartialEq)]
+pub struct JSTrafficStatistics {
+    pub blocks_sent: u64,
+    pub bytes_sent: u64,
+    pub blocks_received: u64,
+    pub bytes_received: u64,
+    /// Blocks that the interface failed to send
+    pub failed_sends: u64,
+    /// Received bytes that were not yet processed by the ComHub, not set
+    /// if the receive queues could not be read because the interface
+    /// was busy
+    pub queue_depth: Option<usize>,
+    pub sent_blocks_per_second: f64,
+    pub sent_bytes_per_second: f64,
+    pub received_blocks_per_second: f64,
+    pub received_bytes_per_second: f64,
+}
+
+impl JSTrafficStatistics {
+    fn new(
+        traffic: &SocketTraffic,
+        queue_depth: Option<usize>,
+        now: f64,
+    ) -> JSTrafficStatistics {
+        let (sent_blocks_per_second, sent_bytes_per_second) =
+            traffic.sent_rate.rate(now);
+        let (received_blocks_per_second, received_bytes_per_second) =
+            traffic.received_rate.rate(now);
+        JSTrafficStatistics {
+            blocks_sent: traffic.blocks_sent,
+            bytes_sent: traffic.bytes_sent,
+            blocks_received: traffic.blocks_received,
+            bytes_received: traffic.bytes_received,
+            failed_sends: traffic.failed_sends,
+            queue_depth,
+            sent_blocks_per_second,
+            sent_bytes_per_second,
+            received_blocks_per_second,
+            received_bytes_per_second,
+        }
+    }
+
+    fn add(&mut self, other: &JSTrafficStatistics) {
+        self.blocks_sent += other.blocks_sent;
+        self.bytes_sent += other.bytes_sent;
+        self.blocks_received += other.blocks_received;
+        self.bytes_received += other.bytes_received;
+        self.failed_sends += other.failed_sends;
+        self.queue_depth = self
+            .queue_depth
+            .zip(other.queue_depth)
+            .map(|(queue_depth, other)| queue_depth + other);
+        self.sent_blocks_per_second += other.sent_blocks_per_second;
+        self.sent_bytes_per_second += other.sent_bytes_per_second;
+        self.received_blocks_per_second += other.received_blocks_per_second;
+        self.received_bytes_per_second += other.received_bytes_per_second;
+    }
+}
+
+/// Traffic of a socket in the ComHub statistics
+#[derive(Serialize, Tsify, Debug, Clone)]
+pub struct JSSocketStatistics {
+    pub uuid: String,
+    /// Endpoint that is connected via the socket, if already known
+    pub endpoint: Option<String>,
+    pub traffic: JSTrafficStatistics,
+}
+
+/// Traffic of an interface in the ComHub statistics, which is the sum
+/// of the traffic of its sockets
+#[derive(Serialize, Tsify, Debug, Clone)]
+pub struct JSInterfaceStatistics {
+    pub uuid: String,
+    pub interface_type: String,
+    pub name: Option<String>,
+    pub traffic: JSTrafficStatistics,
+    pub sockets: Vec<JSSocketStatistics>,
+}
+
+/// Traffic of all interfaces and sockets of the ComHub
+#[derive(Serialize, Tsify, Debug, Clone)]
+pub struct JSComHubStatistics {
+    pub interfaces: Vec<JSInterfaceStatistics>,
+}
+
+/// Traffic of the sockets of a ComHub, counted when the interfaces hand
+/// a block to their transport or add it to the receive queue
+#[derive(Clone, Default)]
+pub struct TrafficStatistics {
+    sockets: Rc<RefCell<HashMap<ComInterfaceSocketUUID, SocketTraffic>>>,
+}
+
+impl TrafficStatistics {
+    pub(crate) fn record_sent(
+        &self,
+        socket_uuid: &ComInterfaceSocketUUID,
+        size: usize,
+        success: bool,
+    ) {
+        let mut sockets = self.sockets.borrow_mut();
+        let traffic = sockets.entry(socket_uuid.clone()).or_default();
+        if !success {
+            traffic.failed_sends += 1;
+            return;
+        }
+        traffic.blocks_sent += 1;
+        traffic.bytes_sent += size as u64;
+        traffic.sent_rate.record(js_sys::Date::now(), size);
+    }
+
+    pub(crate) fn record_received(
+        &self,
+        socket_uuid: &ComInterfaceSocketUUID,
+        size: usize,
+    ) {
+        let mut sockets = self.sockets.borrow_mut();
+        let traffic = sockets.entry(socket_uuid.clone()).or_default();
+        traffic.blocks_received += 1;
+        traffic.bytes_received += size as u64;
+        traffic.received_rate.record(js_sys::Date::now(), size);
+    }
+
+    pub(crate) fn remove_socket(&self, socket_uuid: &ComInterfaceSocketUUID) {
+        self.sockets.borrow_mut().remove(socket_uuid);
+    }
+
+    /// Removes the traffic of sockets that no longer exist. Sockets of
+    /// the interfaces of the DATEX core are not removed when they are
+    /// closed, since their removal is not notified
+    fn remove_closed_sockets(&self, sockets: &HashSet<ComInterfaceSocketUUID>) {
+        let mut traffic = self.sockets.borrow_mut();
+        traffic.retain(|uuid, _| sockets.contains(uuid));
+    }
+}
+
+/// Returns the number of received bytes that were not yet processed
+/// for each socket of the interface, or None if the interface is busy
+fn get_queue_depths(
+    com_hub: &ComHub,
+    interface_uuid: &ComInterfaceUUID,
+) -> Option<HashMap<ComInterfaceSocketUUID, usize>> {
+    let interface = com_hub.get_dyn_interface_by_uuid(interface_uuid)?;
+    // the interface is borrowed while it is sending a block
+    let interface = interface.try_borrow().ok()?;
+    let sockets = interface.get_sockets();
+    let sockets = sockets.lock().unwrap();
+    let queue_depths = sockets
+        .sockets
+        .iter()
+        .map(|(uuid, socket)| {
+            let receive_queue = socket.lock().unwrap().receive_queue.clone();
+            let queue_depth = receive_queue.lock().unwrap().len();
+            (uuid.clone(), queue_depth)
+        })
+        .collect();
+    Some(queue_depths)
+}
+
+#[wasm_bindgen]
+impl JSComHub {
+    /// Returns the number of blocks and bytes that were sent and received
+    /// by each interface and socket, the failed sends, the receive queue
+    /// depth and the rates over the last 10 seconds.
+    /// The blocks of matchbox interfaces are counted as sent when they are
+    /// handed to the interface, failed sends are not counted for them
+    pub fn get_traffic_statistics(&self) -> Result<JsValue, JsError> {
+        let com_hub = self.com_hub();
+        let now = js_sys::Date::now();
+        let mut socket_uuids = HashSet::new();
+        let traffic = self.traffic.sockets.borrow();
+        let interfaces = get_com_hub_metadata(com_hub, &HashMap::new())
+            .interfaces
+            .into_iter()
+            .map(|interface| {
+                let queue_depths = get_queue_depths(
+                    com_hub,
+                    &ComInterfaceUUID::from_string(interface.uuid.clone()),
+                );
+                let sockets = interface
+                    .sockets
+                    .into_iter()
+                    .map(|socket| {
+                        let uuid =
+                            ComInterfaceSocketUUID::from_string(socket.uuid);
+                        let statistics = JSSocketStatistics {
+                            uuid: uuid.0.to_string(),
+                            endpoint: socket.endpoint,
+                            traffic: JSTrafficStatistics::new(
+                                &traffic
+                                    .get(&uuid)
+                                    .cloned()
+                                    .unwrap_or_default(),
+                                queue_depths.as_ref().map(|queue_depths| {
+                                    queue_depths
+                                        .get(&uuid)
+                                        .copied()
+                                        .unwrap_or_default()
+                                }),
+                                now,
+                            ),
+                        };
+                        socket_uuids.insert(uuid);
+                        statistics
+                    })
+                    .collect::<Vec<_>>();
+                let mut total = JSTrafficStatistics {
+                    queue_depth: Some(0),
+                    ..JSTrafficStatistics::default()
+                };
+                for socket in &sockets {
+                    total.add(&socket.traffic);
+                }
+                JSInterfaceStatistics {
+                    uuid: interface.uuid,
+                    interface_type: interface.properties.interface_type,
+                    name: interface.properties.name,
+                    traffic: total,
+                    sockets,
+                }
+            })
+            .collect();
+        drop(traffic);
+        self.traffic.remove_closed_sockets(&socket_uuids);
+        to_value(&JSComHubStatistics { interfaces }).map_err(js_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rolling_rate() {
+        let mut rate = RollingRate::default();
+        let start = 1_000_000.0;
+        rate.record(start, 100);
+        rate.record(start + 500.0, 100);
+        rate.record(start + 3000.0, 300);
+        assert_eq!(rate.rate(start + 3000.0), (0.3, 50.0));
+        // the first second is no longer part of the window
+        assert_eq!(rate.rate(start + 10_500.0), (0.1, 30.0));
+        assert_eq!(rate.rate(start + 20_000.0), (0.0, 0.0));
+    }
+
+    #[test]
+    fn queue_depth_is_unknown_if_a_socket_is_unknown() {
+        let socket = |queue_depth| JSTrafficStatistics {
+            queue_depth,
+            ..JSTrafficStatistics::default()
+        };
+        let mut total = socket(Some(0));
+        total.add(&socket(Some(3)));
+        total.add(&socket(Some(4)));
+        assert_eq!(total.queue_depth, Some(7));
+        total.add(&socket(None));
+        total.add(&socket(Some(1)));
+        assert_eq!(total.queue_depth, None);
+    }
+
+    #[test]
+    fn rolling_rate_reuses_buckets() {
+        let mut rate = RollingRate::default();
+        let start = 1_000_000.0;
+        rate.record(start, 100);
+        rate.record(start + 10_000.0, 200);
+        assert_eq!(rate.rate(start + 10_000.0), (0.1, 20.0));
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::pin;
+use std::str::FromStr;
+use std::time::Duration; // FIXME no-std
+
+use datex_core::values::core_values::endpoint::Endpoint;
+use futures::future::{Either, join_all, select};
+use serde::Serialize;
+use serde_wasm_bindgen::to_value;
+use tsify::Tsify;
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::{JsError, JsValue};
+use web_sys::js_sys;
+
+use crate::js_utils::js_error;
+use crate::network::com_hub::JSComHub;
+
+/// Time after which a ping or trace is cancelled if no timeout is given
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Result of a ping to an endpoint
+#[derive(Serialize, Tsify, Debug, Clone)]
+pub struct JSPingResult {
+    pub endpoint: String,
+    /// Round trip time in milliseconds
+    pub round_trip_time: f64,
+}
+
+/// Hop of a trace, recorded by each endpoint that the trace block passed
+#[derive(Serialize, Tsify, Debug, Clone)]
+pub struct JSTraceHop {
+    /// Endpoint that recorded the hop
+    pub endpoint: String,
+    pub distance: i8,
+    /// "Outgoing" or "Incoming"
+    pub direction: String,
+    pub interface_type: String,
+    pub interface_name: Option<String>,
+    pub channel: String,
+    pub socket_uuid: String,
+    /// Round trip time to the endpoint of the hop in milliseconds,
+    /// not available if the endpoint did not answer a ping in time
+    pub latency: Option<f64>,
+}
+
+/// Route of a trace block to an endpoint and back
+#[derive(Serialize, Tsify, Debug, Clone)]
+pub struct JSTraceResult {
+    pub sender: String,
+    pub receiver: String,
+    /// Round trip time in milliseconds
+    pub round_trip_time: f64,
+    pub hops: Vec<JSTraceHop>,
+}
+
+/// Returns None if the future did not resolve before the timeout
+async fn with_timeout<T>(
+    future: impl Future<Output = T>,
+    timeout: Duration,
+) -> Option<T> {
+    match select(pin!(future), futures_timer::Delay::new(timeout)).await {
+        Either::Left((result, _)) => Some(result),
+        Either::Right(_) => None,
+    }
+}
+
+fn to_millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn parse_endpoint(endpoint: &str) -> Result<Endpoint, JsError> {
+    Endpoint::from_str(endpoint)
+        .map_err(|_| JsError::new(&format!("Invalid endpoint {endpoint}")))
+}
+
+impl JSComHub {
+    async fn ping_endpoint(
+        &self,
+        endpoint: Endpoint,
+        timeout: Duration,
+    ) -> Result<Duration, String> {
+        match with_timeout(
+            self.com_hub().record_trace(endpoint.clone()),
+            timeout,
+        )
+        .await
+        {
+            Some(Some(trace)) => Ok(trace.round_trip_time),
+            Some(None) => Err(format!("No response from {endpoint}")),
+            None => Err(format!("Ping to {endpoint} timed out")),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl JSComHub {
+    /// Sends a trace block to the endpoint and returns the round trip time.
+    /// The timeout is given in milliseconds (default: 5000)
+    pub async fn ping(
+        &self,
+        endpoint: String,
+        timeout: Option<u32>,
+    ) -> Result<JsValue, JsError> {
+        let timeout = timeout
+            .map(|timeout| Duration::from_millis(timeout as u64))
+            .unwrap_or(DEFAULT_TIMEOUT);
+        let round_trip_time = self
+            .ping_endpoint(parse_endpoint(&endpoint)?, timeout)
+            .await
+            .map_err(|e| JsError::new(&e))?;
+        to_value(&JSPingResult {
+            endpoint,
+            round_trip_time: to_millis(round_trip_time),
+        })
+        .map_err(js_error)
+    }
+
+    /// Records the route of a trace block to the endpoint and back.
+    /// The latency of each hop is measured with a ping to the endpoint
+    /// of the hop, like in a traceroute. The hops are pinged concurrently.
+    /// The timeout is given in milliseconds and applies to the trace and
+    /// the pings together (default: 5000)
+    pub async fn trace(
+        &self,
+        endpoint: String,
+        timeout: Option<u32>,
+    ) -> Result<JsValue, JsError> {
+        let timeout = timeout
+            .map(|timeout| Duration::from_millis(timeout as u64))
+            .unwrap_or(DEFAULT_TIMEOUT);
+        let receiver = parse_endpoint(&endpoint)?;
+        let start = js_sys::Date::now();
+        let trace = match with_timeout(
+            self.com_hub().record_trace(receiver.clone()),
+            timeout,
+        )
+        .await
+        {
+            Some(Some(trace)) => trace,
+            Some(None) => {
+                let message = format!("No response from {endpoint}");
+                return Err(JsError::new(&message));
+            }
+            None => {
+                let message = format!("Trace to {endpoint} timed out");
+                return Err(JsError::new(&message));
+            }
+        };
+
+        // each endpoint is only pinged once, even if it recorded
+        // multiple hops, and all pings share the rest of the timeout
+        let own_endpoint = self.endpoint();
+        let mut latencies = HashMap::from([
+            (own_endpoint, Some(0.0)),
+            (receiver, Some(to_millis(trace.round_trip_time))),
+        ]);
+        let hop_endpoints = trace
+            .hops
+            .iter()
+            .map(|hop| hop.endpoint.clone())
+            .filter(|endpoint| !latencies.contains_key(endpoint))
+            .collect::<HashSet<_>>();
+        let elapsed =
+            Duration::from_millis((js_sys::Date::now() - start) as u64);
+        let remaining = timeout.saturating_sub(elapsed);
+        let pings = hop_endpoints.into_iter().map(|endpoint| async move {
+            let latency = self
+                .ping_endpoint(endpoint.clone(), remaining)
+                .await
+                .ok()
+                .map(to_millis);
+            (endpoint, latency)
+        });
+        latencies.extend(join_all(pings).await);
+
+        let hops = trace
+            .hops
+            .iter()
+            .map(|hop| JSTraceHop {
+                endpoint: hop.endpoint.to_string(),
+                distance: hop.distance,
+                direction: format!("{:?}", hop.direction),
+                interface_type: hop.socket.interface_type.clone(),
+                interface_name: hop.socket.interface_name.clone(),
+                channel: hop.socket.channel.clone(),
+                socket_uuid: hop.socket.socket_uuid.clone(),
+                latency: latencies[&hop.endpoint],
+            })
+            .collect();
+        to_value(&JSTraceResult {
+            sender: trace.sender.to_string(),
+            receiver: trace.receiver.to_string(),
+            round_trip_time: to_millis(trace.round_trip_time),
+            hops,
+        })
+        .map_err(js_error)
+    }
+}
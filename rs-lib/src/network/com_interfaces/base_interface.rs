@@ -20,6 +20,11 @@ use log::error;
 use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
 use wasm_bindgen_futures::JsFuture;
 use web_sys::js_sys::{Function, Promise, Uint8Array};
+use crate::intercepted_send_block;
+use crate::network::block_interceptors::receive_block;
+use crate::network::com_hub_events::{
+    notify_socket_closed, notify_socket_registered,
+};
 use crate::wrap_error_for_js;
 use datex_core::network::com_interfaces::com_interface::ComInterfaceState;
 use crate::network::com_hub::JSComHub;
@@ -98,6 +103,7 @@ impl BaseJSInterface {
         let socket =
             ComInterfaceSocket::new(self.get_uuid().clone(), direction, 1);
         let socket_uuid = socket.uuid.clone();
+        notify_socket_registered(&socket);
         let socket = Arc::new(Mutex::new(socket));
         self.add_socket(socket);
         socket_uuid
@@ -128,9 +134,7 @@ impl BaseJSInterface {
     ) -> Result<(), BaseInterfaceError> {
         match self.get_socket_with_uuid(receiver_socket_uuid) {
             Some(socket) => {
-                let socket = socket.lock().unwrap();
-                let receive_queue = socket.get_receive_queue();
-                receive_queue.lock().unwrap().extend(data);
+                receive_block(&socket, &data);
                 Ok(())
             }
             _ => {
@@ -147,8 +151,8 @@ impl MultipleSocketProvider for BaseJSInterface {
     }
 }
 
-impl ComInterface for BaseJSInterface {
-    fn send_block<'a>(
+impl BaseJSInterface {
+    fn send_to_socket<'a>(
         &'a mut self,
         block: &'a [u8],
         socket_uuid: ComInterfaceSocketUUID,
@@ -162,6 +166,10 @@ impl ComInterface for BaseJSInterface {
             Box::pin(async move { false })
         }
     }
+}
+
+impl ComInterface for BaseJSInterface {
+    intercepted_send_block!(send_to_socket);
 
     fn init_properties(&self) -> InterfaceProperties {
         self.properties.clone()
@@ -234,6 +242,10 @@ impl JSComHub {
             .has_socket_with_uuid(socket_uuid.clone())
         {
             base_interface.borrow_mut().remove_socket(&socket_uuid);
+            notify_socket_closed(
+                base_interface.borrow().get_uuid(),
+                &socket_uuid,
+            );
             Ok(())
         } else {
             Err(BaseInterfaceError::SocketNotFound.into())
@@ -0,0 +1,469 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration; // FIXME no-std
+
+use datex_core::crypto::crypto::CryptoTrait;
+use datex_core::network::com_interfaces::com_interface::{
+    ComInterface, ComInterfaceError, ComInterfaceFactory, ComInterfaceInfo,
+    ComInterfaceSockets, ComInterfaceUUID,
+};
+use datex_core::network::com_interfaces::com_interface_properties::{
+    InterfaceDirection, InterfaceProperties,
+};
+use datex_core::network::com_interfaces::com_interface_socket::{
+    ComInterfaceSocket, ComInterfaceSocketUUID,
+};
+use datex_core::network::com_interfaces::socket_provider::MultipleSocketProvider;
+use datex_core::stdlib::sync::Arc;
+use datex_core::values::core_values::endpoint::Endpoint;
+use datex_core::{delegate_com_interface_info, set_sync_opener};
+use datex_macros::{com_interface, create_opener};
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use wasm_bindgen::prelude::{Closure, wasm_bindgen};
+use wasm_bindgen::{JsCast, JsError};
+use web_sys::js_sys::Uint8Array;
+use web_sys::{BroadcastChannel, MessageEvent};
+
+use crate::crypto::crypto_js::CryptoJS;
+use crate::intercepted_send_block;
+use crate::network::block_interceptors::receive_block;
+use crate::network::com_hub::JSComHub;
+use crate::network::com_hub_events::{
+    notify_socket_closed, notify_socket_registered,
+};
+
+/// Name of the channel if no name is configured
+const DEFAULT_CHANNEL_NAME: &str = "datex";
+const PEER_ID_LENGTH: usize = 16;
+
+/// Random id of a BroadcastChannel interface, used to address
+/// the peers on the shared channel
+type PeerId = [u8; PEER_ID_LENGTH];
+
+/// Setup data for an interface that connects all runtimes in the same
+/// origin (e.g. browser tabs or workers) that use the same channel name.
+#[derive(Serialize, Deserialize, Tsify, Debug, Clone)]
+pub struct BroadcastChannelInterfaceSetupData {
+    /// Name of the BroadcastChannel (default: "datex")
+    pub name: Option<String>,
+}
+
+/// Messages posted on the BroadcastChannel. Every peer is announced with
+/// its endpoint, so that each peer can be mapped to its own socket with
+/// a known endpoint before any block is exchanged.
+#[derive(Debug, Clone, PartialEq)]
+enum BroadcastChannelMessage {
+    /// Sent by a peer when it is announced on the channel
+    Join { from: PeerId, endpoint: String },
+    /// Answer of the existing peers to a join message
+    Welcome {
+        from: PeerId,
+        to: PeerId,
+        endpoint: String,
+    },
+    Block {
+        from: PeerId,
+        to: PeerId,
+        data: Vec<u8>,
+    },
+    /// Sent by a peer when its interface is closed
+    Leave { from: PeerId },
+}
+
+impl BroadcastChannelMessage {
+    fn to_bytes(&self) -> Vec<u8> {
+        let (message_type, from, to, payload): (u8, _, _, &[u8]) = match self {
+            BroadcastChannelMessage::Join { from, endpoint } => {
+                (0, from, None, endpoint.as_bytes())
+            }
+            BroadcastChannelMessage::Welcome { from, to, endpoint } => {
+                (1, from, Some(to), endpoint.as_bytes())
+            }
+            BroadcastChannelMessage::Block { from, to, data } => {
+                (2, from, Some(to), data)
+            }
+            BroadcastChannelMessage::Leave { from } => (3, from, None, &[]),
+        };
+        let mut bytes = vec![message_type];
+        bytes.extend_from_slice(from);
+        if let Some(to) = to {
+            bytes.extend_from_slice(to);
+        }
+        bytes.extend_from_slice(payload);
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<BroadcastChannelMessage> {
+        let (message_type, bytes) = bytes.split_first()?;
+        let (from, bytes) = split_peer_id(bytes)?;
+        match message_type {
+            0 => Some(BroadcastChannelMessage::Join {
+                from,
+                endpoint: String::from_utf8(bytes.to_vec()).ok()?,
+            }),
+            1 => {
+                let (to, bytes) = split_peer_id(bytes)?;
+                Some(BroadcastChannelMessage::Welcome {
+                    from,
+                    to,
+                    endpoint: String::from_utf8(bytes.to_vec()).ok()?,
+                })
+            }
+            2 => {
+                let (to, bytes) = split_peer_id(bytes)?;
+                Some(BroadcastChannelMessage::Block {
+                    from,
+                    to,
+                    data: bytes.to_vec(),
+                })
+            }
+            3 if bytes.is_empty() => {
+                Some(BroadcastChannelMessage::Leave { from })
+            }
+            _ => None,
+        }
+    }
+}
+
+fn split_peer_id(bytes: &[u8]) -> Option<(PeerId, &[u8])> {
+    if bytes.len() < PEER_ID_LENGTH {
+        return None;
+    }
+    let (peer_id, rest) = bytes.split_at(PEER_ID_LENGTH);
+    Some((peer_id.try_into().ok()?, rest))
+}
+
+/// State of the channel that is shared with the message listener
+#[derive(Clone)]
+struct BroadcastChannelConnection {
+    id: PeerId,
+    interface_uuid: ComInterfaceUUID,
+    sockets: Arc<Mutex<ComInterfaceSockets>>,
+    channel: BroadcastChannel,
+    /// Own endpoint, set when the interface is announced
+    endpoint: Rc<RefCell<Option<Endpoint>>>,
+    peers: Rc<RefCell<HashMap<PeerId, ComInterfaceSocketUUID>>>,
+}
+
+impl BroadcastChannelConnection {
+    fn post(&self, message: &BroadcastChannelMessage) -> bool {
+        let bytes = Uint8Array::from(message.to_bytes().as_slice());
+        self.channel
+            .post_message(&bytes)
+            .map_err(|e| error!("Error posting message: {e:?}"))
+            .is_ok()
+    }
+
+    fn handle_message(&self, message: BroadcastChannelMessage) {
+        match message {
+            BroadcastChannelMessage::Join { from, endpoint } => {
+                self.add_peer(from, &endpoint);
+                // peers that are not announced yet answer once they
+                // post their own join message
+                if let Some(own_endpoint) = self.endpoint.borrow().as_ref() {
+                    self.post(&BroadcastChannelMessage::Welcome {
+                        from: self.id,
+                        to: from,
+                        endpoint: own_endpoint.to_string(),
+                    });
+                }
+            }
+            BroadcastChannelMessage::Welcome { from, to, endpoint }
+                if to == self.id =>
+            {
+                self.add_peer(from, &endpoint);
+            }
+            BroadcastChannelMessage::Block { from, to, data }
+                if to == self.id =>
+            {
+                self.receive(from, data);
+            }
+            BroadcastChannelMessage::Leave { from } => self.remove_peer(&from),
+            _ => {}
+        }
+    }
+
+    fn add_peer(&self, peer_id: PeerId, endpoint: &str) {
+        if self.peers.borrow().contains_key(&peer_id) {
+            return;
+        }
+        let Ok(endpoint) = Endpoint::from_str(endpoint) else {
+            warn!("Ignoring peer with invalid endpoint {endpoint}");
+            return;
+        };
+        let socket = ComInterfaceSocket::new(
+            self.interface_uuid.clone(),
+            InterfaceDirection::InOut,
+            1,
+        );
+        let socket_uuid = socket.uuid.clone();
+        notify_socket_registered(&socket);
+        {
+            let mut sockets = self.sockets.lock().unwrap();
+            sockets.add_socket(Arc::new(Mutex::new(socket)));
+            if sockets
+                .register_socket_endpoint(
+                    socket_uuid.clone(),
+                    endpoint.clone(),
+                    1,
+                )
+                .is_err()
+            {
+                error!("Failed to register endpoint of socket {socket_uuid}");
+            }
+        }
+        info!("Peer {endpoint} joined the channel");
+        self.peers.borrow_mut().insert(peer_id, socket_uuid);
+    }
+
+    fn remove_peer(&self, peer_id: &PeerId) {
+        if let Some(socket_uuid) = self.peers.borrow_mut().remove(peer_id) {
+            debug!("Socket {socket_uuid} left the channel");
+            self.sockets.lock().unwrap().remove_socket(&socket_uuid);
+            notify_socket_closed(&self.interface_uuid, &socket_uuid);
+        }
+    }
+
+    fn receive(&self, peer_id: PeerId, data: Vec<u8>) {
+        let Some(socket_uuid) = self.peers.borrow().get(&peer_id).cloned()
+        else {
+            warn!("Received block from unknown peer");
+            return;
+        };
+        let sockets = self.sockets.lock().unwrap();
+        if let Some(socket) = sockets.sockets.get(&socket_uuid) {
+            receive_block(socket, &data);
+        }
+    }
+
+    fn get_peer_id(
+        &self,
+        socket_uuid: &ComInterfaceSocketUUID,
+    ) -> Option<PeerId> {
+        self.peers
+            .borrow()
+            .iter()
+            .find(|(_, uuid)| *uuid == socket_uuid)
+            .map(|(peer_id, _)| *peer_id)
+    }
+
+    fn create_onmessage_callback(&self) -> Closure<dyn FnMut(MessageEvent)> {
+        let connection = self.clone();
+        Closure::new(move |e: MessageEvent| {
+            let Ok(data) = e.data().dyn_into::<Uint8Array>() else {
+                return;
+            };
+            match BroadcastChannelMessage::from_bytes(&data.to_vec()) {
+                Some(message) => connection.handle_message(message),
+                None => warn!("Received invalid BroadcastChannel message"),
+            }
+        })
+    }
+}
+
+pub struct BroadcastChannelJSInterface {
+    name: String,
+    connection: Option<BroadcastChannelConnection>,
+    info: ComInterfaceInfo,
+}
+
+impl MultipleSocketProvider for BroadcastChannelJSInterface {
+    fn provide_sockets(&self) -> Arc<Mutex<ComInterfaceSockets>> {
+        self.get_sockets().clone()
+    }
+}
+
+#[com_interface]
+impl BroadcastChannelJSInterface {
+    pub fn new(
+        setup_data: BroadcastChannelInterfaceSetupData,
+    ) -> BroadcastChannelJSInterface {
+        BroadcastChannelJSInterface {
+            name: setup_data
+                .name
+                .unwrap_or_else(|| DEFAULT_CHANNEL_NAME.to_string()),
+            connection: None,
+            info: ComInterfaceInfo::default(),
+        }
+    }
+
+    #[create_opener]
+    fn open(&mut self) -> Result<(), ()> {
+        let channel = BroadcastChannel::new(&self.name).map_err(|e| {
+            error!("Failed to open BroadcastChannel {}: {e:?}", self.name);
+        })?;
+        let id: PeerId = CryptoJS
+            .random_bytes(PEER_ID_LENGTH)
+            .try_into()
+            .map_err(|_| error!("Failed to create peer id"))?;
+        let connection = BroadcastChannelConnection {
+            id,
+            interface_uuid: self.get_uuid().clone(),
+            sockets: self.get_sockets().clone(),
+            channel,
+            endpoint: Rc::new(RefCell::new(None)),
+            peers: Rc::new(RefCell::new(HashMap::new())),
+        };
+        let on_message = connection.create_onmessage_callback();
+        connection
+            .channel
+            .set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        on_message.forget();
+        self.connection = Some(connection);
+        Ok(())
+    }
+
+    /// Announces the interface with the given endpoint on the channel,
+    /// all peers on the channel connect to it and answer with their
+    /// own endpoints
+    pub fn announce(&self, endpoint: Endpoint) -> bool {
+        let Some(connection) = &self.connection else {
+            return false;
+        };
+        connection.endpoint.replace(Some(endpoint.clone()));
+        connection.post(&BroadcastChannelMessage::Join {
+            from: connection.id,
+            endpoint: endpoint.to_string(),
+        })
+    }
+}
+
+impl ComInterfaceFactory<BroadcastChannelInterfaceSetupData>
+    for BroadcastChannelJSInterface
+{
+    fn create(
+        setup_data: BroadcastChannelInterfaceSetupData,
+    ) -> Result<BroadcastChannelJSInterface, ComInterfaceError> {
+        Ok(BroadcastChannelJSInterface::new(setup_data))
+    }
+
+    fn get_default_properties() -> InterfaceProperties {
+        InterfaceProperties {
+            interface_type: "broadcast-channel".to_string(),
+            channel: "broadcast-channel".to_string(),
+            round_trip_time: Duration::from_millis(1),
+            max_bandwidth: u32::MAX,
+            ..InterfaceProperties::default()
+        }
+    }
+}
+
+impl BroadcastChannelJSInterface {
+    fn send_to_socket<'a>(
+        &'a mut self,
+        block: &'a [u8],
+        socket_uuid: ComInterfaceSocketUUID,
+    ) -> Pin<Box<dyn Future<Output = bool> + 'a>> {
+        let success = match &self.connection {
+            Some(connection) => match connection.get_peer_id(&socket_uuid) {
+                Some(peer_id) => {
+                    connection.post(&BroadcastChannelMessage::Block {
+                        from: connection.id,
+                        to: peer_id,
+                        data: block.to_vec(),
+                    })
+                }
+                None => {
+                    error!("Socket not found: {socket_uuid:?}");
+                    false
+                }
+            },
+            None => {
+                error!("BroadcastChannel is not open");
+                false
+            }
+        };
+        Box::pin(async move { success })
+    }
+}
+
+impl ComInterface for BroadcastChannelJSInterface {
+    intercepted_send_block!(send_to_socket);
+
+    fn init_properties(&self) -> InterfaceProperties {
+        InterfaceProperties {
+            name: Some(self.name.clone()),
+            ..Self::get_default_properties()
+        }
+    }
+    fn handle_close<'a>(
+        &'a mut self,
+    ) -> Pin<Box<dyn Future<Output = bool> + 'a>> {
+        let success = match self.connection.take() {
+            Some(connection) => {
+                connection.post(&BroadcastChannelMessage::Leave {
+                    from: connection.id,
+                });
+                connection.channel.set_onmessage(None);
+                connection.channel.close();
+                true
+            }
+            None => false,
+        };
+        Box::pin(async move { success })
+    }
+    delegate_com_interface_info!();
+    set_sync_opener!(open);
+}
+
+#[wasm_bindgen]
+impl JSComHub {
+    /// Announces the own endpoint on the channel of the BroadcastChannel
+    /// interface, so that the other runtimes on the channel connect to it
+    pub fn broadcast_channel_interface_announce(
+        &self,
+        interface_uuid: String,
+    ) -> Result<bool, JsError> {
+        let interface = self
+            .get_interface_for_uuid::<BroadcastChannelJSInterface>(
+                interface_uuid,
+            )
+            .map_err(|e| JsError::new(&format!("{e:?}")))?;
+        Ok(interface.borrow().announce(self.endpoint()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message_roundtrip() {
+        for message in [
+            BroadcastChannelMessage::Join {
+                from: [1; 16],
+                endpoint: "@example".to_string(),
+            },
+            BroadcastChannelMessage::Welcome {
+                from: [1; 16],
+                to: [2; 16],
+                endpoint: "@example".to_string(),
+            },
+            BroadcastChannelMessage::Block {
+                from: [1; 16],
+                to: [2; 16],
+                data: vec![0x01, 0x64, 0x00],
+            },
+            BroadcastChannelMessage::Leave { from: [1; 16] },
+        ] {
+            assert_eq!(
+                BroadcastChannelMessage::from_bytes(&message.to_bytes()),
+                Some(message)
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_messages() {
+        assert_eq!(BroadcastChannelMessage::from_bytes(&[]), None);
+        assert_eq!(BroadcastChannelMessage::from_bytes(&[2; 20]), None);
+        assert_eq!(BroadcastChannelMessage::from_bytes(&[4; 17]), None);
+    }
+}
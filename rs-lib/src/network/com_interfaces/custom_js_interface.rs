@@ -0,0 +1,374 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use datex_core::network::com_interfaces::com_interface::{
+    ComInterface, ComInterfaceError, ComInterfaceFactory, ComInterfaceInfo,
+    ComInterfaceSockets, ComInterfaceState, ComInterfaceUUID,
+};
+use datex_core::network::com_interfaces::com_interface_properties::{
+    InterfaceDirection, InterfaceProperties,
+};
+use datex_core::network::com_interfaces::com_interface_socket::{
+    ComInterfaceSocket, ComInterfaceSocketUUID,
+};
+use datex_core::network::com_interfaces::socket_provider::MultipleSocketProvider;
+use datex_core::stdlib::sync::Arc;
+use datex_core::values::core_values::endpoint::Endpoint;
+use datex_core::{delegate_com_interface_info, set_opener};
+use datex_macros::{com_interface, create_opener};
+use log::error;
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::{from_value, to_value};
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::{JsCast, JsError, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::js_sys::{self, Array, Function, Object, Promise, Reflect};
+
+use crate::intercepted_send_block;
+use crate::network::block_interceptors::receive_block;
+use crate::network::com_hub::JSComHub;
+use crate::network::com_hub_events::{
+    notify_socket_closed, notify_socket_registered, state_setter,
+};
+
+/// Setup data that is passed from [JSComHub::create_interface] to the
+/// factory of a custom interface
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CustomJSInterfaceSetupData {
+    pub request_id: u32,
+}
+
+/// Interface factories of the ComHub are plain functions, so the JS
+/// factory and the setup data of a new interface are kept here until
+/// the factory picks them up
+struct CustomJSInterfaceRequest {
+    interface_type: String,
+    factory: JsValue,
+    setup_data: JsValue,
+}
+
+thread_local! {
+    static REQUESTS: RefCell<HashMap<u32, CustomJSInterfaceRequest>> =
+        RefCell::new(HashMap::new());
+    static NEXT_REQUEST_ID: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Removes a request that was not picked up by the factory
+pub(crate) fn remove_custom_interface_request(request_id: u32) {
+    REQUESTS.with(|requests| requests.borrow_mut().remove(&request_id));
+}
+
+/// Calls a method of the JS object and resolves the returned value if it
+/// is a promise, methods that are not defined resolve to undefined
+async fn call_method(
+    target: &JsValue,
+    name: &str,
+    args: &Array,
+) -> Result<JsValue, JsValue> {
+    let method = Reflect::get(target, &name.into())?;
+    let Some(method) = method.dyn_ref::<Function>() else {
+        return Ok(JsValue::UNDEFINED);
+    };
+    let result = method.apply(target, args)?;
+    JsFuture::from(Promise::resolve(&result)).await
+}
+
+/// Handle that is passed to the `create` function of a JS interface
+/// factory, used by the JS implementation to manage its sockets
+#[wasm_bindgen]
+pub struct CustomJSInterfaceController {
+    interface_uuid: ComInterfaceUUID,
+    sockets: Arc<Mutex<ComInterfaceSockets>>,
+    set_state: Rc<dyn Fn(ComInterfaceState)>,
+}
+
+#[wasm_bindgen]
+impl CustomJSInterfaceController {
+    #[wasm_bindgen(getter)]
+    pub fn interface_uuid(&self) -> String {
+        self.interface_uuid.0.to_string()
+    }
+
+    /// Adds a new socket, the endpoint of the socket can be set if it is
+    /// already known, otherwise it is detected from the received blocks
+    pub fn add_socket(
+        &self,
+        direction: String,
+        endpoint: Option<String>,
+    ) -> Result<String, JsError> {
+        let direction = InterfaceDirection::from_str(&direction)
+            .map_err(|_| JsError::new("Invalid direction"))?;
+        let endpoint = endpoint
+            .map(|endpoint| Endpoint::from_str(&endpoint))
+            .transpose()
+            .map_err(|_| JsError::new("Invalid endpoint"))?;
+        let socket =
+            ComInterfaceSocket::new(self.interface_uuid.clone(), direction, 1);
+        let socket_uuid = socket.uuid.clone();
+        notify_socket_registered(&socket);
+        let mut sockets = self.sockets.lock().unwrap();
+        sockets.add_socket(Arc::new(Mutex::new(socket)));
+        if let Some(endpoint) = endpoint {
+            sockets
+                .register_socket_endpoint(socket_uuid.clone(), endpoint, 1)
+                .map_err(|e| JsError::new(&format!("{e:?}")))?;
+        }
+        Ok(socket_uuid.0.to_string())
+    }
+
+    pub fn remove_socket(&self, socket_uuid: String) -> Result<(), JsError> {
+        let socket_uuid = ComInterfaceSocketUUID::from_string(socket_uuid);
+        let mut sockets = self.sockets.lock().unwrap();
+        if !sockets.sockets.contains_key(&socket_uuid) {
+            return Err(JsError::new("Socket not found"));
+        }
+        sockets.remove_socket(&socket_uuid);
+        notify_socket_closed(&self.interface_uuid, &socket_uuid);
+        Ok(())
+    }
+
+    /// Passes the received bytes to the socket, the bytes do not have to
+    /// contain complete blocks
+    pub fn receive(
+        &self,
+        socket_uuid: String,
+        data: &[u8],
+    ) -> Result<(), JsError> {
+        let socket_uuid = ComInterfaceSocketUUID::from_string(socket_uuid);
+        let sockets = self.sockets.lock().unwrap();
+        let socket = sockets
+            .sockets
+            .get(&socket_uuid)
+            .ok_or_else(|| JsError::new("Socket not found"))?;
+        receive_block(socket, data);
+        Ok(())
+    }
+
+    /// Marks the interface as disconnected, e.g. when the underlying
+    /// transport was closed by the other side, so that the ComHub can
+    /// reconnect it according to its reconnection config
+    pub fn set_disconnected(&self) {
+        (self.set_state)(ComInterfaceState::NotConnected);
+    }
+}
+
+/// Interface that delegates to an implementation created by a factory
+/// that was registered from JS via [JSComHub::register_interface_factory]
+pub struct CustomJSInterface {
+    implementation: JsValue,
+    properties: InterfaceProperties,
+    info: ComInterfaceInfo,
+}
+
+impl MultipleSocketProvider for CustomJSInterface {
+    fn provide_sockets(&self) -> Arc<Mutex<ComInterfaceSockets>> {
+        self.get_sockets().clone()
+    }
+}
+
+#[com_interface]
+impl CustomJSInterface {
+    fn new(
+        request: CustomJSInterfaceRequest,
+    ) -> Result<CustomJSInterface, String> {
+        let properties = Self::create_properties(&request)?;
+        let mut interface = CustomJSInterface {
+            implementation: JsValue::UNDEFINED,
+            properties,
+            info: ComInterfaceInfo::default(),
+        };
+        let controller = CustomJSInterfaceController {
+            interface_uuid: interface.get_uuid().clone(),
+            sockets: interface.get_sockets().clone(),
+            set_state: state_setter(&interface),
+        };
+        let create = Reflect::get(&request.factory, &"create".into())
+            .ok()
+            .and_then(|create| create.dyn_into::<Function>().ok())
+            .ok_or("Factory has no create method")?;
+        interface.implementation = create
+            .call2(
+                &request.factory,
+                &request.setup_data,
+                &JsValue::from(controller),
+            )
+            .map_err(|e| format!("{e:?}"))?;
+        Ok(interface)
+    }
+
+    /// The properties of the factory override the defaults, so that
+    /// factories only have to define the properties that differ
+    fn create_properties(
+        request: &CustomJSInterfaceRequest,
+    ) -> Result<InterfaceProperties, String> {
+        let properties = InterfaceProperties {
+            interface_type: request.interface_type.clone(),
+            channel: request.interface_type.clone(),
+            ..Self::get_default_properties()
+        };
+        let factory_properties =
+            Reflect::get(&request.factory, &"properties".into())
+                .map_err(|e| format!("{e:?}"))?;
+        if factory_properties.is_undefined() {
+            return Ok(properties);
+        }
+        let properties = Object::assign(
+            &to_value(&properties)
+                .map_err(|e| e.to_string())?
+                .unchecked_into(),
+            &factory_properties.unchecked_into(),
+        );
+        from_value(properties.into()).map_err(|e| e.to_string())
+    }
+
+    #[create_opener]
+    async fn open(&mut self) -> Result<(), ()> {
+        call_method(&self.implementation, "open", &Array::new())
+            .await
+            .map(|_| ())
+            .map_err(|e| {
+                error!(
+                    "Failed to open {} interface: {e:?}",
+                    self.properties.interface_type
+                );
+            })
+    }
+}
+
+impl ComInterfaceFactory<CustomJSInterfaceSetupData> for CustomJSInterface {
+    fn create(
+        setup_data: CustomJSInterfaceSetupData,
+    ) -> Result<CustomJSInterface, ComInterfaceError> {
+        let request = REQUESTS
+            .with(|requests| {
+                requests.borrow_mut().remove(&setup_data.request_id)
+            })
+            .ok_or(ComInterfaceError::InvalidSetupData)?;
+        CustomJSInterface::new(request).map_err(|e| {
+            error!("Failed to create custom interface: {e}");
+            ComInterfaceError::InvalidSetupData
+        })
+    }
+
+    fn get_default_properties() -> InterfaceProperties {
+        InterfaceProperties {
+            interface_type: "custom".to_string(),
+            channel: "custom".to_string(),
+            ..InterfaceProperties::default()
+        }
+    }
+}
+
+impl CustomJSInterface {
+    fn send_to_socket<'a>(
+        &'a mut self,
+        block: &'a [u8],
+        socket_uuid: ComInterfaceSocketUUID,
+    ) -> Pin<Box<dyn Future<Output = bool> + 'a>> {
+        let implementation = self.implementation.clone();
+        let args = Array::of2(
+            &js_sys::Uint8Array::from(block),
+            &JsValue::from(socket_uuid.0.to_string()),
+        );
+        Box::pin(async move {
+            match call_method(&implementation, "send", &args).await {
+                Ok(result) => result.as_bool().unwrap_or(false),
+                Err(e) => {
+                    error!("Error sending block: {e:?}");
+                    false
+                }
+            }
+        })
+    }
+}
+
+impl ComInterface for CustomJSInterface {
+    intercepted_send_block!(send_to_socket);
+
+    fn init_properties(&self) -> InterfaceProperties {
+        self.properties.clone()
+    }
+    fn handle_close<'a>(
+        &'a mut self,
+    ) -> Pin<Box<dyn Future<Output = bool> + 'a>> {
+        let implementation = self.implementation.clone();
+        Box::pin(async move {
+            call_method(&implementation, "close", &Array::new())
+                .await
+                .map_err(|e| error!("Error closing interface: {e:?}"))
+                .is_ok()
+        })
+    }
+    delegate_com_interface_info!();
+    set_opener!(open);
+}
+
+impl JSComHub {
+    /// Returns the setup data for interfaces of JS factories, or None
+    /// if the interface type has no JS factory. The setup data is passed
+    /// to the JS factory as is, so it can also contain JS objects
+    pub(crate) fn get_custom_interface_setup_data(
+        &self,
+        interface_type: &str,
+        setup_data: JsValue,
+    ) -> Option<CustomJSInterfaceSetupData> {
+        let factory = self
+            .custom_interface_factories
+            .borrow()
+            .get(interface_type)
+            .cloned()?;
+        let request = CustomJSInterfaceRequest {
+            interface_type: interface_type.to_string(),
+            factory,
+            setup_data,
+        };
+        let request_id =
+            NEXT_REQUEST_ID.with(|id| id.replace(id.get().wrapping_add(1)));
+        REQUESTS
+            .with(|requests| requests.borrow_mut().insert(request_id, request));
+        Some(CustomJSInterfaceSetupData { request_id })
+    }
+}
+
+#[wasm_bindgen]
+impl JSComHub {
+    /// Registers a factory for interfaces that are implemented in JS.
+    /// The factory has a `create(setupData, controller)` method that
+    /// returns the implementation with `open`, `send` and `close` methods,
+    /// and optional `properties` that override the default properties
+    pub fn register_interface_factory(
+        &self,
+        interface_type: String,
+        factory: JsValue,
+    ) -> Result<(), JsError> {
+        // the Rust factories can not be replaced
+        if self.has_setup_data_validator(&interface_type) {
+            return Err(JsError::new(&format!(
+                "Interface type {interface_type} is a built-in interface type"
+            )));
+        }
+        let has_create = Reflect::get(&factory, &"create".into())
+            .map(|create| create.is_function())
+            .unwrap_or(false);
+        if !has_create {
+            return Err(JsError::new("Factory has no create method"));
+        }
+        let mut factories = self.custom_interface_factories.borrow_mut();
+        if factories.contains_key(&interface_type) {
+            return Err(JsError::new(&format!(
+                "Interface factory for {interface_type} already registered"
+            )));
+        }
+        factories.insert(interface_type.clone(), factory);
+        self.com_hub().register_interface_factory(
+            interface_type,
+            CustomJSInterface::factory,
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,392 @@
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Mutex;
+use std::time::Duration; // FIXME no-std
+
+use datex_core::crypto::crypto::CryptoTrait;
+use datex_core::network::com_interfaces::com_interface::{
+    ComInterface, ComInterfaceError, ComInterfaceFactory, ComInterfaceInfo,
+    ComInterfaceSockets, ComInterfaceState, ComInterfaceUUID,
+};
+use datex_core::network::com_interfaces::com_interface_properties::{
+    InterfaceDirection, InterfaceProperties,
+};
+use datex_core::network::com_interfaces::com_interface_socket::{
+    ComInterfaceSocket, ComInterfaceSocketUUID,
+};
+use datex_core::network::com_interfaces::socket_provider::SingleSocketProvider;
+use datex_core::stdlib::sync::Arc;
+use datex_core::task::spawn_with_panic_notify_default;
+use datex_core::{delegate_com_interface_info, set_opener};
+use datex_macros::{com_interface, create_opener};
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use url::Url;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::js_sys::{self, Function, Promise, Reflect, Uint8Array};
+use web_sys::{
+    AbortController, AbortSignal, ReadableStreamDefaultReader, RequestInit,
+    Response,
+};
+
+use crate::crypto::crypto_js::CryptoJS;
+use crate::intercepted_send_block;
+use crate::network::block_interceptors::receive_block;
+use crate::network::com_hub_events::{
+    notify_socket_closed, notify_socket_registered, state_setter,
+};
+
+/// Query parameter that identifies the session of a client
+const SESSION_QUERY_PARAMETER: &str = "session";
+/// Query parameter that requests a streaming instead of a
+/// long-polling response
+const STREAM_QUERY_PARAMETER: &str = "stream";
+const SESSION_ID_LENGTH: usize = 16;
+/// Delay before a failed poll request is retried
+const POLL_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Setup data for an interface that sends blocks via HTTP POST requests
+/// and receives blocks via long-polling or a streaming response.
+/// Used as fallback if WebSockets are blocked, e.g. by a proxy.
+#[derive(Serialize, Deserialize, Tsify, Debug, Clone)]
+pub struct HttpClientInterfaceSetupData {
+    /// URL of the HTTP server interface, e.g. "https://example.com/datex"
+    pub address: String,
+    /// If true, blocks are received over a single chunked response instead
+    /// of repeated long-polling requests (default: false). Some proxies
+    /// buffer chunked responses, so long-polling is the safer default.
+    #[serde(default)]
+    pub streaming: bool,
+}
+
+/// Returns the URL of a request of the given session
+fn get_session_url(address: &Url, session: &str, streaming: bool) -> String {
+    let mut url = address.clone();
+    url.query_pairs_mut()
+        .append_pair(SESSION_QUERY_PARAMETER, session);
+    if streaming {
+        url.query_pairs_mut()
+            .append_pair(STREAM_QUERY_PARAMETER, "1");
+    }
+    url.to_string()
+}
+
+/// Calls the global fetch function, which is available in browsers,
+/// workers and server runtimes
+async fn fetch(url: &str, init: &RequestInit) -> Result<Response, JsValue> {
+    let fetch = Reflect::get(&js_sys::global(), &"fetch".into())?
+        .dyn_into::<Function>()?;
+    let promise = fetch
+        .call2(&JsValue::NULL, &JsValue::from_str(url), init)?
+        .dyn_into::<Promise>()?;
+    JsFuture::from(promise).await?.dyn_into::<Response>()
+}
+
+#[derive(Clone)]
+struct HttpConnection {
+    address: Url,
+    session: String,
+    streaming: bool,
+    interface_uuid: ComInterfaceUUID,
+    sockets: Arc<Mutex<ComInterfaceSockets>>,
+    set_state: Rc<dyn Fn(ComInterfaceState)>,
+    socket_uuid: Rc<RefCell<Option<ComInterfaceSocketUUID>>>,
+    /// Aborts the pending poll request when the interface is closed
+    abort_controller: Rc<RefCell<Option<AbortController>>>,
+    closed: Rc<Cell<bool>>,
+}
+
+impl HttpConnection {
+    async fn request(
+        &self,
+        method: &str,
+        body: Option<&[u8]>,
+        signal: Option<&AbortSignal>,
+        streaming: bool,
+    ) -> Result<Response, JsValue> {
+        let init = RequestInit::new();
+        init.set_method(method);
+        if let Some(body) = body {
+            init.set_body(&Uint8Array::from(body));
+        }
+        init.set_signal(signal);
+        let url = get_session_url(&self.address, &self.session, streaming);
+        let response = fetch(&url, &init).await?;
+        if !response.ok() {
+            return Err(JsValue::from_str(&format!(
+                "Request failed with status {}",
+                response.status()
+            )));
+        }
+        Ok(response)
+    }
+
+    /// Opens the session on the server with an empty POST request, fails
+    /// if the server cannot be reached, so that the ComHub can fall back
+    /// to another interface
+    async fn connect(&self) -> Result<(), ()> {
+        self.request("POST", None, None, false).await.map_err(|e| {
+            error!("Failed to connect to {}: {e:?}", self.address)
+        })?;
+        let socket = ComInterfaceSocket::new(
+            self.interface_uuid.clone(),
+            InterfaceDirection::InOut,
+            1,
+        );
+        self.socket_uuid.replace(Some(socket.uuid.clone()));
+        notify_socket_registered(&socket);
+        self.sockets
+            .lock()
+            .unwrap()
+            .add_socket(Arc::new(Mutex::new(socket)));
+        info!("Connected to {} via HTTP", self.address);
+        (self.set_state)(ComInterfaceState::Connected);
+        spawn_with_panic_notify_default(self.clone().poll());
+        Ok(())
+    }
+
+    async fn send(&self, block: &[u8]) -> bool {
+        self.request("POST", Some(block), None, false)
+            .await
+            .map_err(|e| error!("Error sending block: {e:?}"))
+            .is_ok()
+    }
+
+    /// Receives blocks until the interface is closed, failed requests
+    /// are retried after a delay
+    async fn poll(self) {
+        while !self.closed.get() {
+            let Ok(abort_controller) = AbortController::new() else {
+                error!("Failed to create AbortController");
+                break;
+            };
+            let signal = abort_controller.signal();
+            self.abort_controller.replace(Some(abort_controller));
+            let result = match self
+                .request("GET", None, Some(&signal), self.streaming)
+                .await
+            {
+                Ok(response) => self.receive_response(response).await,
+                Err(e) => Err(e),
+            };
+            if self.closed.get() {
+                break;
+            }
+            match result {
+                Ok(_) => (self.set_state)(ComInterfaceState::Connected),
+                Err(e) => {
+                    warn!(
+                        "Receiving blocks from {} failed: {e:?}",
+                        self.address
+                    );
+                    (self.set_state)(ComInterfaceState::NotConnected);
+                    futures_timer::Delay::new(POLL_RETRY_DELAY).await;
+                }
+            }
+        }
+        debug!("Stopped receiving blocks from {}", self.address);
+    }
+
+    /// Blocks split across chunks or responses are reassembled by the
+    /// ComHub's block collector, so the bytes are queued as received
+    async fn receive_response(
+        &self,
+        response: Response,
+    ) -> Result<(), JsValue> {
+        if !self.streaming {
+            let buffer = JsFuture::from(response.array_buffer()?).await?;
+            self.receive(&Uint8Array::new(&buffer).to_vec());
+            return Ok(());
+        }
+        let Some(body) = response.body() else {
+            return Ok(());
+        };
+        let reader = body
+            .get_reader()
+            .dyn_into::<ReadableStreamDefaultReader>()?;
+        loop {
+            let result = JsFuture::from(reader.read()).await?;
+            if Reflect::get(&result, &"done".into())?.is_truthy() {
+                return Ok(());
+            }
+            let value = Reflect::get(&result, &"value".into())?;
+            if let Some(chunk) = value.dyn_ref::<Uint8Array>() {
+                self.receive(&chunk.to_vec());
+            }
+        }
+    }
+
+    fn receive(&self, bytes: &[u8]) {
+        if bytes.is_empty() {
+            return;
+        }
+        let Some(socket_uuid) = self.socket_uuid.borrow().clone() else {
+            return;
+        };
+        let sockets = self.sockets.lock().unwrap();
+        if let Some(socket) = sockets.sockets.get(&socket_uuid) {
+            receive_block(socket, bytes);
+        }
+    }
+
+    /// Stops polling and ends the session on the server
+    fn close(&self) -> bool {
+        self.closed.set(true);
+        if let Some(abort_controller) = self.abort_controller.take() {
+            abort_controller.abort();
+        }
+        if let Some(socket_uuid) = self.socket_uuid.take() {
+            self.sockets.lock().unwrap().remove_socket(&socket_uuid);
+            notify_socket_closed(&self.interface_uuid, &socket_uuid);
+        }
+        let connection = self.clone();
+        spawn_with_panic_notify_default(async move {
+            if let Err(e) =
+                connection.request("DELETE", None, None, false).await
+            {
+                debug!("Failed to end HTTP session: {e:?}");
+            }
+        });
+        true
+    }
+}
+
+pub struct HttpClientJSInterface {
+    address: Url,
+    streaming: bool,
+    connection: Option<HttpConnection>,
+    info: ComInterfaceInfo,
+}
+
+impl SingleSocketProvider for HttpClientJSInterface {
+    fn provide_sockets(&self) -> Arc<Mutex<ComInterfaceSockets>> {
+        self.get_sockets().clone()
+    }
+}
+
+#[com_interface]
+impl HttpClientJSInterface {
+    pub fn new(
+        setup_data: HttpClientInterfaceSetupData,
+    ) -> Result<HttpClientJSInterface, url::ParseError> {
+        Ok(HttpClientJSInterface {
+            address: Url::parse(&setup_data.address)?,
+            streaming: setup_data.streaming,
+            connection: None,
+            info: ComInterfaceInfo::default(),
+        })
+    }
+
+    #[create_opener]
+    async fn open(&mut self) -> Result<(), ()> {
+        let session = CryptoJS
+            .random_bytes(SESSION_ID_LENGTH)
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>();
+        let connection = HttpConnection {
+            address: self.address.clone(),
+            session,
+            streaming: self.streaming,
+            interface_uuid: self.get_uuid().clone(),
+            sockets: self.get_sockets().clone(),
+            set_state: state_setter(self),
+            socket_uuid: Rc::new(RefCell::new(None)),
+            abort_controller: Rc::new(RefCell::new(None)),
+            closed: Rc::new(Cell::new(false)),
+        };
+        connection.connect().await?;
+        self.connection = Some(connection);
+        Ok(())
+    }
+}
+
+impl ComInterfaceFactory<HttpClientInterfaceSetupData>
+    for HttpClientJSInterface
+{
+    fn create(
+        setup_data: HttpClientInterfaceSetupData,
+    ) -> Result<HttpClientJSInterface, ComInterfaceError> {
+        let interface = HttpClientJSInterface::new(setup_data)
+            .map_err(|_| ComInterfaceError::InvalidSetupData)?;
+        if !matches!(interface.address.scheme(), "http" | "https") {
+            error!("HTTP address must start with http:// or https://");
+            return Err(ComInterfaceError::InvalidSetupData);
+        }
+        Ok(interface)
+    }
+
+    fn get_default_properties() -> InterfaceProperties {
+        InterfaceProperties {
+            interface_type: "http-client".to_string(),
+            channel: "http".to_string(),
+            round_trip_time: Duration::from_millis(100),
+            max_bandwidth: 1000,
+            ..InterfaceProperties::default()
+        }
+    }
+}
+
+impl HttpClientJSInterface {
+    fn send_to_socket<'a>(
+        &'a mut self,
+        block: &'a [u8],
+        _: ComInterfaceSocketUUID,
+    ) -> Pin<Box<dyn Future<Output = bool> + 'a>> {
+        let connection = self.connection.clone();
+        Box::pin(async move {
+            match connection {
+                Some(connection) => connection.send(block).await,
+                None => {
+                    error!("Error sending block: HTTP session is not open");
+                    false
+                }
+            }
+        })
+    }
+}
+
+impl ComInterface for HttpClientJSInterface {
+    intercepted_send_block!(send_to_socket);
+
+    fn init_properties(&self) -> InterfaceProperties {
+        InterfaceProperties {
+            name: Some(self.address.to_string()),
+            ..Self::get_default_properties()
+        }
+    }
+    fn handle_close<'a>(
+        &'a mut self,
+    ) -> Pin<Box<dyn Future<Output = bool> + 'a>> {
+        let success = match self.connection.take() {
+            Some(connection) => connection.close(),
+            None => false,
+        };
+        Box::pin(async move { success })
+    }
+    delegate_com_interface_info!();
+    set_opener!(open);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_url() {
+        let address = Url::parse("https://example.com/datex").unwrap();
+        assert_eq!(
+            get_session_url(&address, "abc", false),
+            "https://example.com/datex?session=abc"
+        );
+        let address = Url::parse("https://example.com/datex?key=1").unwrap();
+        assert_eq!(
+            get_session_url(&address, "abc", true),
+            "https://example.com/datex?key=1&session=abc&stream=1"
+        );
+    }
+}
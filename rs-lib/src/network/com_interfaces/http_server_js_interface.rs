@@ -0,0 +1,423 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Mutex;
+use std::time::Duration; // FIXME no-std
+
+use datex_core::network::com_interfaces::com_interface::{
+    ComInterface, ComInterfaceError, ComInterfaceFactory, ComInterfaceInfo,
+    ComInterfaceSockets, ComInterfaceUUID,
+};
+use datex_core::network::com_interfaces::com_interface_properties::{
+    InterfaceDirection, InterfaceProperties,
+};
+use datex_core::network::com_interfaces::com_interface_socket::{
+    ComInterfaceSocket, ComInterfaceSocketUUID,
+};
+use datex_core::network::com_interfaces::socket_provider::MultipleSocketProvider;
+use datex_core::stdlib::sync::Arc;
+use datex_core::{delegate_com_interface_info, set_sync_opener};
+use datex_macros::{com_interface, create_opener};
+use futures::future::select;
+use futures_channel::oneshot;
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use wasm_bindgen::JsError;
+use wasm_bindgen::prelude::wasm_bindgen;
+use web_sys::js_sys::{self, Uint8Array};
+
+use crate::intercepted_send_block;
+use crate::network::block_interceptors::receive_block;
+use crate::network::com_hub::JSComHub;
+use crate::network::com_hub_events::{
+    notify_socket_closed, notify_socket_registered,
+};
+
+/// Time in milliseconds after which a poll request is answered
+/// without blocks, below the timeout of most proxies
+const DEFAULT_POLL_TIMEOUT: u32 = 25_000;
+const MAX_SESSION_ID_LENGTH: usize = 64;
+/// Maximum number of blocks that are held for a session until the client
+/// fetches them, the session is closed if the limit is exceeded
+const MAX_PENDING_BLOCKS: usize = 1024;
+
+/// Setup data for the HTTP server interface that answers the requests of
+/// HTTP client interfaces. The requests are passed to the interface by
+/// the request handler of the JS implementation.
+#[derive(Serialize, Deserialize, Tsify, Debug, Clone)]
+pub struct HttpServerJSInterfaceSetupData {
+    /// Port on which the JS implementation serves the interface.
+    /// If not set, requests must be passed to the request handler.
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// Time in milliseconds after which a poll request is answered
+    /// without blocks (default: 25000)
+    #[serde(default)]
+    pub poll_timeout: Option<u32>,
+}
+
+/// Session ids are chosen by the clients and must be unguessable,
+/// so only short alphanumeric ids are accepted
+fn is_valid_session_id(session_id: &str) -> bool {
+    !session_id.is_empty()
+        && session_id.len() <= MAX_SESSION_ID_LENGTH
+        && session_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+struct HttpSession {
+    socket_uuid: ComInterfaceSocketUUID,
+    /// Blocks that were not yet fetched by the client
+    pending: Vec<u8>,
+    /// Number of blocks in `pending`
+    pending_blocks: usize,
+    /// Resolves the pending poll request of the client
+    waiter: Option<oneshot::Sender<()>>,
+    last_seen: f64,
+}
+
+impl HttpSession {
+    /// Returns the blocks that were not yet fetched by the client
+    fn take_pending(&mut self) -> Vec<u8> {
+        self.pending_blocks = 0;
+        std::mem::take(&mut self.pending)
+    }
+}
+
+/// Sessions of the server, shared with the pending poll requests
+#[derive(Clone)]
+struct HttpSessions {
+    interface_uuid: ComInterfaceUUID,
+    sockets: Arc<Mutex<ComInterfaceSockets>>,
+    sessions: Rc<RefCell<HashMap<String, HttpSession>>>,
+    poll_timeout: Duration,
+}
+
+impl HttpSessions {
+    /// Returns the socket of the session, a new session is opened
+    /// for unknown ids
+    fn touch(&self, session_id: &str) -> ComInterfaceSocketUUID {
+        self.remove_expired();
+        let now = js_sys::Date::now();
+        let mut sessions = self.sessions.borrow_mut();
+        if let Some(session) = sessions.get_mut(session_id) {
+            session.last_seen = now;
+            return session.socket_uuid.clone();
+        }
+        let socket = ComInterfaceSocket::new(
+            self.interface_uuid.clone(),
+            InterfaceDirection::InOut,
+            1,
+        );
+        let socket_uuid = socket.uuid.clone();
+        notify_socket_registered(&socket);
+        self.sockets
+            .lock()
+            .unwrap()
+            .add_socket(Arc::new(Mutex::new(socket)));
+        sessions.insert(
+            session_id.to_string(),
+            HttpSession {
+                socket_uuid: socket_uuid.clone(),
+                pending: Vec::new(),
+                pending_blocks: 0,
+                waiter: None,
+                last_seen: now,
+            },
+        );
+        info!("HTTP session opened");
+        socket_uuid
+    }
+
+    /// Clients poll continuously, so sessions without a pending poll
+    /// that were not seen for two poll timeouts are closed
+    fn remove_expired(&self) {
+        let expiry =
+            js_sys::Date::now() - 2.0 * self.poll_timeout.as_millis() as f64;
+        let expired = self
+            .sessions
+            .borrow()
+            .iter()
+            .filter(|(_, session)| {
+                session.waiter.is_none() && session.last_seen < expiry
+            })
+            .map(|(session_id, _)| session_id.clone())
+            .collect::<Vec<_>>();
+        for session_id in expired {
+            debug!("HTTP session expired");
+            self.close(&session_id);
+        }
+    }
+
+    fn receive(&self, session_id: &str, body: &[u8]) {
+        let socket_uuid = self.touch(session_id);
+        if body.is_empty() {
+            return;
+        }
+        let sockets = self.sockets.lock().unwrap();
+        if let Some(socket) = sockets.sockets.get(&socket_uuid) {
+            receive_block(socket, body);
+        }
+    }
+
+    /// Waits until blocks for the session are available or the poll
+    /// timeout is reached. Returns None if the session was closed.
+    async fn poll(&self, session_id: &str) -> Option<Vec<u8>> {
+        self.touch(session_id);
+        let receiver = {
+            let mut sessions = self.sessions.borrow_mut();
+            let session = sessions.get_mut(session_id)?;
+            if !session.pending.is_empty() {
+                return Some(session.take_pending());
+            }
+            let (sender, receiver) = oneshot::channel();
+            // only one poll per session, a previous poll is answered
+            if let Some(waiter) = session.waiter.replace(sender) {
+                let _ = waiter.send(());
+            }
+            receiver
+        };
+        select(receiver, futures_timer::Delay::new(self.poll_timeout)).await;
+        let mut sessions = self.sessions.borrow_mut();
+        let session = sessions.get_mut(session_id)?;
+        session.last_seen = js_sys::Date::now();
+        if session.waiter.as_ref().is_some_and(|w| w.is_canceled()) {
+            session.waiter = None;
+        }
+        Some(session.take_pending())
+    }
+
+    fn send(&self, socket_uuid: &ComInterfaceSocketUUID, block: &[u8]) -> bool {
+        let mut sessions = self.sessions.borrow_mut();
+        let Some((session_id, session)) = sessions
+            .iter_mut()
+            .find(|(_, session)| &session.socket_uuid == socket_uuid)
+        else {
+            error!("Error sending block: HTTP session not found");
+            return false;
+        };
+        // the client stopped fetching its blocks without closing the session
+        if session.pending_blocks >= MAX_PENDING_BLOCKS {
+            warn!("Too many pending blocks, closing the HTTP session");
+            let session_id = session_id.clone();
+            drop(sessions);
+            self.close(&session_id);
+            return false;
+        }
+        session.pending.extend_from_slice(block);
+        session.pending_blocks += 1;
+        if let Some(waiter) = session.waiter.take() {
+            let _ = waiter.send(());
+        }
+        true
+    }
+
+    /// Removes the session and its socket, a pending poll is answered
+    fn close(&self, session_id: &str) -> bool {
+        let Some(session) = self.sessions.borrow_mut().remove(session_id)
+        else {
+            return false;
+        };
+        self.sockets
+            .lock()
+            .unwrap()
+            .remove_socket(&session.socket_uuid);
+        notify_socket_closed(&self.interface_uuid, &session.socket_uuid);
+        info!("HTTP session closed");
+        true
+    }
+
+    fn close_all(&self) {
+        let session_ids =
+            self.sessions.borrow().keys().cloned().collect::<Vec<_>>();
+        for session_id in session_ids {
+            self.close(&session_id);
+        }
+    }
+}
+
+pub struct HttpServerJSInterface {
+    poll_timeout: Duration,
+    sessions: Option<HttpSessions>,
+    info: ComInterfaceInfo,
+}
+
+impl MultipleSocketProvider for HttpServerJSInterface {
+    fn provide_sockets(&self) -> Arc<Mutex<ComInterfaceSockets>> {
+        self.get_sockets().clone()
+    }
+}
+
+#[com_interface]
+impl HttpServerJSInterface {
+    pub fn new(
+        setup_data: HttpServerJSInterfaceSetupData,
+    ) -> HttpServerJSInterface {
+        let poll_timeout =
+            setup_data.poll_timeout.unwrap_or(DEFAULT_POLL_TIMEOUT);
+        HttpServerJSInterface {
+            poll_timeout: Duration::from_millis(poll_timeout as u64),
+            sessions: None,
+            info: ComInterfaceInfo::default(),
+        }
+    }
+
+    #[create_opener]
+    fn open(&mut self) -> Result<(), ()> {
+        self.sessions = Some(HttpSessions {
+            interface_uuid: self.get_uuid().clone(),
+            sockets: self.get_sockets().clone(),
+            sessions: Rc::new(RefCell::new(HashMap::new())),
+            poll_timeout: self.poll_timeout,
+        });
+        Ok(())
+    }
+}
+
+impl ComInterfaceFactory<HttpServerJSInterfaceSetupData>
+    for HttpServerJSInterface
+{
+    fn create(
+        setup_data: HttpServerJSInterfaceSetupData,
+    ) -> Result<HttpServerJSInterface, ComInterfaceError> {
+        if setup_data.poll_timeout == Some(0) {
+            error!("Poll timeout must be greater than 0");
+            return Err(ComInterfaceError::InvalidSetupData);
+        }
+        Ok(HttpServerJSInterface::new(setup_data))
+    }
+
+    fn get_default_properties() -> InterfaceProperties {
+        InterfaceProperties {
+            interface_type: "http-server".to_string(),
+            channel: "http".to_string(),
+            round_trip_time: Duration::from_millis(100),
+            max_bandwidth: 1000,
+            ..InterfaceProperties::default()
+        }
+    }
+}
+
+impl HttpServerJSInterface {
+    fn send_to_socket<'a>(
+        &'a mut self,
+        block: &'a [u8],
+        socket_uuid: ComInterfaceSocketUUID,
+    ) -> Pin<Box<dyn Future<Output = bool> + 'a>> {
+        let success = match &self.sessions {
+            Some(sessions) => sessions.send(&socket_uuid, block),
+            None => false,
+        };
+        Box::pin(async move { success })
+    }
+}
+
+impl ComInterface for HttpServerJSInterface {
+    intercepted_send_block!(send_to_socket);
+
+    fn init_properties(&self) -> InterfaceProperties {
+        Self::get_default_properties()
+    }
+    fn handle_close<'a>(
+        &'a mut self,
+    ) -> Pin<Box<dyn Future<Output = bool> + 'a>> {
+        if let Some(sessions) = self.sessions.take() {
+            sessions.close_all();
+        }
+        Box::pin(async move { true })
+    }
+    delegate_com_interface_info!();
+    set_sync_opener!(open);
+}
+
+impl JSComHub {
+    fn get_http_sessions(
+        &self,
+        interface_uuid: String,
+    ) -> Result<HttpSessions, JsError> {
+        let interface = self
+            .get_interface_for_uuid::<HttpServerJSInterface>(interface_uuid)
+            .map_err(|e| JsError::new(&format!("{e:?}")))?;
+        let sessions = interface.borrow().sessions.clone();
+        sessions.ok_or_else(|| JsError::new("HTTP server interface is closed"))
+    }
+}
+
+fn check_session_id(session_id: &str) -> Result<(), JsError> {
+    if is_valid_session_id(session_id) {
+        Ok(())
+    } else {
+        Err(JsError::new("Invalid session id"))
+    }
+}
+
+#[wasm_bindgen]
+impl JSComHub {
+    /// Passes the body of a POST request of the given session to the
+    /// HTTP server interface, an unknown session is opened
+    pub fn http_server_interface_receive(
+        &self,
+        interface_uuid: String,
+        session_id: String,
+        body: &[u8],
+    ) -> Result<(), JsError> {
+        check_session_id(&session_id)?;
+        self.get_http_sessions(interface_uuid)?
+            .receive(&session_id, body);
+        Ok(())
+    }
+
+    /// Waits for blocks for the given session and returns the bytes for
+    /// the response of a GET request. The bytes are empty if the poll
+    /// timed out and undefined if the session was closed.
+    pub async fn http_server_interface_poll(
+        &self,
+        interface_uuid: String,
+        session_id: String,
+    ) -> Result<Option<Uint8Array>, JsError> {
+        check_session_id(&session_id)?;
+        let sessions = self.get_http_sessions(interface_uuid)?;
+        let bytes = sessions.poll(&session_id).await;
+        Ok(bytes.map(|bytes| Uint8Array::from(&bytes[..])))
+    }
+
+    /// Closes the given session, e.g. for a DELETE request.
+    /// Returns false if the session does not exist.
+    pub fn http_server_interface_close_session(
+        &self,
+        interface_uuid: String,
+        session_id: String,
+    ) -> Result<bool, JsError> {
+        check_session_id(&session_id)?;
+        Ok(self.get_http_sessions(interface_uuid)?.close(&session_id))
+    }
+
+    /// Closes all sessions, so that pending poll requests are answered
+    /// before the server is shut down
+    pub fn http_server_interface_close_all_sessions(
+        &self,
+        interface_uuid: String,
+    ) -> Result<(), JsError> {
+        self.get_http_sessions(interface_uuid)?.close_all();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_session_ids() {
+        assert!(is_valid_session_id("0f3a9c1e2b7d4a5f8e6c0b1d2a3f4e5d"));
+        assert!(is_valid_session_id("c0ffee-42"));
+        assert!(!is_valid_session_id(""));
+        assert!(!is_valid_session_id("../session"));
+        assert!(!is_valid_session_id("a&stream=1"));
+        assert!(!is_valid_session_id(&"a".repeat(65)));
+    }
+}
@@ -0,0 +1,432 @@
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Mutex;
+use std::time::Duration; // FIXME no-std
+
+use datex_core::network::com_interfaces::com_interface::{
+    ComInterface, ComInterfaceError, ComInterfaceFactory, ComInterfaceInfo,
+    ComInterfaceSockets, ComInterfaceState, ComInterfaceUUID,
+};
+use datex_core::network::com_interfaces::com_interface_properties::{
+    InterfaceDirection, InterfaceProperties,
+};
+use datex_core::network::com_interfaces::com_interface_socket::{
+    ComInterfaceSocket, ComInterfaceSocketUUID,
+};
+use datex_core::network::com_interfaces::socket_provider::SingleSocketProvider;
+use datex_core::stdlib::sync::Arc;
+use datex_core::task::spawn_local;
+use datex_core::{delegate_com_interface_info, set_sync_opener};
+use datex_macros::{com_interface, create_opener};
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use wasm_bindgen::JsError;
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::intercepted_send_block;
+use crate::network::block_interceptors::receive_block;
+use crate::network::com_hub::JSComHub;
+use crate::network::com_hub_events::{
+    notify_socket_closed, notify_socket_registered, state_setter,
+};
+
+/// Seed of the link simulation if no seed is set
+const DEFAULT_SEED: u32 = 0x5eed;
+
+/// Setup data for an in-memory interface that is connected to a loopback
+/// interface of another runtime in the same wasm instance.
+/// The link conditions apply to all blocks sent by this interface, so
+/// both directions of a connection can be configured independently.
+#[derive(Serialize, Deserialize, Tsify, Debug, Clone, Default)]
+#[serde(default)]
+pub struct LoopbackInterfaceSetupData {
+    /// Name of the interface
+    pub name: Option<String>,
+    /// Delay of each block in milliseconds (default: 0)
+    pub latency: Option<u32>,
+    /// Maximum random deviation of the latency in milliseconds (default: 0)
+    pub jitter: Option<u32>,
+    /// Probability that a block is dropped, between 0 and 1 (default: 0)
+    pub packet_loss: Option<f64>,
+    /// Maximum bandwidth in bytes per second, unlimited if not set
+    pub bandwidth: Option<u32>,
+    /// Seed for the random jitter and packet loss. Links with the same
+    /// seed and conditions drop and delay the same blocks.
+    pub seed: Option<u32>,
+}
+
+impl LoopbackInterfaceSetupData {
+    fn is_valid(&self) -> bool {
+        self.packet_loss
+            .is_none_or(|packet_loss| (0.0..=1.0).contains(&packet_loss))
+            && self.bandwidth.is_none_or(|bandwidth| bandwidth > 0)
+    }
+}
+
+/// Timing of a single block on a simulated link
+#[derive(Debug, PartialEq)]
+struct LinkDelivery {
+    /// Time the link is busy sending the block
+    transmission: Duration,
+    /// Time after the transmission until the block is received
+    delay: Duration,
+}
+
+/// Decides deterministically if and when blocks are delivered
+struct LinkSimulator {
+    latency: u32,
+    jitter: u32,
+    packet_loss: f64,
+    bandwidth: Option<u32>,
+    rng_state: u64,
+}
+
+impl LinkSimulator {
+    fn new(setup_data: &LoopbackInterfaceSetupData) -> LinkSimulator {
+        LinkSimulator {
+            latency: setup_data.latency.unwrap_or(0),
+            jitter: setup_data.jitter.unwrap_or(0),
+            packet_loss: setup_data.packet_loss.unwrap_or(0.0),
+            bandwidth: setup_data.bandwidth,
+            rng_state: setup_data.seed.unwrap_or(DEFAULT_SEED) as u64,
+        }
+    }
+
+    /// splitmix64, returns a number between 0 (inclusive) and 1 (exclusive)
+    fn next_random(&mut self) -> f64 {
+        self.rng_state = self.rng_state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.rng_state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns None if the block of the given length is lost
+    fn next_delivery(&mut self, length: usize) -> Option<LinkDelivery> {
+        // random numbers are only drawn for enabled conditions, so that
+        // enabling one condition does not change the outcome of another
+        if self.packet_loss > 0.0 && self.next_random() < self.packet_loss {
+            return None;
+        }
+        let mut delay = self.latency as f64;
+        if self.jitter > 0 {
+            delay += self.jitter as f64 * (2.0 * self.next_random() - 1.0);
+        }
+        let transmission = match self.bandwidth {
+            Some(bandwidth) => {
+                Duration::from_secs_f64(length as f64 / bandwidth as f64)
+            }
+            None => Duration::ZERO,
+        };
+        Some(LinkDelivery {
+            transmission,
+            delay: Duration::from_secs_f64(delay.max(0.0) / 1000.0),
+        })
+    }
+}
+
+/// One side of a loopback connection, shared with the other side
+#[derive(Clone)]
+struct LoopbackSide {
+    interface_uuid: ComInterfaceUUID,
+    sockets: Arc<Mutex<ComInterfaceSockets>>,
+    set_state: Rc<dyn Fn(ComInterfaceState)>,
+    socket_uuid: Rc<RefCell<Option<ComInterfaceSocketUUID>>>,
+}
+
+impl LoopbackSide {
+    fn add_socket(&self) {
+        let socket = ComInterfaceSocket::new(
+            self.interface_uuid.clone(),
+            InterfaceDirection::InOut,
+            1,
+        );
+        self.socket_uuid.replace(Some(socket.uuid.clone()));
+        notify_socket_registered(&socket);
+        self.sockets
+            .lock()
+            .unwrap()
+            .add_socket(Arc::new(Mutex::new(socket)));
+        (self.set_state)(ComInterfaceState::Connected);
+    }
+
+    fn remove_socket(&self) {
+        if let Some(socket_uuid) = self.socket_uuid.take() {
+            self.sockets.lock().unwrap().remove_socket(&socket_uuid);
+            notify_socket_closed(&self.interface_uuid, &socket_uuid);
+            (self.set_state)(ComInterfaceState::NotConnected);
+        }
+    }
+
+    /// Blocks that arrive after the side was disconnected are dropped
+    fn receive(&self, block: &[u8]) {
+        let Some(socket_uuid) = self.socket_uuid.borrow().clone() else {
+            debug!("Dropping block for disconnected loopback interface");
+            return;
+        };
+        let sockets = self.sockets.lock().unwrap();
+        if let Some(socket) = sockets.sockets.get(&socket_uuid) {
+            receive_block(socket, block);
+        }
+    }
+}
+
+pub struct LoopbackJSInterface {
+    name: Option<String>,
+    simulator: LinkSimulator,
+    /// Own side and side of the other interface, set while connected
+    sides: Option<(LoopbackSide, LoopbackSide)>,
+    info: ComInterfaceInfo,
+}
+
+impl SingleSocketProvider for LoopbackJSInterface {
+    fn provide_sockets(&self) -> Arc<Mutex<ComInterfaceSockets>> {
+        self.get_sockets().clone()
+    }
+}
+
+#[com_interface]
+impl LoopbackJSInterface {
+    pub fn new(setup_data: LoopbackInterfaceSetupData) -> LoopbackJSInterface {
+        LoopbackJSInterface {
+            simulator: LinkSimulator::new(&setup_data),
+            name: setup_data.name,
+            sides: None,
+            info: ComInterfaceInfo::default(),
+        }
+    }
+
+    #[create_opener]
+    fn open(&mut self) -> Result<(), ()> {
+        Ok(())
+    }
+
+    fn create_side(&self) -> LoopbackSide {
+        LoopbackSide {
+            interface_uuid: self.get_uuid().clone(),
+            sockets: self.get_sockets().clone(),
+            set_state: state_setter(self),
+            socket_uuid: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    /// Connects the interface to a loopback interface of another runtime,
+    /// both interfaces must not be connected yet
+    pub fn connect(
+        &mut self,
+        other: &mut LoopbackJSInterface,
+    ) -> Result<(), String> {
+        if self.sides.is_some() || other.sides.is_some() {
+            return Err("Loopback interface is already connected".to_string());
+        }
+        let own_side = self.create_side();
+        let other_side = other.create_side();
+        own_side.add_socket();
+        other_side.add_socket();
+        self.sides = Some((own_side.clone(), other_side.clone()));
+        other.sides = Some((other_side, own_side));
+        info!("Loopback interface connected");
+        Ok(())
+    }
+
+    /// Removes the sockets on both sides of the connection
+    fn disconnect(&mut self) {
+        if let Some((own_side, other_side)) = self.sides.take() {
+            own_side.remove_socket();
+            other_side.remove_socket();
+            info!("Loopback interface disconnected");
+        }
+    }
+}
+
+impl ComInterfaceFactory<LoopbackInterfaceSetupData> for LoopbackJSInterface {
+    fn create(
+        setup_data: LoopbackInterfaceSetupData,
+    ) -> Result<LoopbackJSInterface, ComInterfaceError> {
+        if !setup_data.is_valid() {
+            return Err(ComInterfaceError::InvalidSetupData);
+        }
+        Ok(LoopbackJSInterface::new(setup_data))
+    }
+
+    fn get_default_properties() -> InterfaceProperties {
+        InterfaceProperties {
+            interface_type: "loopback".to_string(),
+            channel: "loopback".to_string(),
+            round_trip_time: Duration::from_millis(0),
+            max_bandwidth: u32::MAX,
+            ..InterfaceProperties::default()
+        }
+    }
+}
+
+impl LoopbackJSInterface {
+    fn send_to_socket<'a>(
+        &'a mut self,
+        block: &'a [u8],
+        _: ComInterfaceSocketUUID,
+    ) -> Pin<Box<dyn Future<Output = bool> + 'a>> {
+        let Some((_, other_side)) = self.sides.clone() else {
+            return Box::pin(async move { false });
+        };
+        let delivery = self.simulator.next_delivery(block.len());
+        Box::pin(async move {
+            // lost blocks count as sent, as on a real network
+            let Some(delivery) = delivery else {
+                debug!("Simulating loss of block");
+                return true;
+            };
+            // the link is busy until the block is transmitted
+            if !delivery.transmission.is_zero() {
+                futures_timer::Delay::new(delivery.transmission).await;
+            }
+            if delivery.delay.is_zero() {
+                other_side.receive(block);
+            } else {
+                let block = block.to_vec();
+                spawn_local(async move {
+                    futures_timer::Delay::new(delivery.delay).await;
+                    other_side.receive(&block);
+                });
+            }
+            true
+        })
+    }
+}
+
+impl ComInterface for LoopbackJSInterface {
+    intercepted_send_block!(send_to_socket);
+
+    fn init_properties(&self) -> InterfaceProperties {
+        let round_trip_time =
+            Duration::from_millis(2 * self.simulator.latency as u64);
+        InterfaceProperties {
+            name: self.name.clone(),
+            round_trip_time,
+            max_bandwidth: self.simulator.bandwidth.unwrap_or(u32::MAX),
+            ..Self::get_default_properties()
+        }
+    }
+    fn handle_close<'a>(
+        &'a mut self,
+    ) -> Pin<Box<dyn Future<Output = bool> + 'a>> {
+        self.disconnect();
+        Box::pin(async move { true })
+    }
+    delegate_com_interface_info!();
+    set_sync_opener!(open);
+}
+
+#[wasm_bindgen]
+impl JSComHub {
+    /// Connects a loopback interface to a loopback interface of another
+    /// runtime in the same wasm instance. Blocks are passed in memory,
+    /// delayed and dropped as configured in the setup data of the sender.
+    pub fn loopback_interface_connect(
+        &self,
+        interface_uuid: String,
+        other_com_hub: &JSComHub,
+        other_interface_uuid: String,
+    ) -> Result<(), JsError> {
+        if interface_uuid == other_interface_uuid {
+            return Err(JsError::new(
+                "Loopback interface cannot be connected to itself",
+            ));
+        }
+        let interface = self
+            .get_interface_for_uuid::<LoopbackJSInterface>(interface_uuid)
+            .map_err(|e| JsError::new(&format!("{e:?}")))?;
+        let other_interface = other_com_hub
+            .get_interface_for_uuid::<LoopbackJSInterface>(other_interface_uuid)
+            .map_err(|e| JsError::new(&format!("{e:?}")))?;
+        interface
+            .borrow_mut()
+            .connect(&mut other_interface.borrow_mut())
+            .map_err(|e| JsError::new(&e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simulate(
+        setup_data: &LoopbackInterfaceSetupData,
+        count: usize,
+    ) -> Vec<Option<LinkDelivery>> {
+        let mut simulator = LinkSimulator::new(setup_data);
+        (0..count).map(|_| simulator.next_delivery(100)).collect()
+    }
+
+    #[test]
+    fn delivers_immediately_without_conditions() {
+        let deliveries = simulate(&LoopbackInterfaceSetupData::default(), 10);
+        for delivery in deliveries {
+            assert_eq!(
+                delivery,
+                Some(LinkDelivery {
+                    transmission: Duration::ZERO,
+                    delay: Duration::ZERO,
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn simulation_is_deterministic() {
+        let setup_data = LoopbackInterfaceSetupData {
+            latency: Some(50),
+            jitter: Some(20),
+            packet_loss: Some(0.3),
+            seed: Some(42),
+            ..LoopbackInterfaceSetupData::default()
+        };
+        let deliveries = simulate(&setup_data, 100);
+        assert_eq!(deliveries, simulate(&setup_data, 100));
+
+        let lost = deliveries.iter().filter(|d| d.is_none()).count();
+        assert!((10..50).contains(&lost));
+        for delivery in deliveries.into_iter().flatten() {
+            assert!(delivery.delay >= Duration::from_millis(30));
+            assert!(delivery.delay <= Duration::from_millis(70));
+        }
+
+        let other_seed = LoopbackInterfaceSetupData {
+            seed: Some(43),
+            ..setup_data.clone()
+        };
+        assert_ne!(simulate(&other_seed, 100), simulate(&setup_data, 100));
+    }
+
+    #[test]
+    fn applies_bandwidth_and_loss() {
+        let setup_data = LoopbackInterfaceSetupData {
+            bandwidth: Some(1000),
+            ..LoopbackInterfaceSetupData::default()
+        };
+        assert_eq!(
+            simulate(&setup_data, 1)[0],
+            Some(LinkDelivery {
+                transmission: Duration::from_millis(100),
+                delay: Duration::ZERO,
+            })
+        );
+
+        let setup_data = LoopbackInterfaceSetupData {
+            packet_loss: Some(1.0),
+            ..LoopbackInterfaceSetupData::default()
+        };
+        assert!(simulate(&setup_data, 10).iter().all(Option::is_none));
+        assert!(
+            !LoopbackInterfaceSetupData {
+                packet_loss: Some(1.5),
+                ..LoopbackInterfaceSetupData::default()
+            }
+            .is_valid()
+        );
+    }
+}
@@ -1,16 +1,94 @@
+use std::collections::HashSet;
+use std::time::Duration; // FIXME no-std
 use std::{cell::RefCell, rc::Rc};
 
 use crate::define_registry;
 use datex_core::network::com_hub::InterfacePriority;
-use datex_core::network::com_interfaces::{
-    com_interface::ComInterface,
-    default_com_interfaces::webrtc::matchbox_client_interface::MatchboxClientInterface,
+use datex_core::network::com_interfaces::com_interface::{
+    ComInterface, ComInterfaceError, ComInterfaceFactory, ComInterfaceUUID,
 };
+use datex_core::network::com_interfaces::com_interface_properties::InterfaceProperties;
+use datex_core::network::com_interfaces::com_interface_socket::ComInterfaceSocketUUID;
+use datex_core::network::com_interfaces::default_com_interfaces::webrtc::matchbox_client_interface::MatchboxClientInterface;
+use datex_core::network::com_interfaces::default_com_interfaces::webrtc::webrtc_common::structures::RTCIceServer;
+use datex_core::task::spawn_local;
+use js_sys::Function;
 use log::error;
-use wasm_bindgen::{prelude::wasm_bindgen, JsError, JsValue};
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use url::Url;
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::{JsError, JsValue};
 use wasm_bindgen_futures::future_to_promise;
 use web_sys::js_sys::Promise;
 
+use crate::network::com_hub::JSComHub;
+
+/// Interval in which the sockets of the interface are checked
+/// for joined and left peers. The matchbox interface of datex-core
+/// does not notify about peer state changes, so the peer events are
+/// emulated by comparing its sockets
+const PEER_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Setup data for a WebRTC interface that uses a matchbox signaling
+/// server to connect to all peers in a room.
+#[derive(Serialize, Deserialize, Tsify, Debug, Clone)]
+pub struct MatchboxJSInterfaceSetupData {
+    /// URL of the matchbox signaling server, e.g. "wss://example.com"
+    pub address: String,
+    /// Name of the room to join, appended to the address as path.
+    /// If not set, the address is expected to contain the room.
+    pub room: Option<String>,
+    /// If true, blocks are sent over an unordered channel without
+    /// retransmissions (default: false)
+    #[serde(default)]
+    pub unreliable: bool,
+    /// ICE server used to establish connections to the peers
+    pub ice_server: Option<RTCIceServer>,
+}
+
+impl MatchboxJSInterfaceSetupData {
+    fn get_room_url(&self) -> Result<Url, ComInterfaceError> {
+        let mut url = Url::parse(&self.address)
+            .map_err(|_| ComInterfaceError::InvalidSetupData)?;
+        if !matches!(url.scheme(), "ws" | "wss") {
+            error!("Matchbox address must use the ws or wss scheme");
+            return Err(ComInterfaceError::InvalidSetupData);
+        }
+        if let Some(room) = &self.room {
+            url.path_segments_mut()
+                .map_err(|_| ComInterfaceError::InvalidSetupData)?
+                .pop_if_empty()
+                .push(room);
+        }
+        Ok(url)
+    }
+}
+
+impl ComInterfaceFactory<MatchboxJSInterfaceSetupData>
+    for MatchboxClientInterface
+{
+    fn create(
+        setup_data: MatchboxJSInterfaceSetupData,
+    ) -> Result<MatchboxClientInterface, ComInterfaceError> {
+        let url = setup_data.get_room_url()?;
+        let ice_server = setup_data.ice_server;
+        if setup_data.unreliable {
+            MatchboxClientInterface::new_unreliable(url.as_str(), ice_server)
+        } else {
+            MatchboxClientInterface::new_reliable(url.as_str(), ice_server)
+        }
+    }
+
+    fn get_default_properties() -> InterfaceProperties {
+        InterfaceProperties {
+            interface_type: "matchbox".to_string(),
+            channel: "matchbox".to_string(),
+            ..InterfaceProperties::default()
+        }
+    }
+}
+
 define_registry!(MatchboxClientRegistry, MatchboxClientInterface);
 
 #[wasm_bindgen]
@@ -37,3 +115,95 @@ impl MatchboxClientRegistry {
         })
     }
 }
+
+fn get_socket_uuids(
+    interface: &MatchboxClientInterface,
+) -> HashSet<ComInterfaceSocketUUID> {
+    interface
+        .get_sockets()
+        .lock()
+        .unwrap()
+        .sockets
+        .keys()
+        .cloned()
+        .collect()
+}
+
+#[wasm_bindgen]
+impl JSComHub {
+    /// Sets the callback that is invoked with ("join" | "leave", socket_uuid)
+    /// when a peer joins or leaves the room of the matchbox interface.
+    /// Each peer is connected via its own socket. A previously set
+    /// callback of the interface is replaced.
+    ///
+    /// The events are detected by comparing the sockets of the interface
+    /// every 250ms, so they are delayed by up to 250ms and a peer that
+    /// joins and leaves within one interval is not reported. When the
+    /// interface is removed, a leave event is emitted for each peer.
+    pub fn matchbox_interface_set_on_peer_event(
+        &self,
+        interface_uuid: String,
+        on_peer_event: Function,
+    ) -> Result<(), JsError> {
+        let interface_uuid = ComInterfaceUUID::from_string(interface_uuid);
+        let mut peers = self
+            .com_hub()
+            .get_interface_by_uuid::<MatchboxClientInterface>(&interface_uuid)
+            .map(|interface| get_socket_uuids(&interface.borrow()))
+            .ok_or_else(|| JsError::new("Failed to find matchbox interface"))?;
+        let callbacks = self.matchbox_peer_event_callbacks.clone();
+        let is_polling = callbacks
+            .borrow_mut()
+            .insert(interface_uuid.clone(), on_peer_event)
+            .is_some();
+        if is_polling {
+            return Ok(());
+        }
+
+        let emit = {
+            let callbacks = callbacks.clone();
+            let interface_uuid = interface_uuid.clone();
+            move |event: &str, socket_uuid: &ComInterfaceSocketUUID| {
+                let Some(on_peer_event) =
+                    callbacks.borrow().get(&interface_uuid).cloned()
+                else {
+                    return;
+                };
+                if let Err(e) = on_peer_event.call2(
+                    &JsValue::NULL,
+                    &JsValue::from_str(event),
+                    &JsValue::from_str(&socket_uuid.0.to_string()),
+                ) {
+                    error!("Error in matchbox peer event callback: {e:?}");
+                }
+            }
+        };
+
+        // the sockets are managed by the matchbox interface, so changes
+        // are detected by comparing the sockets until it is removed.
+        // There is one loop per interface that calls the current callback
+        let js_com_hub = self.clone();
+        spawn_local(async move {
+            while let Some(interface) = js_com_hub
+                .com_hub()
+                .get_interface_by_uuid::<MatchboxClientInterface>(
+                &interface_uuid,
+            ) {
+                let current = get_socket_uuids(&interface.borrow());
+                for socket_uuid in current.difference(&peers) {
+                    emit("join", socket_uuid);
+                }
+                for socket_uuid in peers.difference(&current) {
+                    emit("leave", socket_uuid);
+                }
+                peers = current;
+                futures_timer::Delay::new(PEER_POLL_INTERVAL).await;
+            }
+            for socket_uuid in &peers {
+                emit("leave", socket_uuid);
+            }
+            callbacks.borrow_mut().remove(&interface_uuid);
+        });
+        Ok(())
+    }
+}
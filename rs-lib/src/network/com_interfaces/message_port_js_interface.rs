@@ -0,0 +1,335 @@
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Mutex;
+use std::time::Duration; // FIXME no-std
+
+use datex_core::network::com_interfaces::com_interface::{
+    ComInterface, ComInterfaceError, ComInterfaceFactory, ComInterfaceInfo,
+    ComInterfaceSockets, ComInterfaceState, ComInterfaceUUID,
+};
+use datex_core::network::com_interfaces::com_interface_properties::{
+    InterfaceDirection, InterfaceProperties,
+};
+use datex_core::network::com_interfaces::com_interface_socket::{
+    ComInterfaceSocket, ComInterfaceSocketUUID,
+};
+use datex_core::network::com_interfaces::socket_provider::SingleSocketProvider;
+use datex_core::stdlib::sync::Arc;
+use datex_core::{delegate_com_interface_info, set_sync_opener};
+use datex_macros::{com_interface, create_opener};
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use wasm_bindgen::prelude::{Closure, wasm_bindgen};
+use wasm_bindgen::{JsCast, JsError, JsValue};
+use web_sys::js_sys::{Array, Function, Reflect, Uint8Array};
+use web_sys::{Event, EventTarget, MessageEvent, MessagePort};
+
+use crate::intercepted_send_block;
+use crate::network::block_interceptors::receive_block;
+use crate::network::com_hub::JSComHub;
+use crate::network::com_hub_events::{
+    notify_socket_closed, notify_socket_registered, state_setter,
+};
+
+/// Sent when the interface is connected to a port. The other side creates
+/// its socket and answers, so that no blocks are lost if one side starts
+/// listening later (e.g. a worker that is still initializing).
+const HELLO_MESSAGE: &str = "datex:hello";
+const HELLO_ACK_MESSAGE: &str = "datex:hello-ack";
+/// Sent when the interface is closed
+const CLOSE_MESSAGE: &str = "datex:close";
+
+/// Setup data for an interface that connects to another runtime over a
+/// MessagePort, a Worker or the global scope of a worker.
+/// The port itself is passed to the interface after it was created.
+#[derive(Serialize, Deserialize, Tsify, Debug, Clone)]
+pub struct MessagePortInterfaceSetupData {
+    /// Name of the interface, e.g. the name of the worker
+    pub name: Option<String>,
+}
+
+/// Connection to the port that is shared with the event listeners
+#[derive(Clone)]
+struct MessagePortConnection {
+    port: EventTarget,
+    post_message: Function,
+    interface_uuid: ComInterfaceUUID,
+    sockets: Arc<Mutex<ComInterfaceSockets>>,
+    set_state: Rc<dyn Fn(ComInterfaceState)>,
+    socket_uuid: Rc<RefCell<Option<ComInterfaceSocketUUID>>>,
+}
+
+impl MessagePortConnection {
+    /// Creates a connection to any object with a postMessage method that
+    /// emits message events (MessagePort, Worker, DedicatedWorkerGlobalScope)
+    fn new(
+        port: JsValue,
+        interface_uuid: ComInterfaceUUID,
+        sockets: Arc<Mutex<ComInterfaceSockets>>,
+        set_state: Rc<dyn Fn(ComInterfaceState)>,
+    ) -> Result<MessagePortConnection, String> {
+        let post_message = Reflect::get(&port, &"postMessage".into())
+            .ok()
+            .and_then(|post_message| post_message.dyn_into::<Function>().ok())
+            .ok_or_else(|| "Port has no postMessage method".to_string())?;
+        let port = port
+            .dyn_into::<EventTarget>()
+            .map_err(|_| "Port is not an EventTarget".to_string())?;
+        Ok(MessagePortConnection {
+            port,
+            post_message,
+            interface_uuid,
+            sockets,
+            set_state,
+            socket_uuid: Rc::new(RefCell::new(None)),
+        })
+    }
+
+    fn post(&self, message: &JsValue, transfer: &Array) -> bool {
+        self.post_message
+            .call2(&self.port, message, transfer)
+            .map_err(|e| error!("Error posting message: {e:?}"))
+            .is_ok()
+    }
+
+    fn post_control(&self, message: &str) -> bool {
+        self.post(&JsValue::from_str(message), &Array::new())
+    }
+
+    /// The block is copied out of the wasm memory once, its buffer is
+    /// then transferred to the other side instead of being cloned
+    fn send(&self, block: &[u8]) -> bool {
+        let array = Uint8Array::from(block);
+        self.post(&array, &Array::of1(&array.buffer()))
+    }
+
+    fn handle_message(&self, data: JsValue) {
+        if let Some(message) = data.as_string() {
+            match message.as_str() {
+                HELLO_MESSAGE => {
+                    self.add_socket();
+                    self.post_control(HELLO_ACK_MESSAGE);
+                }
+                HELLO_ACK_MESSAGE => self.add_socket(),
+                CLOSE_MESSAGE => self.remove_socket(),
+                _ => warn!("Received unknown message: {message}"),
+            }
+            return;
+        }
+        let Ok(array) = data.dyn_into::<Uint8Array>() else {
+            warn!("Received message that is not a block");
+            return;
+        };
+        let Some(socket_uuid) = self.socket_uuid.borrow().clone() else {
+            warn!("Received block before the port was connected");
+            return;
+        };
+        let sockets = self.sockets.lock().unwrap();
+        if let Some(socket) = sockets.sockets.get(&socket_uuid) {
+            receive_block(socket, &array.to_vec());
+        }
+    }
+
+    fn add_socket(&self) {
+        if self.socket_uuid.borrow().is_some() {
+            return;
+        }
+        let socket = ComInterfaceSocket::new(
+            self.interface_uuid.clone(),
+            InterfaceDirection::InOut,
+            1,
+        );
+        self.socket_uuid.replace(Some(socket.uuid.clone()));
+        notify_socket_registered(&socket);
+        self.sockets
+            .lock()
+            .unwrap()
+            .add_socket(Arc::new(Mutex::new(socket)));
+        info!("Message port connected");
+        (self.set_state)(ComInterfaceState::Connected);
+    }
+
+    fn remove_socket(&self) {
+        if let Some(socket_uuid) = self.socket_uuid.take() {
+            self.sockets.lock().unwrap().remove_socket(&socket_uuid);
+            notify_socket_closed(&self.interface_uuid, &socket_uuid);
+            info!("Message port disconnected");
+            (self.set_state)(ComInterfaceState::NotConnected);
+        }
+    }
+}
+
+pub struct MessagePortJSInterface {
+    name: Option<String>,
+    connection: Option<MessagePortConnection>,
+    listeners: Vec<(&'static str, Closure<dyn FnMut(Event)>)>,
+    info: ComInterfaceInfo,
+}
+
+impl SingleSocketProvider for MessagePortJSInterface {
+    fn provide_sockets(&self) -> Arc<Mutex<ComInterfaceSockets>> {
+        self.get_sockets().clone()
+    }
+}
+
+#[com_interface]
+impl MessagePortJSInterface {
+    pub fn new(
+        setup_data: MessagePortInterfaceSetupData,
+    ) -> MessagePortJSInterface {
+        MessagePortJSInterface {
+            name: setup_data.name,
+            connection: None,
+            listeners: Vec::new(),
+            info: ComInterfaceInfo::default(),
+        }
+    }
+
+    #[create_opener]
+    fn open(&mut self) -> Result<(), ()> {
+        Ok(())
+    }
+
+    /// Connects the interface to the given port, a previously
+    /// connected port is disconnected
+    pub fn set_port(&mut self, port: JsValue) -> Result<(), String> {
+        self.disconnect();
+        let connection = MessagePortConnection::new(
+            port,
+            self.get_uuid().clone(),
+            self.get_sockets().clone(),
+            state_setter(self),
+        )?;
+        self.add_event_listeners(&connection);
+        // messages of a MessagePort are only dispatched after start
+        if let Some(port) = connection.port.dyn_ref::<MessagePort>() {
+            port.start();
+        }
+        connection.post_control(HELLO_MESSAGE);
+        self.connection = Some(connection);
+        Ok(())
+    }
+
+    fn add_event_listeners(&mut self, connection: &MessagePortConnection) {
+        let message_connection = connection.clone();
+        let on_message = Closure::<dyn FnMut(Event)>::new(move |e: Event| {
+            if let Some(e) = e.dyn_ref::<MessageEvent>() {
+                message_connection.handle_message(e.data());
+            }
+        });
+        // only emitted by MessagePorts in some browsers
+        let close_connection = connection.clone();
+        let on_close = Closure::<dyn FnMut(Event)>::new(move |_: Event| {
+            close_connection.remove_socket();
+        });
+
+        for (event, listener) in [("message", on_message), ("close", on_close)]
+        {
+            if let Err(e) = connection.port.add_event_listener_with_callback(
+                event,
+                listener.as_ref().unchecked_ref(),
+            ) {
+                error!("Failed to add message port {event} listener: {e:?}");
+            }
+            self.listeners.push((event, listener));
+        }
+    }
+
+    fn disconnect(&mut self) {
+        let Some(connection) = self.connection.take() else {
+            return;
+        };
+        debug!("Disconnecting message port");
+        connection.post_control(CLOSE_MESSAGE);
+        for (event, listener) in self.listeners.drain(..) {
+            let _ = connection.port.remove_event_listener_with_callback(
+                event,
+                listener.as_ref().unchecked_ref(),
+            );
+        }
+        connection.remove_socket();
+        // workers and worker scopes are not closed by the interface
+        if let Some(port) = connection.port.dyn_ref::<MessagePort>() {
+            port.close();
+        }
+    }
+}
+
+impl ComInterfaceFactory<MessagePortInterfaceSetupData>
+    for MessagePortJSInterface
+{
+    fn create(
+        setup_data: MessagePortInterfaceSetupData,
+    ) -> Result<MessagePortJSInterface, ComInterfaceError> {
+        Ok(MessagePortJSInterface::new(setup_data))
+    }
+
+    fn get_default_properties() -> InterfaceProperties {
+        InterfaceProperties {
+            interface_type: "message-port".to_string(),
+            channel: "message-port".to_string(),
+            round_trip_time: Duration::from_millis(1),
+            max_bandwidth: u32::MAX,
+            ..InterfaceProperties::default()
+        }
+    }
+}
+
+impl MessagePortJSInterface {
+    fn send_to_socket<'a>(
+        &'a mut self,
+        block: &'a [u8],
+        _: ComInterfaceSocketUUID,
+    ) -> Pin<Box<dyn Future<Output = bool> + 'a>> {
+        let success = match &self.connection {
+            Some(connection) => connection.send(block),
+            None => {
+                error!("Error sending message: no port connected");
+                false
+            }
+        };
+        Box::pin(async move { success })
+    }
+}
+
+impl ComInterface for MessagePortJSInterface {
+    intercepted_send_block!(send_to_socket);
+
+    fn init_properties(&self) -> InterfaceProperties {
+        InterfaceProperties {
+            name: self.name.clone(),
+            ..Self::get_default_properties()
+        }
+    }
+    fn handle_close<'a>(
+        &'a mut self,
+    ) -> Pin<Box<dyn Future<Output = bool> + 'a>> {
+        self.disconnect();
+        Box::pin(async move { true })
+    }
+    delegate_com_interface_info!();
+    set_sync_opener!(open);
+}
+
+#[wasm_bindgen]
+impl JSComHub {
+    /// Connects the message port interface to a MessagePort, a Worker or
+    /// the global scope of a worker. The runtime on the other side must
+    /// connect a message port interface to the corresponding port.
+    pub fn message_port_interface_set_port(
+        &self,
+        interface_uuid: String,
+        port: JsValue,
+    ) -> Result<(), JsError> {
+        let interface = self
+            .get_interface_for_uuid::<MessagePortJSInterface>(interface_uuid)
+            .map_err(|e| JsError::new(&format!("{e:?}")))?;
+        interface
+            .borrow_mut()
+            .set_port(port)
+            .map_err(|e| JsError::new(&e))
+    }
+}
@@ -1,10 +1,30 @@
 pub mod base_interface;
+pub mod custom_js_interface;
 
+#[cfg(feature = "wasm_broadcast_channel")]
+pub mod broadcast_channel_js_interface;
+#[cfg(feature = "wasm_http_client")]
+pub mod http_client_js_interface;
+#[cfg(feature = "wasm_http_server")]
+pub mod http_server_js_interface;
+#[cfg(feature = "wasm_loopback")]
+pub mod loopback_js_interface;
+#[cfg(feature = "wasm_webrtc")]
+pub mod matchbox_js_interface;
+#[cfg(feature = "wasm_message_port")]
+pub mod message_port_js_interface;
 #[cfg(feature = "wasm_serial")]
 pub mod serial_js_interface;
 #[cfg(feature = "wasm_webrtc")]
 pub mod webrtc_js_interface;
+#[cfg(feature = "wasm_webtransport")]
+pub mod webtransport_js_interface;
 #[cfg(feature = "wasm_websocket_client")]
 pub mod websocket_client_js_interface;
+#[cfg(any(
+    feature = "wasm_websocket_client",
+    feature = "wasm_websocket_server"
+))]
+pub mod websocket_js_common;
 #[cfg(feature = "wasm_websocket_server")]
 pub mod websocket_server_js_interface;
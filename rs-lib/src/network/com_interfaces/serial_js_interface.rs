@@ -1,4 +1,4 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::future::Future;
 use std::pin::Pin;
 use std::rc::Rc;
@@ -6,74 +6,168 @@ use std::sync::Mutex;
 use std::time::Duration; // FIXME no-std
 
 use datex_core::{ delegate_com_interface_info, set_opener};
-use datex_core::network::com_interfaces::com_interface::{ComInterface, ComInterfaceError, ComInterfaceFactory, ComInterfaceInfo};
-use datex_core::network::com_interfaces::com_interface_properties::InterfaceProperties;
-use datex_core::network::com_interfaces::com_interface_socket::ComInterfaceSocketUUID;
-use datex_core::network::com_interfaces::default_com_interfaces::serial::serial_common::{SerialError, SerialInterfaceSetupData};
+use datex_core::network::com_interfaces::com_interface::{ComInterface, ComInterfaceError, ComInterfaceFactory, ComInterfaceInfo, ComInterfaceSockets, ComInterfaceUUID};
+use datex_core::network::com_interfaces::com_interface_properties::{InterfaceDirection, InterfaceProperties};
+use datex_core::network::com_interfaces::com_interface_socket::{ComInterfaceSocket, ComInterfaceSocketUUID};
+use datex_core::network::com_interfaces::default_com_interfaces::serial::serial_common::SerialError;
+use datex_core::network::com_interfaces::socket_provider::SingleSocketProvider;
 use datex_core::stdlib::sync::Arc;
 
 use datex_core::network::com_interfaces::com_interface::ComInterfaceState;
 
+use crate::intercepted_send_block;
+use crate::network::block_interceptors::receive_block;
+use crate::network::com_hub_events::{
+    notify_socket_closed, notify_socket_registered, state_setter,
+};
+use crate::network::framing::{FrameCodec, FramingConfig};
 use crate::wrap_error_for_js;
 use datex_core::task::spawn_with_panic_notify_default;
-use log::{debug, error};
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
 use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::Closure;
 use wasm_bindgen_futures::JsFuture;
-use web_sys::SerialPort;
-use web_sys::js_sys::Uint8Array;
+use web_sys::js_sys::{Array, Uint8Array};
 use web_sys::{
-    ReadableStreamDefaultReader, SerialOptions, WritableStreamDefaultWriter,
-    js_sys,
+    Event, FlowControlType, ParityType, ReadableStreamDefaultReader, Serial,
+    SerialOptions, SerialPortFilter, SerialPortRequestOptions,
+    WritableStreamDefaultWriter, js_sys,
 };
+use web_sys::{SerialPort, SerialPortInfo};
 
-pub struct SerialJSInterface {
-    port: Option<SerialPort>,
-    tx: Option<Rc<RefCell<WritableStreamDefaultWriter>>>,
-    info: ComInterfaceInfo,
-    options: SerialOptions,
+/// Setup data for the Web Serial interface.
+/// Superset of the common serial setup data with options that are
+/// only available for the JS implementation.
+#[derive(Serialize, Deserialize, Tsify, Debug, Clone)]
+pub struct SerialJSInterfaceSetupData {
+    pub port_name: Option<String>,
+    pub baud_rate: u32,
+    /// Framing used to delimit blocks on the serial line (default: none)
+    #[serde(default)]
+    pub framing: FramingConfig,
+    /// USB filters for the port selection. If a previously granted port
+    /// matches one of the filters, it is reused without user interaction
+    /// when `reuse_granted_port` is enabled.
+    #[serde(default)]
+    pub filters: Vec<SerialPortFilterDX>,
+    /// If true, an already granted port (`navigator.serial.getPorts()`) is
+    /// opened instead of prompting the user (default: false)
+    #[serde(default)]
+    pub reuse_granted_port: bool,
+    /// Number of data bits per frame, 7 or 8 (default: 8)
+    pub data_bits: Option<u8>,
+    /// Number of stop bits, 1 or 2 (default: 1)
+    pub stop_bits: Option<u8>,
+    /// Parity mode (default: none)
+    pub parity: Option<SerialParity>,
+    /// Size of the read and write buffers in bytes (default: 255)
+    pub buffer_size: Option<u32>,
+    /// Flow control mode (default: none)
+    pub flow_control: Option<SerialFlowControl>,
 }
 
-wrap_error_for_js!(JsSerialError, datex_core::network::com_interfaces::default_com_interfaces::serial::serial_common::SerialError);
+#[derive(Serialize, Deserialize, Tsify, Debug, Clone)]
+pub struct SerialPortFilterDX {
+    pub usb_vendor_id: u16,
+    pub usb_product_id: Option<u16>,
+}
 
-use datex_macros::{com_interface, create_opener};
+impl SerialPortFilterDX {
+    fn matches(&self, info: &SerialPortInfo) -> bool {
+        info.get_usb_vendor_id() == Some(self.usb_vendor_id)
+            && (self.usb_product_id.is_none()
+                || info.get_usb_product_id() == self.usb_product_id)
+    }
+}
 
-#[com_interface]
-impl SerialJSInterface {
-    pub fn new(baud_rate: u32) -> Result<SerialJSInterface, JsSerialError> {
-        let interface = SerialJSInterface {
-            info: ComInterfaceInfo::new(),
-            tx: None,
-            port: None,
-            options: SerialOptions::new(baud_rate),
-        };
-        Ok(interface)
+#[derive(Serialize, Deserialize, Tsify, Debug, Clone, Copy)]
+pub enum SerialParity {
+    None,
+    Even,
+    Odd,
+}
+
+impl From<SerialParity> for ParityType {
+    fn from(parity: SerialParity) -> Self {
+        match parity {
+            SerialParity::None => ParityType::None,
+            SerialParity::Even => ParityType::Even,
+            SerialParity::Odd => ParityType::Odd,
+        }
     }
+}
 
-    #[create_opener]
-    async fn open(&mut self) -> Result<(), SerialError> {
-        let window = web_sys::window()
-            .ok_or(SerialError::Other("Unsupported platform".to_string()))?;
-        let navigator = window.navigator();
-        let serial = navigator.serial();
+#[derive(Serialize, Deserialize, Tsify, Debug, Clone, Copy)]
+pub enum SerialFlowControl {
+    None,
+    Hardware,
+}
 
-        let port_promise = serial.request_port();
-        let port_js = JsFuture::from(port_promise)
-            .await
-            .map_err(|_| SerialError::PermissionError)?;
-        let port: SerialPort = port_js.into();
+impl From<SerialFlowControl> for FlowControlType {
+    fn from(flow_control: SerialFlowControl) -> Self {
+        match flow_control {
+            SerialFlowControl::None => FlowControlType::None,
+            SerialFlowControl::Hardware => FlowControlType::Hardware,
+        }
+    }
+}
 
+/// Connection to the serial port that is shared with the read loop and the
+/// connect/disconnect event listeners, so that the port can be reopened
+/// after the device was unplugged and plugged in again.
+#[derive(Clone)]
+struct SerialConnection {
+    interface_uuid: ComInterfaceUUID,
+    sockets: Arc<Mutex<ComInterfaceSockets>>,
+    set_state: Rc<dyn Fn(ComInterfaceState)>,
+    options: SerialOptions,
+    framing: FramingConfig,
+    port: Rc<RefCell<Option<SerialPort>>>,
+    reader: Rc<RefCell<Option<ReadableStreamDefaultReader>>>,
+    writer: Rc<RefCell<Option<WritableStreamDefaultWriter>>>,
+    closed: Rc<Cell<bool>>,
+}
+
+impl SerialConnection {
+    fn is_connected(&self) -> bool {
+        self.writer.borrow().is_some()
+    }
+
+    async fn connect(&self, port: SerialPort) -> Result<(), SerialError> {
         JsFuture::from(port.open(&self.options))
             .await
             .map_err(|_| SerialError::PortNotFound)?;
 
-        let readable = port.readable();
-        let reader = readable
+        let reader = port
+            .readable()
             .get_reader()
             .dyn_into::<ReadableStreamDefaultReader>()
             .unwrap();
-        let writable = port.writable();
-        let writer = writable.get_writer().unwrap();
-        self.tx = Some(Rc::new(RefCell::new(writer)));
+        let writer = port.writable().get_writer().unwrap();
+        self.reader.replace(Some(reader.clone()));
+        self.writer.replace(Some(writer));
+        self.port.replace(Some(port.clone()));
+
+        // The serial link is a single point-to-point connection, so all
+        // incoming data is pushed into the receive queue of one socket.
+        // The FrameCodec buffers partial frames across reads and only
+        // yields complete payloads. Without framing, the raw chunks are
+        // passed through and reassembled by the ComHub's block collector.
+        let socket = ComInterfaceSocket::new(
+            self.interface_uuid.clone(),
+            InterfaceDirection::InOut,
+            1,
+        );
+        let socket_uuid = socket.uuid.clone();
+        notify_socket_registered(&socket);
+        let socket = Arc::new(Mutex::new(socket));
+        self.sockets.lock().unwrap().add_socket(socket.clone());
+        (self.set_state)(ComInterfaceState::Connected);
+
+        let connection = self.clone();
+        let mut codec = FrameCodec::new(self.framing);
         spawn_with_panic_notify_default(async move {
             loop {
                 let result = JsFuture::from(reader.read()).await;
@@ -95,7 +189,10 @@ impl SerialJSInterface {
                                 .dyn_into::<Uint8Array>()
                                 .unwrap()
                                 .to_vec();
-                            println!("Received bytes: {bytes:?}");
+                            debug!("Received {} bytes", bytes.len());
+                            for payload in codec.decode(&bytes) {
+                                receive_block(&socket, &payload);
+                            }
                         }
                     }
                     Err(_) => {
@@ -105,17 +202,271 @@ impl SerialJSInterface {
                 }
             }
             reader.release_lock();
+            connection.disconnect(&port, &socket_uuid).await;
         });
-        self.port = Some(port.clone());
         Ok(())
     }
+
+    /// Releases the streams and closes the port after the read loop ended,
+    /// either because the device was lost or the interface was closed
+    async fn disconnect(
+        &self,
+        port: &SerialPort,
+        socket_uuid: &ComInterfaceSocketUUID,
+    ) {
+        self.reader.take();
+        if let Some(writer) = self.writer.take() {
+            writer.release_lock();
+        }
+        self.sockets.lock().unwrap().remove_socket(socket_uuid);
+        notify_socket_closed(&self.interface_uuid, socket_uuid);
+        // the port must be closed before it can be reopened
+        let _ = JsFuture::from(port.close()).await;
+
+        if !self.closed.get() {
+            warn!("Serial port disconnected, waiting for device to reconnect");
+            (self.set_state)(ComInterfaceState::NotConnected);
+        }
+    }
+
+    /// Cancels the pending read, which ends the read loop and
+    /// disconnects the port
+    async fn cancel_read(&self) {
+        let reader = self.reader.borrow().clone();
+        if let Some(reader) = reader {
+            let _ = JsFuture::from(reader.cancel()).await;
+        }
+    }
 }
 
-impl ComInterfaceFactory<SerialInterfaceSetupData> for SerialJSInterface {
+fn matches_filters(filters: &[SerialPortFilterDX], port: &SerialPort) -> bool {
+    if filters.is_empty() {
+        return true;
+    }
+    let info = port.get_info();
+    filters.iter().any(|filter| filter.matches(&info))
+}
+
+fn event_target_port(event: &Event) -> Option<SerialPort> {
+    event.target()?.dyn_into::<SerialPort>().ok()
+}
+
+type SerialEventListener = Closure<dyn FnMut(Event)>;
+
+pub struct SerialJSInterface {
+    info: ComInterfaceInfo,
+    options: SerialOptions,
+    framing: FramingConfig,
+    filters: Vec<SerialPortFilterDX>,
+    reuse_granted_port: bool,
+    connection: Option<SerialConnection>,
+    listeners: Vec<(&'static str, SerialEventListener)>,
+}
+
+impl SingleSocketProvider for SerialJSInterface {
+    fn provide_sockets(&self) -> Arc<Mutex<ComInterfaceSockets>> {
+        self.get_sockets().clone()
+    }
+}
+
+wrap_error_for_js!(JsSerialError, datex_core::network::com_interfaces::default_com_interfaces::serial::serial_common::SerialError);
+
+use datex_macros::{com_interface, create_opener};
+
+#[com_interface]
+impl SerialJSInterface {
+    pub fn new(
+        setup_data: SerialJSInterfaceSetupData,
+    ) -> Result<SerialJSInterface, JsSerialError> {
+        let options = SerialOptions::new(setup_data.baud_rate);
+        if let Some(data_bits) = setup_data.data_bits {
+            if data_bits != 7 && data_bits != 8 {
+                return Err(SerialError::Other(format!(
+                    "Invalid number of data bits: {data_bits}"
+                ))
+                .into());
+            }
+            options.set_data_bits(data_bits);
+        }
+        if let Some(stop_bits) = setup_data.stop_bits {
+            if stop_bits != 1 && stop_bits != 2 {
+                return Err(SerialError::Other(format!(
+                    "Invalid number of stop bits: {stop_bits}"
+                ))
+                .into());
+            }
+            options.set_stop_bits(stop_bits);
+        }
+        if let Some(parity) = setup_data.parity {
+            options.set_parity(parity.into());
+        }
+        if let Some(buffer_size) = setup_data.buffer_size {
+            options.set_buffer_size(buffer_size);
+        }
+        if let Some(flow_control) = setup_data.flow_control {
+            options.set_flow_control(flow_control.into());
+        }
+        let interface = SerialJSInterface {
+            info: ComInterfaceInfo::new(),
+            options,
+            framing: setup_data.framing,
+            filters: setup_data.filters,
+            reuse_granted_port: setup_data.reuse_granted_port,
+            connection: None,
+            listeners: vec![],
+        };
+        Ok(interface)
+    }
+
+    /// Returns a previously granted port matching the filters if
+    /// `reuse_granted_port` is enabled, otherwise prompts the user
+    /// to select a port
+    async fn select_port(&self) -> Result<SerialPort, SerialError> {
+        let window = web_sys::window()
+            .ok_or(SerialError::Other("Unsupported platform".to_string()))?;
+        let serial = window.navigator().serial();
+
+        if self.reuse_granted_port {
+            let ports: Array = JsFuture::from(serial.get_ports())
+                .await
+                .map_err(|_| SerialError::PermissionError)?
+                .into();
+            let port = ports
+                .iter()
+                .map(SerialPort::from)
+                .find(|port| matches_filters(&self.filters, port));
+            if let Some(port) = port {
+                debug!("Reusing previously granted serial port");
+                return Ok(port);
+            }
+        }
+
+        let request_options = SerialPortRequestOptions::new();
+        if !self.filters.is_empty() {
+            let filters = Array::new();
+            for filter in &self.filters {
+                let js_filter = SerialPortFilter::new();
+                js_filter.set_usb_vendor_id(filter.usb_vendor_id);
+                if let Some(usb_product_id) = filter.usb_product_id {
+                    js_filter.set_usb_product_id(usb_product_id);
+                }
+                filters.push(&js_filter);
+            }
+            request_options.set_filters(&filters);
+        }
+        let port_js =
+            JsFuture::from(serial.request_port_with_options(&request_options))
+                .await
+                .map_err(|_| SerialError::PermissionError)?;
+        Ok(port_js.into())
+    }
+
+    /// Listens for the connect and disconnect events of the Web Serial API
+    /// to reopen the port when the device is plugged in again
+    fn add_event_listeners(
+        &mut self,
+        serial: &Serial,
+        connection: SerialConnection,
+    ) {
+        let filters = self.filters.clone();
+        let reconnecting = connection.clone();
+        let on_connect = Closure::<dyn FnMut(Event)>::new(move |e: Event| {
+            let Some(port) = event_target_port(&e) else {
+                return;
+            };
+            if reconnecting.closed.get() || reconnecting.is_connected() {
+                return;
+            }
+            // the same device is either represented by the same port object
+            // or can be identified by the configured USB filters
+            let is_same_port =
+                reconnecting.port.borrow().as_ref() == Some(&port);
+            if !is_same_port
+                && (filters.is_empty() || !matches_filters(&filters, &port))
+            {
+                return;
+            }
+            info!("Serial device reconnected, reopening port");
+            let connection = reconnecting.clone();
+            spawn_with_panic_notify_default(async move {
+                (connection.set_state)(ComInterfaceState::Connecting);
+                if let Err(e) = connection.connect(port).await {
+                    error!("Failed to reopen serial port: {e:?}");
+                    (connection.set_state)(ComInterfaceState::NotConnected);
+                }
+            });
+        });
+
+        let on_disconnect =
+            Closure::<dyn FnMut(Event)>::new(move |e: Event| {
+                let Some(port) = event_target_port(&e) else {
+                    return;
+                };
+                if connection.port.borrow().as_ref() != Some(&port) {
+                    return;
+                }
+                info!("Serial device disconnected");
+                let connection = connection.clone();
+                spawn_with_panic_notify_default(async move {
+                    connection.cancel_read().await;
+                });
+            });
+
+        for (event, listener) in
+            [("connect", on_connect), ("disconnect", on_disconnect)]
+        {
+            if let Err(e) = serial.add_event_listener_with_callback(
+                event,
+                listener.as_ref().unchecked_ref(),
+            ) {
+                error!("Failed to add serial {event} listener: {e:?}");
+            }
+            self.listeners.push((event, listener));
+        }
+    }
+
+    fn remove_event_listeners(&mut self) {
+        let Some(serial) = web_sys::window().map(|w| w.navigator().serial())
+        else {
+            return;
+        };
+        for (event, listener) in self.listeners.drain(..) {
+            let _ = serial.remove_event_listener_with_callback(
+                event,
+                listener.as_ref().unchecked_ref(),
+            );
+        }
+    }
+
+    #[create_opener]
+    async fn open(&mut self) -> Result<(), SerialError> {
+        let window = web_sys::window()
+            .ok_or(SerialError::Other("Unsupported platform".to_string()))?;
+        let serial = window.navigator().serial();
+        let port = self.select_port().await?;
+        let connection = SerialConnection {
+            interface_uuid: self.get_uuid().clone(),
+            sockets: self.get_sockets().clone(),
+            set_state: state_setter(self),
+            options: self.options.clone(),
+            framing: self.framing,
+            port: Rc::new(RefCell::new(None)),
+            reader: Rc::new(RefCell::new(None)),
+            writer: Rc::new(RefCell::new(None)),
+            closed: Rc::new(Cell::new(false)),
+        };
+        connection.connect(port).await?;
+        self.add_event_listeners(&serial, connection.clone());
+        self.connection = Some(connection);
+        Ok(())
+    }
+}
+
+impl ComInterfaceFactory<SerialJSInterfaceSetupData> for SerialJSInterface {
     fn create(
-        setup_data: SerialInterfaceSetupData,
+        setup_data: SerialJSInterfaceSetupData,
     ) -> Result<SerialJSInterface, ComInterfaceError> {
-        SerialJSInterface::new(setup_data.baud_rate)
+        SerialJSInterface::new(setup_data)
             .map_err(|_| ComInterfaceError::InvalidSetupData)
     }
 
@@ -130,21 +481,25 @@ impl ComInterfaceFactory<SerialInterfaceSetupData> for SerialJSInterface {
     }
 }
 
-impl ComInterface for SerialJSInterface {
-    fn send_block<'a>(
+impl SerialJSInterface {
+    fn send_to_socket<'a>(
         &'a mut self,
         block: &'a [u8],
         _: ComInterfaceSocketUUID,
     ) -> Pin<Box<dyn Future<Output = bool> + 'a>> {
-        let tx = self.tx.clone();
+        let tx = self
+            .connection
+            .as_ref()
+            .and_then(|connection| connection.writer.borrow().clone());
         if tx.is_none() {
             error!("Client is not connected");
             return Box::pin(async { false });
         }
         let tx = tx.unwrap();
+        let frame = FrameCodec::new(self.framing).encode(block);
         Box::pin(async move {
-            let js_array = Uint8Array::from(block);
-            let promise = tx.borrow().write_with_chunk(&js_array);
+            let js_array = Uint8Array::from(&frame[..]);
+            let promise = tx.write_with_chunk(&js_array);
             debug!("Sending block: {block:?}");
             match JsFuture::from(promise).await {
                 Ok(_) => true,
@@ -155,6 +510,10 @@ impl ComInterface for SerialJSInterface {
             }
         })
     }
+}
+
+impl ComInterface for SerialJSInterface {
+    intercepted_send_block!(send_to_socket);
 
     fn init_properties(&self) -> InterfaceProperties {
         Self::get_default_properties()
@@ -162,11 +521,17 @@ impl ComInterface for SerialJSInterface {
     fn handle_close<'a>(
         &'a mut self,
     ) -> Pin<Box<dyn Future<Output = bool> + 'a>> {
-        // TODO add shutdown hook
-        let port = self.port.as_ref();
+        self.remove_event_listeners();
+        let connection = self.connection.take();
         Box::pin(async move {
-            let result = JsFuture::from(port.unwrap().close()).await;
-            result.is_ok()
+            if let Some(connection) = connection {
+                connection.closed.set(true);
+                // the read loop closes the port once the read is cancelled
+                connection.cancel_read().await;
+                true
+            } else {
+                false
+            }
         })
     }
     delegate_com_interface_info!();
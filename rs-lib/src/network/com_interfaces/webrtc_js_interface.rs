@@ -1,7 +1,8 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::future::Future;
 use std::pin::Pin;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
+use std::str::FromStr;
 use std::sync::Mutex;
 use std::time::Duration; // FIXME no-std
 
@@ -12,11 +13,11 @@ use datex_core::network::com_interfaces::com_interface::{
     ComInterface, ComInterfaceError, ComInterfaceFactory, ComInterfaceInfo, ComInterfaceSockets
 };
 use datex_core::network::com_interfaces::com_interface_properties::InterfaceProperties;
-use datex_core::network::com_interfaces::com_interface_socket::ComInterfaceSocketUUID;
+use datex_core::network::com_interfaces::com_interface_socket::{ComInterfaceSocket, ComInterfaceSocketUUID};
 use datex_core::network::com_interfaces::default_com_interfaces::webrtc::webrtc_common::data_channels::{DataChannel, DataChannels};
 use datex_core::network::com_interfaces::default_com_interfaces::webrtc::webrtc_common::structures::{RTCIceCandidateInitDX, RTCIceServer, RTCSdpTypeDX, RTCSessionDescriptionDX};
 use datex_core::network::com_interfaces::default_com_interfaces::webrtc::webrtc_common::utils::WebRTCError;
-use datex_core::network::com_interfaces::default_com_interfaces::webrtc::webrtc_common::webrtc_commons::{WebRTCCommon, WebRTCInterfaceSetupData};
+use datex_core::network::com_interfaces::default_com_interfaces::webrtc::webrtc_common::webrtc_commons::WebRTCCommon;
 use datex_core::network::com_interfaces::default_com_interfaces::webrtc::webrtc_common::webrtc_trait::{WebRTCTrait, WebRTCTraitInternal};
 use datex_core::network::com_interfaces::socket_provider::SingleSocketProvider;
 use datex_core::stdlib::sync::Arc;
@@ -27,19 +28,27 @@ use datex_core::network::com_interfaces::com_interface::ComInterfaceState;
 use js_sys::{Array, Function, Reflect};
 use wasm_bindgen_futures::JsFuture;
 
-use crate::js_utils::TryAsByteSlice;
+use crate::intercepted_send_block;
+use crate::js_utils::{TryAsByteSlice, js_error};
+use crate::network::block_interceptors::receive_block;
 use crate::network::com_hub::JSComHub;
 use crate::wrap_error_for_js;
 use datex_core::network::com_hub::ComHubError;
 use datex_macros::{com_interface, create_opener};
-use log::{error, info};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::{from_value, to_value};
+use tsify::Tsify;
 use wasm_bindgen::prelude::{Closure, wasm_bindgen};
-use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen::{JsCast, JsError, JsValue};
 use web_sys::{
-    MediaStream, MessageEvent, RtcConfiguration, RtcDataChannel,
-    RtcDataChannelEvent, RtcIceCandidateInit, RtcIceServer, RtcPeerConnection,
-    RtcPeerConnectionIceEvent, RtcSdpType, RtcSessionDescriptionInit,
-    RtcSignalingState,
+    MediaStream, MediaStreamTrack, MessageEvent, RtcConfiguration,
+    RtcDataChannel, RtcDataChannelEvent, RtcDataChannelInit,
+    RtcIceCandidateInit, RtcIceConnectionState, RtcIceServer,
+    RtcPeerConnection, RtcPeerConnectionIceEvent, RtcPeerConnectionState,
+    RtcRtpTransceiverDirection, RtcRtpTransceiverInit, RtcSdpType,
+    RtcSessionDescriptionInit, RtcSignalingState, RtcStatsReport,
+    RtcTrackEvent,
 };
 
 wrap_error_for_js!(JSWebRTCError, datex_core::network::com_interfaces::default_com_interfaces::webrtc::webrtc_common::utils::WebRTCError);
@@ -50,13 +59,126 @@ impl From<ComHubError> for JSWebRTCError {
     }
 }
 
+/// Label of the data channel that is negotiated with the initial offer
+const DATEX_CHANNEL: &str = "DATEX";
+
+const DEFAULT_STATS_INTERVAL: u32 = 5000;
+
+type InterfaceDataChannels = (
+    Weak<RefCell<DataChannels<RtcDataChannel>>>,
+    Arc<Mutex<ComInterfaceSockets>>,
+);
+
+thread_local! {
+    /// Data channels and sockets of the opened WebRTC interfaces, so that
+    /// the data channels can pass received blocks to the socket of their
+    /// interface
+    static INTERFACE_DATA_CHANNELS: RefCell<Vec<InterfaceDataChannels>> =
+        RefCell::new(Vec::new());
+}
+
+/// Returns the socket of the interface that the data channel belongs to
+fn get_data_channel_socket(
+    channel: &Rc<RefCell<DataChannel<RtcDataChannel>>>,
+) -> Option<Arc<Mutex<ComInterfaceSocket>>> {
+    let label = channel.borrow().data_channel.label();
+    INTERFACE_DATA_CHANNELS.with(|interfaces| {
+        interfaces
+            .borrow()
+            .iter()
+            .find_map(|(data_channels, sockets)| {
+                let data_channels = data_channels.upgrade()?;
+                let data_channel =
+                    data_channels.borrow().get_data_channel(&label)?;
+                if !Rc::ptr_eq(&data_channel, channel) {
+                    return None;
+                }
+                sockets.lock().unwrap().sockets.values().next().cloned()
+            })
+    })
+}
+
+/// Reliability settings of a WebRTC data channel.
+/// If neither `max_retransmits` nor `max_packet_life_time` is set,
+/// the channel is reliable. Both options are mutually exclusive.
+#[derive(Serialize, Deserialize, Tsify, Debug, Clone, Default)]
+pub struct DataChannelConfig {
+    /// If false, messages may arrive out of order (default: true)
+    pub ordered: Option<bool>,
+    /// Maximum number of retransmissions of a message
+    pub max_retransmits: Option<u16>,
+    /// Maximum time in milliseconds a message is retransmitted
+    pub max_packet_life_time: Option<u16>,
+}
+
+impl DataChannelConfig {
+    fn is_valid(&self) -> bool {
+        self.max_retransmits.is_none() || self.max_packet_life_time.is_none()
+    }
+
+    fn to_init(&self) -> RtcDataChannelInit {
+        let init = RtcDataChannelInit::new();
+        if let Some(ordered) = self.ordered {
+            init.set_ordered(ordered);
+        }
+        if let Some(max_retransmits) = self.max_retransmits {
+            init.set_max_retransmits(max_retransmits);
+        }
+        if let Some(max_packet_life_time) = self.max_packet_life_time {
+            init.set_max_packet_life_time(max_packet_life_time);
+        }
+        init
+    }
+}
+
+/// Setup data for the WebRTC interface in the browser.
+/// Superset of the common WebRTC setup data with options that are
+/// only available for the JS implementation.
+#[derive(Serialize, Deserialize, Tsify, Debug, Clone)]
+pub struct WebRTCJSInterfaceSetupData {
+    pub peer_endpoint: String,
+    pub ice_servers: Option<Vec<RTCIceServer>>,
+    /// Reliability settings of the DATEX data channel
+    /// (default: ordered and reliable)
+    #[serde(default)]
+    pub data_channel: DataChannelConfig,
+    /// If true, an ICE restart is triggered when the ICE connection
+    /// fails, e.g. after a network change (default: true)
+    pub ice_restart: Option<bool>,
+    /// Interval in milliseconds in which connection statistics are
+    /// collected, 0 disables statistics (default: 5000)
+    pub stats_interval: Option<u32>,
+}
+
+/// Statistics of the candidate pair that is currently used
+/// for the peer connection
+#[derive(Serialize, Deserialize, Tsify, Debug, Clone, Default)]
+pub struct WebRTCStats {
+    /// Round trip time in milliseconds
+    pub round_trip_time: Option<f64>,
+    /// Estimated available outgoing bandwidth in bits per second
+    pub available_outgoing_bitrate: Option<f64>,
+    pub bytes_sent: f64,
+    pub bytes_received: f64,
+}
+
 pub struct WebRTCJSInterface {
     info: ComInterfaceInfo,
+    data_channel_config: DataChannelConfig,
+    send_channel: String,
     commons: Arc<Mutex<WebRTCCommon>>,
     peer_connection: Rc<Option<RtcPeerConnection>>,
     data_channels: Rc<RefCell<DataChannels<RtcDataChannel>>>,
     local_media_tracks: Rc<RefCell<MediaTracks<MediaStream>>>,
     remote_media_tracks: Rc<RefCell<MediaTracks<MediaStream>>>,
+    on_remote_stream: Rc<RefCell<Option<Function>>>,
+    /// Selects the data channel for each block, see set_channel_selector
+    channel_selector: RefCell<Option<Function>>,
+    on_connection_state_change: Rc<RefCell<Option<Function>>>,
+    on_negotiation_needed: Rc<RefCell<Option<Box<dyn Fn()>>>>,
+    ice_restart: bool,
+    stats_interval: u32,
+    stats: Rc<RefCell<Option<WebRTCStats>>>,
 }
 impl SingleSocketProvider for WebRTCJSInterface {
     fn provide_sockets(&self) -> Arc<Mutex<ComInterfaceSockets>> {
@@ -69,11 +191,20 @@ impl WebRTCTrait<RtcDataChannel, MediaStream, MediaStream>
     fn new(peer_endpoint: impl Into<Endpoint>) -> Self {
         WebRTCJSInterface {
             info: ComInterfaceInfo::default(),
+            data_channel_config: DataChannelConfig::default(),
+            send_channel: DATEX_CHANNEL.to_string(),
             commons: Arc::new(Mutex::new(WebRTCCommon::new(peer_endpoint))),
             peer_connection: Rc::new(None),
             data_channels: Rc::new(RefCell::new(DataChannels::default())),
             local_media_tracks: Rc::new(RefCell::new(MediaTracks::default())),
             remote_media_tracks: Rc::new(RefCell::new(MediaTracks::default())),
+            on_remote_stream: Rc::new(RefCell::new(None)),
+            channel_selector: RefCell::new(None),
+            on_connection_state_change: Rc::new(RefCell::new(None)),
+            on_negotiation_needed: Rc::new(RefCell::new(None)),
+            ice_restart: true,
+            stats_interval: DEFAULT_STATS_INTERVAL,
+            stats: Rc::new(RefCell::new(None)),
         }
     }
     fn new_with_ice_servers(
@@ -106,13 +237,39 @@ impl WebRTCTraitInternal<RtcDataChannel, MediaStream, MediaStream>
         id: String,
         kind: MediaKind,
     ) -> Result<MediaTrack<MediaStream>, WebRTCError> {
-        todo!("Implement media channel creation")
+        if let Some(peer_connection) = self.peer_connection.as_ref() {
+            // The transceiver is created without a track, a local track
+            // can later be attached to the stream via add_local_stream
+            let stream =
+                MediaStream::new().map_err(|_| WebRTCError::Unsupported)?;
+            let init = RtcRtpTransceiverInit::new();
+            init.set_direction(RtcRtpTransceiverDirection::Sendrecv);
+            init.set_streams(&Array::of1(&stream));
+            peer_connection.add_transceiver_with_str_and_init(
+                media_kind_str(&kind),
+                &init,
+            );
+            Ok(MediaTrack::new(id, kind, stream))
+        } else {
+            error!("Peer connection is not initialized");
+            Err(WebRTCError::ConnectionError)
+        }
     }
 
     async fn handle_setup_media_channel(
         channel: Rc<RefCell<MediaTrack<MediaStream>>>,
     ) -> Result<(), WebRTCError> {
-        todo!("Implement media channel setup")
+        // Tracks are sent and received by the peer connection, the stream
+        // only needs to be observed for removed tracks
+        let stream_id = channel.borrow().track.id();
+        let onremovetrack_callback = Closure::<dyn FnMut()>::new(move || {
+            info!("Track removed from media stream {stream_id}");
+        });
+        channel.borrow().track.set_onremovetrack(Some(
+            onremovetrack_callback.as_ref().unchecked_ref(),
+        ));
+        onremovetrack_callback.forget();
+        Ok(())
     }
 
     fn provide_data_channels(
@@ -127,7 +284,11 @@ impl WebRTCTraitInternal<RtcDataChannel, MediaStream, MediaStream>
         &self,
     ) -> Result<DataChannel<RtcDataChannel>, WebRTCError> {
         if let Some(peer_connection) = self.peer_connection.as_ref() {
-            let data_channel = peer_connection.create_data_channel("DATEX");
+            let data_channel = peer_connection
+                .create_data_channel_with_data_channel_dict(
+                    DATEX_CHANNEL,
+                    &self.data_channel_config.to_init(),
+                );
             Ok(DataChannel::new(data_channel.label(), data_channel))
         } else {
             error!("Peer connection is not initialized");
@@ -168,7 +329,14 @@ impl WebRTCTraitInternal<RtcDataChannel, MediaStream, MediaStream>
                 move |message_event: MessageEvent| {
                     let channel_clone = channel_clone.clone();
                     let data = message_event.data().try_as_u8_slice();
-                    if let Ok(data) = data
+                    // blocks are received via the socket, so that they pass
+                    // the block interceptors of the ComHub
+                    if let Ok(data) = &data
+                        && let Some(socket) =
+                            get_data_channel_socket(&channel_clone)
+                    {
+                        receive_block(&socket, data);
+                    } else if let Ok(data) = data
                         && let Some(on_message) = channel_clone
                             .clone()
                             .borrow()
@@ -386,6 +554,14 @@ impl WebRTCJSInterface {
         let connection = Rc::new(Some(connection));
         self.peer_connection = connection.clone();
 
+        INTERFACE_DATA_CHANNELS.with(|interfaces| {
+            let mut interfaces = interfaces.borrow_mut();
+            interfaces
+                .retain(|(data_channels, _)| data_channels.strong_count() > 0);
+            interfaces
+                .push((Rc::downgrade(&self.data_channels), self.get_sockets()));
+        });
+
         let data_channels = self.data_channels.clone();
         let ondatachannel_callback =
             Closure::<dyn FnMut(_)>::new(move |ev: RtcDataChannelEvent| {
@@ -402,7 +578,44 @@ impl WebRTCJSInterface {
                 });
             });
 
+        let remote_media_tracks = self.remote_media_tracks.clone();
+        let on_remote_stream = self.on_remote_stream.clone();
+        let ontrack_callback =
+            Closure::<dyn FnMut(_)>::new(move |ev: RtcTrackEvent| {
+                let kind = media_kind_from_track(&ev.track());
+                for stream in ev.streams().iter() {
+                    let stream = MediaStream::from(stream);
+                    // streams with multiple tracks trigger one event per track
+                    if remote_media_tracks
+                        .borrow()
+                        .tracks
+                        .contains_key(&stream.id())
+                    {
+                        continue;
+                    }
+                    info!("Received remote media stream {}", stream.id());
+                    insert_media_track(
+                        &remote_media_tracks,
+                        kind.clone(),
+                        &stream,
+                    );
+                    if let Some(callback) = on_remote_stream.borrow().as_ref()
+                        && let Err(e) = callback.call1(&JsValue::NULL, &stream)
+                    {
+                        error!("Error in remote stream callback: {e:?}");
+                    }
+                }
+            });
+
+        // set when an ICE restart was triggered, the following
+        // negotiationneeded event requires a new offer to be sent
+        let ice_restart_pending = Rc::new(Cell::new(false));
+
         let connection_clone = connection.clone();
+        let on_connection_state_change =
+            self.on_connection_state_change.clone();
+        let ice_restart = self.ice_restart;
+        let ice_restart_pending_clone = ice_restart_pending.clone();
         let oniceconnectionstatechange_callback = Closure::<dyn FnMut()>::new(
             move || {
                 if let Some(connection) = connection_clone.as_ref() {
@@ -410,37 +623,362 @@ impl WebRTCJSInterface {
                     info!(
                         "ICE connection state of remote {remote_endpoint}: {state:?}"
                     );
+                    if state == RtcIceConnectionState::Failed && ice_restart {
+                        info!("Restarting ICE for remote {remote_endpoint}");
+                        ice_restart_pending_clone.set(true);
+                        restart_ice(connection);
+                    }
+                    notify_connection_state(
+                        connection,
+                        &on_connection_state_change,
+                    );
                 }
             },
         );
+
+        let connection_clone = connection.clone();
+        let on_connection_state_change =
+            self.on_connection_state_change.clone();
+        let onconnectionstatechange_callback =
+            Closure::<dyn FnMut()>::new(move || {
+                if let Some(connection) = connection_clone.as_ref() {
+                    notify_connection_state(
+                        connection,
+                        &on_connection_state_change,
+                    );
+                }
+            });
+
+        let on_negotiation_needed = self.on_negotiation_needed.clone();
+        let onnegotiationneeded_callback =
+            Closure::<dyn FnMut()>::new(move || {
+                if !ice_restart_pending.replace(false) {
+                    return;
+                }
+                if let Some(on_negotiation_needed) =
+                    on_negotiation_needed.borrow().as_ref()
+                {
+                    on_negotiation_needed();
+                } else {
+                    warn!("ICE restart requires a new offer to be negotiated");
+                }
+            });
+
         if let Some(connection) = connection.as_ref() {
             connection.set_oniceconnectionstatechange(Some(
                 oniceconnectionstatechange_callback.as_ref().unchecked_ref(),
             ));
             oniceconnectionstatechange_callback.forget();
 
+            connection.set_onconnectionstatechange(Some(
+                onconnectionstatechange_callback.as_ref().unchecked_ref(),
+            ));
+            onconnectionstatechange_callback.forget();
+
+            connection.set_onnegotiationneeded(Some(
+                onnegotiationneeded_callback.as_ref().unchecked_ref(),
+            ));
+            onnegotiationneeded_callback.forget();
+
             connection.set_ondatachannel(Some(
                 ondatachannel_callback.as_ref().unchecked_ref(),
             ));
             ondatachannel_callback.forget();
+
+            connection
+                .set_ontrack(Some(ontrack_callback.as_ref().unchecked_ref()));
+            ontrack_callback.forget();
         }
         self.setup_listeners();
         Ok(())
     }
+
+    /// Collects the connection statistics in the configured interval and
+    /// applies them to the interface properties, until the interface is
+    /// removed or the peer connection is closed. Called by the ComHub
+    /// once the interface was opened
+    pub(crate) fn collect_stats_periodically(
+        interface: &Rc<RefCell<WebRTCJSInterface>>,
+    ) {
+        let (connection, stats, interval) = {
+            let interface = interface.borrow();
+            if interface.stats_interval == 0 {
+                return;
+            }
+            (
+                interface.peer_connection.clone(),
+                interface.stats.clone(),
+                Duration::from_millis(interface.stats_interval as u64),
+            )
+        };
+        let interface = Rc::downgrade(interface);
+        spawn_local(async move {
+            while let Some(connection) = connection.as_ref()
+                && connection.connection_state()
+                    != RtcPeerConnectionState::Closed
+            {
+                futures_timer::Delay::new(interval).await;
+                let Some(interface) = interface.upgrade() else {
+                    break;
+                };
+                if connection.connection_state()
+                    == RtcPeerConnectionState::Connected
+                    && let Some(new_stats) = collect_stats(connection).await
+                {
+                    stats.replace(Some(new_stats));
+                    // the interface is borrowed while a block is sent, the
+                    // properties are then updated with the next statistics
+                    if let Ok(mut interface) = interface.try_borrow_mut() {
+                        interface.update_properties();
+                    }
+                }
+            }
+        });
+    }
+
+    /// Returns the latest collected connection statistics
+    pub fn get_stats(&self) -> Option<WebRTCStats> {
+        self.stats.borrow().clone()
+    }
+
+    /// Applies the latest connection statistics to the interface properties,
+    /// so that routing prefers peers with a low latency and high bandwidth
+    fn update_properties(&mut self) {
+        let Some(stats) = self.stats.borrow().clone() else {
+            return;
+        };
+        let Some(properties) = self.info.interface_properties.as_mut() else {
+            return;
+        };
+        if let Some(round_trip_time) = stats.round_trip_time {
+            properties.round_trip_time =
+                Duration::from_secs_f64(round_trip_time / 1000.0);
+        }
+        if let Some(bitrate) = stats.available_outgoing_bitrate {
+            // max_bandwidth is given in kbit/s
+            properties.max_bandwidth = (bitrate / 1000.0) as u32;
+        }
+    }
+
+    /// Sets the callback that is invoked with the connection state and
+    /// the ICE connection state whenever one of them changes
+    pub fn set_on_connection_state_change(&self, callback: Function) {
+        self.on_connection_state_change.replace(Some(callback));
+    }
+
+    /// Sets the handler that is invoked after an ICE restart was triggered.
+    /// The handler must create a new offer and send it to the remote peer.
+    pub fn set_on_negotiation_needed(&self, handler: Box<dyn Fn()>) {
+        self.on_negotiation_needed.replace(Some(handler));
+    }
+
+    /// Adds all tracks of a local media stream (e.g. from getUserMedia)
+    /// to the peer connection. If the connection is already established,
+    /// a new offer has to be negotiated for the tracks to be sent.
+    pub fn add_local_stream(
+        &self,
+        stream: &MediaStream,
+    ) -> Result<(), WebRTCError> {
+        if let Some(peer_connection) = self.peer_connection.as_ref() {
+            for track in stream.get_tracks().iter() {
+                let track = MediaStreamTrack::from(track);
+                peer_connection.add_track_0(&track, stream);
+            }
+            let kind = if stream.get_video_tracks().length() > 0 {
+                MediaKind::Video
+            } else {
+                MediaKind::Audio
+            };
+            insert_media_track(&self.local_media_tracks, kind, stream);
+            Ok(())
+        } else {
+            error!("Peer connection is not initialized");
+            Err(WebRTCError::ConnectionError)
+        }
+    }
+
+    /// Creates an additional data channel with its own reliability settings.
+    /// Blocks received on the channel are handled like blocks received on
+    /// the DATEX channel, blocks are only sent over the channel if it is
+    /// selected with set_send_channel or by the channel selector.
+    pub async fn add_data_channel(
+        &self,
+        label: String,
+        config: DataChannelConfig,
+    ) -> Result<(), WebRTCError> {
+        if let Some(peer_connection) = self.peer_connection.as_ref() {
+            let data_channel = peer_connection
+                .create_data_channel_with_data_channel_dict(
+                    &label,
+                    &config.to_init(),
+                );
+            self.data_channels
+                .borrow_mut()
+                .create_data_channel(label, data_channel)
+                .await;
+            Ok(())
+        } else {
+            error!("Peer connection is not initialized");
+            Err(WebRTCError::ConnectionError)
+        }
+    }
+
+    /// Selects the data channel that is used to send blocks for which
+    /// the channel selector does not select a channel
+    pub fn set_send_channel(&mut self, label: String) -> bool {
+        if self
+            .data_channels
+            .borrow()
+            .get_data_channel(&label)
+            .is_some()
+        {
+            self.send_channel = label;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns all media streams received from the remote peer
+    pub fn get_remote_streams(&self) -> Vec<MediaStream> {
+        self.remote_media_tracks
+            .borrow()
+            .tracks
+            .values()
+            .map(|track| track.borrow().track.clone())
+            .collect()
+    }
+
+    pub fn set_on_remote_stream(&self, callback: Function) {
+        self.on_remote_stream.replace(Some(callback));
+    }
+
+    /// Sets the function that selects the data channel for each block, so
+    /// that e.g. latency-sensitive blocks use an unreliable channel.
+    /// The function is called with the block and returns the label of the
+    /// channel, or undefined to send the block over the send channel.
+    pub fn set_channel_selector(&self, selector: Function) {
+        self.channel_selector.replace(Some(selector));
+    }
+
+    /// Returns the label of the data channel that the block is sent over
+    fn select_channel(&self, block: &[u8]) -> String {
+        let Some(selector) = self.channel_selector.borrow().clone() else {
+            return self.send_channel.clone();
+        };
+        match selector.call1(&JsValue::NULL, &js_sys::Uint8Array::from(block)) {
+            Ok(label) => label
+                .as_string()
+                .unwrap_or_else(|| self.send_channel.clone()),
+            Err(e) => {
+                error!("Error in channel selector: {e:?}");
+                self.send_channel.clone()
+            }
+        }
+    }
 }
 
-impl ComInterface for WebRTCJSInterface {
-    fn send_block<'a>(
+fn media_kind_str(kind: &MediaKind) -> &'static str {
+    match kind {
+        MediaKind::Audio => "audio",
+        MediaKind::Video => "video",
+    }
+}
+
+fn media_kind_from_track(track: &MediaStreamTrack) -> MediaKind {
+    if track.kind() == "audio" {
+        MediaKind::Audio
+    } else {
+        MediaKind::Video
+    }
+}
+
+/// Triggers an ICE restart, RTCPeerConnection.restartIce is not
+/// exposed by web-sys yet
+fn restart_ice(connection: &RtcPeerConnection) {
+    let restart_ice =
+        Reflect::get(connection, &JsValue::from_str("restartIce"))
+            .ok()
+            .and_then(|restart_ice| restart_ice.dyn_into::<Function>().ok());
+    if let Some(restart_ice) = restart_ice {
+        if let Err(e) = restart_ice.call0(connection) {
+            error!("Failed to restart ICE: {e:?}");
+        }
+    } else {
+        error!("ICE restart is not supported");
+    }
+}
+
+fn notify_connection_state(
+    connection: &RtcPeerConnection,
+    callback: &Rc<RefCell<Option<Function>>>,
+) {
+    if let Some(callback) = callback.borrow().as_ref()
+        && let Err(e) = callback.call2(
+            &JsValue::NULL,
+            &JsValue::from(connection.connection_state()),
+            &JsValue::from(connection.ice_connection_state()),
+        )
+    {
+        error!("Error in connection state callback: {e:?}");
+    }
+}
+
+/// Reads the statistics of the nominated candidate pair
+async fn collect_stats(connection: &RtcPeerConnection) -> Option<WebRTCStats> {
+    let report: RtcStatsReport = JsFuture::from(connection.get_stats())
+        .await
+        .ok()?
+        .unchecked_into();
+    let get = |entry: &JsValue, key: &str| {
+        Reflect::get(entry, &JsValue::from_str(key)).unwrap_or_default()
+    };
+    for entry in report.values().into_iter().flatten() {
+        if get(&entry, "type").as_string().as_deref() != Some("candidate-pair")
+            || get(&entry, "state").as_string().as_deref() != Some("succeeded")
+            || get(&entry, "nominated").as_bool() != Some(true)
+        {
+            continue;
+        }
+        return Some(WebRTCStats {
+            // currentRoundTripTime is given in seconds
+            round_trip_time: get(&entry, "currentRoundTripTime")
+                .as_f64()
+                .map(|rtt| rtt * 1000.0),
+            available_outgoing_bitrate: get(&entry, "availableOutgoingBitrate")
+                .as_f64(),
+            bytes_sent: get(&entry, "bytesSent").as_f64().unwrap_or_default(),
+            bytes_received: get(&entry, "bytesReceived")
+                .as_f64()
+                .unwrap_or_default(),
+        });
+    }
+    None
+}
+
+fn insert_media_track(
+    tracks: &Rc<RefCell<MediaTracks<MediaStream>>>,
+    kind: MediaKind,
+    stream: &MediaStream,
+) {
+    let id = stream.id();
+    let track = MediaTrack::new(id.clone(), kind, stream.clone());
+    tracks
+        .borrow_mut()
+        .tracks
+        .insert(id, Rc::new(RefCell::new(track)));
+}
+
+impl WebRTCJSInterface {
+    fn send_to_socket<'a>(
         &'a mut self,
         block: &'a [u8],
         _: ComInterfaceSocketUUID,
     ) -> Pin<Box<dyn Future<Output = bool> + 'a>> {
+        let label = self.select_channel(block);
         let success = {
-            if let Some(channel) = self
-                .data_channels
-                .clone()
-                .borrow()
-                .get_data_channel("DATEX")
+            if let Some(channel) =
+                self.data_channels.clone().borrow().get_data_channel(&label)
             {
                 channel
                     .clone()
@@ -449,12 +987,18 @@ impl ComInterface for WebRTCJSInterface {
                     .send_with_u8_array(block)
                     .is_ok()
             } else {
-                error!("Failed to send message, data channel not found");
+                error!(
+                    "Failed to send message, data channel {label} not found"
+                );
                 false
             }
         };
         Box::pin(async move { success })
     }
+}
+
+impl ComInterface for WebRTCJSInterface {
+    intercepted_send_block!(send_to_socket);
 
     fn init_properties(&self) -> InterfaceProperties {
         InterfaceProperties {
@@ -489,25 +1033,39 @@ impl ComInterface for WebRTCJSInterface {
     set_opener!(open);
 }
 
-impl ComInterfaceFactory<WebRTCInterfaceSetupData> for WebRTCJSInterface {
+impl ComInterfaceFactory<WebRTCJSInterfaceSetupData> for WebRTCJSInterface {
     fn create(
-        setup_data: WebRTCInterfaceSetupData,
+        setup_data: WebRTCJSInterfaceSetupData,
     ) -> Result<WebRTCJSInterface, ComInterfaceError> {
-        if let Some(ice_servers) = setup_data.ice_servers.as_ref() {
+        let peer_endpoint = Endpoint::from_str(&setup_data.peer_endpoint)
+            .map_err(|_| ComInterfaceError::InvalidSetupData)?;
+        if !setup_data.data_channel.is_valid() {
+            error!(
+                "max_retransmits and max_packet_life_time can not be used together"
+            );
+            return Err(ComInterfaceError::InvalidSetupData);
+        }
+        let mut interface = if let Some(ice_servers) =
+            setup_data.ice_servers.as_ref()
+        {
             if ice_servers.is_empty() {
                 error!(
                     "Ice servers list is empty, at least one ice server is required"
                 );
-                Err(ComInterfaceError::InvalidSetupData)
-            } else {
-                Ok(WebRTCJSInterface::new_with_ice_servers(
-                    setup_data.peer_endpoint,
-                    ice_servers.to_owned(),
-                ))
+                return Err(ComInterfaceError::InvalidSetupData);
             }
+            WebRTCJSInterface::new_with_ice_servers(
+                peer_endpoint,
+                ice_servers.to_owned(),
+            )
         } else {
-            Ok(WebRTCJSInterface::new(setup_data.peer_endpoint))
-        }
+            WebRTCJSInterface::new(peer_endpoint)
+        };
+        interface.data_channel_config = setup_data.data_channel;
+        interface.ice_restart = setup_data.ice_restart.unwrap_or(true);
+        interface.stats_interval =
+            setup_data.stats_interval.unwrap_or(DEFAULT_STATS_INTERVAL);
+        Ok(interface)
     }
 
     fn get_default_properties() -> InterfaceProperties {
@@ -579,6 +1137,95 @@ impl JSComHub {
         Ok(())
     }
 
+    pub fn webrtc_interface_add_local_stream(
+        &self,
+        interface_uuid: String,
+        stream: MediaStream,
+    ) -> Result<(), JSWebRTCError> {
+        let interface =
+            self.get_interface_for_uuid::<WebRTCJSInterface>(interface_uuid)?;
+        interface.borrow().add_local_stream(&stream)?;
+        Ok(())
+    }
+
+    pub fn webrtc_interface_get_remote_streams(
+        &self,
+        interface_uuid: String,
+    ) -> Result<Array, JSWebRTCError> {
+        let interface =
+            self.get_interface_for_uuid::<WebRTCJSInterface>(interface_uuid)?;
+        let streams = interface.borrow().get_remote_streams();
+        Ok(streams.iter().collect())
+    }
+
+    pub fn webrtc_interface_set_on_remote_stream(
+        &self,
+        interface_uuid: String,
+        on_remote_stream: Function,
+    ) -> Result<(), JSWebRTCError> {
+        let interface =
+            self.get_interface_for_uuid::<WebRTCJSInterface>(interface_uuid)?;
+        interface.borrow().set_on_remote_stream(on_remote_stream);
+        Ok(())
+    }
+
+    pub async fn webrtc_interface_create_data_channel(
+        &self,
+        interface_uuid: String,
+        label: String,
+        config: JsValue,
+    ) -> Result<(), JsError> {
+        let config: DataChannelConfig = if config.is_undefined() {
+            DataChannelConfig::default()
+        } else {
+            from_value(config).map_err(js_error)?
+        };
+        if !config.is_valid() {
+            return Err(JsError::new(
+                "max_retransmits and max_packet_life_time can not be used together",
+            ));
+        }
+        let interface = self
+            .get_interface_for_uuid::<WebRTCJSInterface>(interface_uuid)
+            .map_err(JSWebRTCError::from)?;
+        let webrtc_interface = interface.borrow();
+        webrtc_interface
+            .add_data_channel(label, config)
+            .await
+            .map_err(JSWebRTCError::from)?;
+        Ok(())
+    }
+
+    pub fn webrtc_interface_set_send_channel(
+        &self,
+        interface_uuid: String,
+        label: String,
+    ) -> Result<(), JsError> {
+        let interface = self
+            .get_interface_for_uuid::<WebRTCJSInterface>(interface_uuid)
+            .map_err(JSWebRTCError::from)?;
+        if interface.borrow_mut().set_send_channel(label.clone()) {
+            Ok(())
+        } else {
+            Err(JsError::new(&format!("Data channel {label} not found")))
+        }
+    }
+
+    /// Sets the function that is called with each block before it is sent
+    /// and returns the label of the data channel for the block, or
+    /// undefined to use the send channel
+    pub fn webrtc_interface_set_channel_selector(
+        &self,
+        interface_uuid: String,
+        selector: Function,
+    ) -> Result<(), JsError> {
+        let interface = self
+            .get_interface_for_uuid::<WebRTCJSInterface>(interface_uuid)
+            .map_err(JSWebRTCError::from)?;
+        interface.borrow().set_channel_selector(selector);
+        Ok(())
+    }
+
     pub async fn webrtc_interface_wait_for_connection(
         &self,
         interface_uuid: String,
@@ -589,4 +1236,48 @@ impl JSComHub {
         webrtc_interface.wait_for_connection().await?;
         Ok(())
     }
+
+    pub fn webrtc_interface_set_on_connection_state_change(
+        &self,
+        interface_uuid: String,
+        on_connection_state_change: Function,
+    ) -> Result<(), JSWebRTCError> {
+        let interface =
+            self.get_interface_for_uuid::<WebRTCJSInterface>(interface_uuid)?;
+        interface
+            .borrow()
+            .set_on_connection_state_change(on_connection_state_change);
+        Ok(())
+    }
+
+    /// Sets the callback that is invoked after an ICE restart was
+    /// triggered. The callback must send a new offer to the remote peer.
+    pub fn webrtc_interface_set_on_negotiation_needed(
+        &self,
+        interface_uuid: String,
+        on_negotiation_needed: Function,
+    ) -> Result<(), JSWebRTCError> {
+        let interface =
+            self.get_interface_for_uuid::<WebRTCJSInterface>(interface_uuid)?;
+        interface
+            .borrow()
+            .set_on_negotiation_needed(Box::new(move || {
+                if let Err(e) = on_negotiation_needed.call0(&JsValue::NULL) {
+                    error!("Error in negotiation needed callback: {e:?}");
+                }
+            }));
+        Ok(())
+    }
+
+    /// Returns the latest connection statistics
+    pub fn webrtc_interface_get_stats(
+        &self,
+        interface_uuid: String,
+    ) -> Result<JsValue, JsError> {
+        let interface = self
+            .get_interface_for_uuid::<WebRTCJSInterface>(interface_uuid)
+            .map_err(JSWebRTCError::from)?;
+        let stats = interface.borrow().get_stats();
+        to_value(&stats).map_err(js_error)
+    }
 }
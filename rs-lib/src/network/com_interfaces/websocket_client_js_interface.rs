@@ -1,139 +1,480 @@
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
 use std::future::Future;
 use std::pin::Pin;
+use std::rc::Rc;
 use std::sync::Mutex;
 use std::time::Duration; // FIXME no-std
 
 use datex_core::{delegate_com_interface_info, set_opener};
-use datex_core::network::com_interfaces::com_interface::{ComInterface, ComInterfaceError, ComInterfaceFactory, ComInterfaceInfo, ComInterfaceSockets};
+use datex_core::network::com_interfaces::com_interface::{ComInterface, ComInterfaceError, ComInterfaceFactory, ComInterfaceInfo, ComInterfaceSockets, ComInterfaceUUID};
 use datex_core::network::com_interfaces::com_interface_properties::{
     InterfaceDirection, InterfaceProperties,
 };
 use datex_core::network::com_interfaces::com_interface_socket::{
     ComInterfaceSocket, ComInterfaceSocketUUID,
 };
-use datex_core::network::com_interfaces::default_com_interfaces::websocket::websocket_common::{WebSocketClientInterfaceSetupData, WebSocketError};
+use datex_core::network::com_interfaces::default_com_interfaces::websocket::websocket_common::WebSocketError;
 use datex_core::network::com_interfaces::socket_provider::SingleSocketProvider;
 use datex_core::stdlib::sync::Arc;
 
 use datex_core::network::com_interfaces::com_interface::ComInterfaceState;
 use datex_core::network::com_interfaces::default_com_interfaces::websocket::websocket_common::parse_url;
 
+use crate::crypto::crypto_js::CryptoJS;
+use crate::intercepted_send_block;
+use crate::js_utils::js_error;
+use crate::network::block_interceptors::receive_block;
+use crate::network::com_hub::JSComHub;
+use crate::network::com_hub_events::{notify_socket_registered, state_setter};
+use crate::network::com_interfaces::websocket_js_common::{
+    WebSocketCloseInfo, WebSocketHandshakeMessage,
+};
 use crate::wrap_error_for_js;
+use datex_core::crypto::crypto::CryptoTrait;
 use datex_core::task::spawn_with_panic_notify_default;
+use datex_core::values::core_values::endpoint::Endpoint;
+use futures::StreamExt;
 use futures::channel::mpsc;
-use futures::{SinkExt, StreamExt};
+use js_sys::Function;
 use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::to_value;
+use tsify::Tsify;
 use url::Url;
-use wasm_bindgen::{JsCast, prelude::Closure};
-use web_sys::{ErrorEvent, MessageEvent, js_sys};
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::{JsCast, JsError, JsValue, prelude::Closure};
+use web_sys::{CloseEvent, ErrorEvent, MessageEvent, js_sys};
 
-pub struct WebSocketClientJSInterface {
-    pub address: Url,
-    pub ws: web_sys::WebSocket,
-    info: ComInterfaceInfo,
+/// Maximum number of outgoing blocks that are held while reconnecting,
+/// the oldest blocks are dropped if the limit is exceeded
+const MAX_PENDING_BLOCKS: usize = 1024;
+
+/// Query parameter that contains the token if the auth method is `Query`
+const AUTH_QUERY_PARAMETER: &str = "access_token";
+
+/// Reconnection behaviour after an established connection was lost.
+/// The delay before each attempt grows exponentially up to `max_delay`
+/// and is randomized by `jitter`, so that clients do not all reconnect
+/// at the same time after a server restart.
+#[derive(Serialize, Deserialize, Tsify, Debug, Clone, Default)]
+#[serde(default)]
+pub struct WebSocketReconnectConfig {
+    /// If false, the connection is not reestablished (default: true)
+    pub enabled: Option<bool>,
+    /// Delay before the first attempt in milliseconds (default: 500)
+    pub initial_delay: Option<u32>,
+    /// Maximum delay between two attempts in milliseconds (default: 30000)
+    pub max_delay: Option<u32>,
+    /// Factor by which the delay grows after each attempt (default: 2)
+    pub multiplier: Option<f64>,
+    /// Maximum random deviation of the delay as a fraction of the
+    /// delay, between 0 and 1 (default: 0.2)
+    pub jitter: Option<f64>,
+    /// Maximum number of attempts, unlimited if not set
+    pub max_attempts: Option<u32>,
 }
 
-impl SingleSocketProvider for WebSocketClientJSInterface {
-    fn provide_sockets(&self) -> Arc<Mutex<ComInterfaceSockets>> {
-        self.get_sockets().clone()
+impl WebSocketReconnectConfig {
+    fn is_enabled(&self) -> bool {
+        self.enabled.unwrap_or(true)
+    }
+
+    fn is_valid(&self) -> bool {
+        self.multiplier.is_none_or(|multiplier| multiplier >= 1.0)
+            && self
+                .jitter
+                .is_none_or(|jitter| (0.0..=1.0).contains(&jitter))
+    }
+
+    /// Returns the delay before the given attempt, starting at 0
+    fn get_delay(&self, attempt: u32) -> Duration {
+        let initial_delay = self.initial_delay.unwrap_or(500) as f64;
+        let max_delay = self.max_delay.unwrap_or(30_000) as f64;
+        let multiplier = self.multiplier.unwrap_or(2.0);
+        let jitter = self.jitter.unwrap_or(0.2);
+        let delay =
+            (initial_delay * multiplier.powi(attempt as i32)).min(max_delay);
+        let deviation = jitter * (2.0 * js_sys::Math::random() - 1.0);
+        Duration::from_millis((delay * (1.0 + deviation)) as u64)
     }
 }
-wrap_error_for_js!(JSWebSocketError, datex_core::network::com_interfaces::default_com_interfaces::websocket::websocket_common::WebSocketError);
-use datex_macros::{com_interface, create_opener};
 
-#[com_interface]
-impl WebSocketClientJSInterface {
-    pub fn new(
-        address: &str,
-    ) -> Result<WebSocketClientJSInterface, WebSocketError> {
-        let address =
-            parse_url(address, true).map_err(|_| WebSocketError::InvalidURL)?;
-        let ws = web_sys::WebSocket::new(address.as_ref())
-            .map_err(|_| WebSocketError::InvalidURL)?;
-        let interface = WebSocketClientJSInterface {
-            address,
-            info: ComInterfaceInfo::new(),
-            ws,
-        };
-        Ok(interface)
+/// Browsers do not allow custom headers for WebSocket connections,
+/// so the token is sent either as query parameter or as subprotocol
+#[derive(Serialize, Deserialize, Tsify, Debug, Clone, Copy, Default)]
+pub enum WebSocketAuthMethod {
+    /// The token is appended to the address as `access_token` query parameter
+    #[default]
+    Query,
+    /// The token is offered as additional subprotocol `bearer.<token>`,
+    /// the server must not select it as protocol
+    Protocol,
+}
+
+#[derive(Serialize, Deserialize, Tsify, Debug, Clone)]
+pub struct WebSocketAuth {
+    /// Bearer token, e.g. a JWT
+    pub token: String,
+    #[serde(default)]
+    pub method: WebSocketAuthMethod,
+}
+
+/// Credentials with which the client answers the challenge of a
+/// WebSocket server with enabled handshake
+#[derive(Serialize, Deserialize, Tsify, Clone)]
+pub struct WebSocketHandshakeCredentials {
+    /// Endpoint of the client, must be the endpoint of the runtime
+    pub endpoint: String,
+    /// PKCS#8 encoded Ed25519 private key of the endpoint
+    pub private_key: Vec<u8>,
+}
+
+// the private key must not be logged
+impl std::fmt::Debug for WebSocketHandshakeCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebSocketHandshakeCredentials")
+            .field("endpoint", &self.endpoint)
+            .field("private_key", &"<redacted>")
+            .finish()
     }
+}
 
-    #[create_opener]
-    async fn open(&mut self) -> Result<(), WebSocketError> {
-        let address = self.address.clone();
-        info!("Connecting to WebSocket server at {address}");
+/// Checks that the handshake credentials in the setup data of a WebSocket
+/// client interface are for the endpoint of the runtime.
+/// Invalid setup data is reported by the setup data validation.
+pub(crate) fn check_handshake_endpoint(
+    setup_data: &JsValue,
+    endpoint: &Endpoint,
+) -> Result<(), String> {
+    let Ok(setup_data) = serde_wasm_bindgen::from_value::<
+        WebSocketClientJSInterfaceSetupData,
+    >(setup_data.clone()) else {
+        return Ok(());
+    };
+    match setup_data.handshake {
+        Some(credentials) if credentials.endpoint != endpoint.to_string() => {
+            Err(format!(
+                "Handshake endpoint {} does not match the runtime endpoint \
+                 {endpoint}",
+                credentials.endpoint
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Setup data for the WebSocket client interface in the browser.
+/// Superset of the common WebSocket client setup data with options that
+/// are only available for the JS implementation.
+#[derive(Serialize, Deserialize, Tsify, Debug, Clone)]
+pub struct WebSocketClientJSInterfaceSetupData {
+    pub address: String,
+    /// Reconnection behaviour after the connection was lost
+    /// (default: reconnect with exponential backoff)
+    #[serde(default)]
+    pub reconnect: WebSocketReconnectConfig,
+    /// Subprotocols offered to the server, the selected protocol
+    /// is available after the connection was opened
+    #[serde(default)]
+    pub protocols: Vec<String>,
+    /// Token that is sent to authenticate the client
+    #[serde(default)]
+    pub auth: Option<WebSocketAuth>,
+    /// Credentials for servers that require the endpoint to sign a
+    /// challenge before blocks are accepted
+    #[serde(default)]
+    pub handshake: Option<WebSocketHandshakeCredentials>,
+}
+
+/// Event handlers of a WebSocket, kept until the WebSocket is replaced
+/// by a new connection attempt
+struct WebSocketHandlers {
+    ws: web_sys::WebSocket,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+    _on_error: Closure<dyn FnMut(ErrorEvent)>,
+    _on_close: Closure<dyn FnMut(CloseEvent)>,
+    _on_open: Closure<dyn FnMut(MessageEvent)>,
+}
 
-        self.ws.set_binary_type(web_sys::BinaryType::Arraybuffer);
+impl Drop for WebSocketHandlers {
+    fn drop(&mut self) {
+        // the closures are invalid once dropped, so events of the
+        // replaced WebSocket must no longer call them
+        self.ws.set_onmessage(None);
+        self.ws.set_onerror(None);
+        self.ws.set_onclose(None);
+        self.ws.set_onopen(None);
+    }
+}
 
-        let (mut sender, mut receiver) =
+/// WebSocket connection that is shared with the event callbacks, so that a
+/// new WebSocket can be opened after the connection was lost.
+/// The socket of the interface is kept while reconnecting, so that outgoing
+/// blocks are held until the connection is reestablished.
+#[derive(Clone)]
+struct WebSocketConnection {
+    address: Url,
+    /// Address including the auth token, must not be logged
+    url: Url,
+    protocols: Vec<String>,
+    interface_uuid: ComInterfaceUUID,
+    sockets: Arc<Mutex<ComInterfaceSockets>>,
+    set_state: Rc<dyn Fn(ComInterfaceState)>,
+    reconnect: WebSocketReconnectConfig,
+    ws: Rc<RefCell<Option<web_sys::WebSocket>>>,
+    handlers: Rc<RefCell<Option<WebSocketHandlers>>>,
+    pending_blocks: Rc<RefCell<VecDeque<Vec<u8>>>>,
+    is_reconnecting: Rc<Cell<bool>>,
+    closed: Rc<Cell<bool>>,
+    close_info: Rc<RefCell<Option<WebSocketCloseInfo>>>,
+    on_close: Rc<RefCell<Option<Function>>>,
+    handshake: Option<WebSocketHandshakeCredentials>,
+}
+
+impl WebSocketConnection {
+    async fn connect(&self) -> Result<(), WebSocketError> {
+        info!("Connecting to WebSocket server at {}", self.address);
+        let protocols = self
+            .protocols
+            .iter()
+            .map(|protocol| JsValue::from_str(protocol))
+            .collect::<js_sys::Array>();
+        let ws = web_sys::WebSocket::new_with_str_sequence(
+            self.url.as_ref(),
+            &protocols,
+        )
+        .map_err(|_| WebSocketError::InvalidURL)?;
+        ws.set_binary_type(web_sys::BinaryType::Arraybuffer);
+
+        let (sender, mut receiver) =
             mpsc::channel::<Result<(), WebSocketError>>(32); // buffer size of 32
 
-        let message_callback = self.create_onmessage_callback();
+        // only connections that were opened successfully are reestablished,
+        // failed attempts are reported to the caller of connect
+        let is_open = Rc::new(Cell::new(false));
+
+        let message_callback = self.create_onmessage_callback(
+            ws.clone(),
+            is_open.clone(),
+            sender.clone(),
+        );
         let error_callback = self.create_onerror_callback(sender.clone());
+        let close_callback =
+            self.create_onclose_callback(is_open.clone(), sender.clone());
 
-        let uuid = self.get_uuid().clone();
-        let com_interface_sockets = self.get_sockets().clone();
+        let mut open_sender = sender;
+        let interface_uuid = self.interface_uuid.clone();
+        let sockets = self.sockets.clone();
+        // with handshake, the connection is established once accepted
+        let has_handshake = self.handshake.is_some();
         let open_callback = Closure::once(move |_: MessageEvent| {
-            let socket = ComInterfaceSocket::new(
-                uuid.clone(),
-                InterfaceDirection::InOut,
-                1,
-            );
-            com_interface_sockets
-                .lock()
-                .unwrap()
-                .add_socket(Arc::new(Mutex::new(socket)));
-            info!("WebSocket connection opened successfully");
-            spawn_with_panic_notify_default(async move {
-                sender
-                    .send(Ok(()))
-                    .await
-                    .expect("Failed to send onopen event");
-            });
+            if !has_handshake {
+                is_open.set(true);
+            }
+            // the socket is kept while reconnecting
+            let mut sockets = sockets.lock().unwrap();
+            if sockets.sockets.is_empty() {
+                let socket = ComInterfaceSocket::new(
+                    interface_uuid,
+                    InterfaceDirection::InOut,
+                    1,
+                );
+                notify_socket_registered(&socket);
+                sockets.add_socket(Arc::new(Mutex::new(socket)));
+            }
+            let _ = open_sender.try_send(Ok(()));
         });
-        let close_callback = self.create_onclose_callback();
-
-        self.ws
-            .set_onmessage(Some(message_callback.as_ref().unchecked_ref()));
-        self.ws
-            .set_onerror(Some(error_callback.as_ref().unchecked_ref()));
 
-        self.ws
-            .set_onclose(Some(close_callback.as_ref().unchecked_ref()));
-        self.ws
-            .set_onopen(Some(open_callback.as_ref().unchecked_ref()));
+        ws.set_onmessage(Some(message_callback.as_ref().unchecked_ref()));
+        ws.set_onerror(Some(error_callback.as_ref().unchecked_ref()));
+        ws.set_onclose(Some(close_callback.as_ref().unchecked_ref()));
+        ws.set_onopen(Some(open_callback.as_ref().unchecked_ref()));
+        // the handlers of the previous attempt are removed
+        self.handlers.replace(Some(WebSocketHandlers {
+            ws: ws.clone(),
+            _on_message: message_callback,
+            _on_error: error_callback,
+            _on_close: close_callback,
+            _on_open: open_callback,
+        }));
 
         info!("Waiting for WebSocket connection to open...");
-        /* receiver.recv().await.map_err(|_| {
-            error!("Failed to receive onopen event");
-            WebSocketError::Other("Failed to receive onopen event".to_string())
-        })?;*/
         receiver.next().await.ok_or_else(|| {
             error!("Failed to receive onopen event");
             WebSocketError::Other("Failed to receive onopen event".to_string())
         })??;
+        if has_handshake {
+            info!("Waiting for WebSocket handshake...");
+            receiver.next().await.ok_or_else(|| {
+                error!("Failed to receive handshake result");
+                WebSocketError::Other(
+                    "Failed to receive handshake result".to_string(),
+                )
+            })??;
+        }
 
-        message_callback.forget();
-        error_callback.forget();
-        open_callback.forget();
-        close_callback.forget();
+        if !ws.protocol().is_empty() {
+            info!("WebSocket protocol selected by server: {}", ws.protocol());
+        }
+        self.ws.replace(Some(ws));
+        info!("WebSocket connection opened successfully");
+        (self.set_state)(ComInterfaceState::Connected);
+        self.send_pending_blocks();
         Ok(())
     }
 
+    /// Tries to reestablish the connection until it succeeds, the maximum
+    /// number of attempts is reached or the interface is closed
+    async fn reconnect(self) {
+        self.is_reconnecting.set(true);
+        (self.set_state)(ComInterfaceState::Connecting);
+        let mut attempt = 0;
+        while !self.closed.get() {
+            if let Some(max_attempts) = self.reconnect.max_attempts
+                && attempt >= max_attempts
+            {
+                warn!(
+                    "Giving up reconnecting to {} after {attempt} attempts",
+                    self.address
+                );
+                break;
+            }
+            let delay = self.reconnect.get_delay(attempt);
+            info!("Reconnecting to {} in {delay:?}", self.address);
+            futures_timer::Delay::new(delay).await;
+            if self.closed.get() {
+                break;
+            }
+            attempt += 1;
+            match self.connect().await {
+                Ok(()) => {
+                    self.is_reconnecting.set(false);
+                    return;
+                }
+                Err(e) => warn!("Failed to reconnect: {e:?}"),
+            }
+        }
+        self.is_reconnecting.set(false);
+        self.handlers.take();
+        let pending_blocks = self.pending_blocks.take();
+        if !pending_blocks.is_empty() {
+            warn!(
+                "Dropping {} pending blocks after reconnecting failed",
+                pending_blocks.len()
+            );
+        }
+        (self.set_state)(ComInterfaceState::NotConnected);
+    }
+
+    fn send(&self, block: &[u8]) -> bool {
+        if let Some(ws) = self.ws.borrow().as_ref()
+            && ws.ready_state() == web_sys::WebSocket::OPEN
+        {
+            return ws
+                .send_with_u8_array(block)
+                .map_err(|e| {
+                    error!("Error sending message: {e:?}");
+                    false
+                })
+                .is_ok();
+        }
+        if self.is_reconnecting.get() {
+            let mut pending_blocks = self.pending_blocks.borrow_mut();
+            if pending_blocks.len() >= MAX_PENDING_BLOCKS {
+                warn!("Too many pending blocks, dropping the oldest block");
+                pending_blocks.pop_front();
+            }
+            pending_blocks.push_back(block.to_vec());
+            true
+        } else {
+            error!("Error sending message: WebSocket is not connected");
+            false
+        }
+    }
+
+    fn send_pending_blocks(&self) {
+        let pending_blocks = self.pending_blocks.take();
+        if !pending_blocks.is_empty() {
+            info!("Sending {} pending blocks", pending_blocks.len());
+        }
+        for block in pending_blocks {
+            self.send(&block);
+        }
+    }
+
+    fn close(&self) -> bool {
+        self.closed.set(true);
+        match self.ws.borrow().as_ref() {
+            Some(ws) => ws.close().is_ok(),
+            None => true,
+        }
+    }
+
+    fn get_protocol(&self) -> Option<String> {
+        self.ws.borrow().as_ref().map(|ws| ws.protocol())
+    }
+
+    /// Stores the close info and notifies the on_close callback
+    fn handle_close_event(&self, event: &CloseEvent) {
+        let close_info = WebSocketCloseInfo::from(event);
+        warn!(
+            "Socket closed with code {} (clean: {}): {}",
+            close_info.code, close_info.was_clean, close_info.reason
+        );
+        if let Some(on_close) = self.on_close.borrow().as_ref() {
+            match to_value(&close_info) {
+                Ok(value) => {
+                    if let Err(e) = on_close.call1(&JsValue::NULL, &value) {
+                        error!("Error in close callback: {e:?}");
+                    }
+                }
+                Err(e) => error!("Failed to convert close info: {e:?}"),
+            }
+        }
+        self.close_info.replace(Some(close_info));
+    }
+
     fn create_onmessage_callback(
-        &mut self,
+        &self,
+        ws: web_sys::WebSocket,
+        is_open: Rc<Cell<bool>>,
+        sender: mpsc::Sender<Result<(), WebSocketError>>,
     ) -> Closure<dyn FnMut(MessageEvent)> {
-        let sockets = self.get_sockets().clone();
+        let sockets = self.sockets.clone();
+        let handshake = self.handshake.clone();
+        let origin = self.address.origin().ascii_serialization();
         Closure::new(move |e: MessageEvent| {
-            let sockets = sockets.clone();
+            let Ok(abuf) = e.data().dyn_into::<js_sys::ArrayBuffer>() else {
+                return;
+            };
+            let data = js_sys::Uint8Array::new(&abuf).to_vec();
+            if let Some(credentials) = &handshake
+                && let Some(message) =
+                    WebSocketHandshakeMessage::from_bytes(&data)
+            {
+                match message {
+                    WebSocketHandshakeMessage::Challenge(challenge) => {
+                        spawn_with_panic_notify_default(answer_challenge(
+                            ws.clone(),
+                            credentials.clone(),
+                            origin.clone(),
+                            challenge,
+                        ));
+                    }
+                    WebSocketHandshakeMessage::Accepted => {
+                        info!("WebSocket handshake accepted");
+                        is_open.set(true);
+                        let _ = sender.clone().try_send(Ok(()));
+                    }
+                    _ => warn!("Unexpected handshake message: {message:?}"),
+                }
+                return;
+            }
+
             let sockets = sockets.lock().unwrap();
             let socket = sockets.sockets.values().next().unwrap();
-
-            let receive_queue = socket.lock().unwrap().receive_queue.clone();
-            if let Ok(abuf) = e.data().dyn_into::<js_sys::ArrayBuffer>() {
-                let array = js_sys::Uint8Array::new(&abuf);
-                receive_queue.lock().unwrap().extend(array.to_vec());
-            }
+            receive_block(socket, &data);
         })
     }
 
@@ -143,32 +484,187 @@ impl WebSocketClientJSInterface {
     ) -> Closure<dyn FnMut(ErrorEvent)> {
         Closure::new(move |e: ErrorEvent| {
             error!("Socket error event: {:?}", e.message());
-            let mut sender = sender.clone();
-            spawn_with_panic_notify_default(async move {
-                sender
-                    .send(Err(WebSocketError::ConnectionError))
-                    .await
-                    .expect("Failed to send onerror event");
-            })
+            let _ = sender
+                .clone()
+                .try_send(Err(WebSocketError::ConnectionError));
         })
     }
 
-    fn create_onclose_callback(&self) -> Closure<dyn FnMut()> {
-        let state = self.get_info().state.clone();
-        Closure::new(move || {
-            warn!("Socket closed");
-            state.lock().unwrap().set(ComInterfaceState::NotConnected);
+    fn create_onclose_callback(
+        &self,
+        is_open: Rc<Cell<bool>>,
+        sender: mpsc::Sender<Result<(), WebSocketError>>,
+    ) -> Closure<dyn FnMut(CloseEvent)> {
+        let connection = self.clone();
+        Closure::new(move |event: CloseEvent| {
+            connection.handle_close_event(&event);
+            if !is_open.replace(false) {
+                // the connection could not be established
+                let _ = sender
+                    .clone()
+                    .try_send(Err(WebSocketError::ConnectionError));
+                return;
+            }
+            connection.ws.take();
+            if connection.closed.get() || !connection.reconnect.is_enabled() {
+                (connection.set_state)(ComInterfaceState::NotConnected);
+                // the handlers reference the connection, they are removed
+                // once this handler has returned
+                let handlers = connection.handlers.clone();
+                spawn_with_panic_notify_default(async move {
+                    handlers.take();
+                });
+            } else {
+                // blocks sent before the reconnect task starts are held
+                connection.is_reconnecting.set(true);
+                spawn_with_panic_notify_default(connection.clone().reconnect());
+            }
         })
     }
 }
 
-impl ComInterfaceFactory<WebSocketClientInterfaceSetupData>
+/// Signs the challenge of the server together with the endpoint and
+/// the origin of the server
+async fn answer_challenge(
+    ws: web_sys::WebSocket,
+    credentials: WebSocketHandshakeCredentials,
+    origin: String,
+    challenge: Vec<u8>,
+) {
+    let data = WebSocketHandshakeMessage::get_signed_data(
+        &origin,
+        &challenge,
+        &credentials.endpoint,
+    );
+    match CryptoJS.sig_ed25519(&credentials.private_key, &data).await {
+        Ok(signature) => {
+            let response = WebSocketHandshakeMessage::Response {
+                endpoint: credentials.endpoint,
+                signature: signature.to_vec(),
+            };
+            if let Err(e) = ws.send_with_u8_array(&response.to_bytes()) {
+                error!("Error sending handshake response: {e:?}");
+            }
+        }
+        Err(_) => {
+            error!("Failed to sign handshake challenge");
+            let _ = ws.close();
+        }
+    }
+}
+
+pub struct WebSocketClientJSInterface {
+    pub address: Url,
+    url: Url,
+    reconnect: WebSocketReconnectConfig,
+    protocols: Vec<String>,
+    connection: Option<WebSocketConnection>,
+    close_info: Rc<RefCell<Option<WebSocketCloseInfo>>>,
+    on_close: Rc<RefCell<Option<Function>>>,
+    handshake: Option<WebSocketHandshakeCredentials>,
+    info: ComInterfaceInfo,
+}
+
+impl SingleSocketProvider for WebSocketClientJSInterface {
+    fn provide_sockets(&self) -> Arc<Mutex<ComInterfaceSockets>> {
+        self.get_sockets().clone()
+    }
+}
+wrap_error_for_js!(JSWebSocketError, datex_core::network::com_interfaces::default_com_interfaces::websocket::websocket_common::WebSocketError);
+use datex_macros::{com_interface, create_opener};
+
+#[com_interface]
+impl WebSocketClientJSInterface {
+    pub fn new(
+        setup_data: WebSocketClientJSInterfaceSetupData,
+    ) -> Result<WebSocketClientJSInterface, WebSocketError> {
+        let address = parse_url(&setup_data.address, true)
+            .map_err(|_| WebSocketError::InvalidURL)?;
+        let mut url = address.clone();
+        let mut protocols = setup_data.protocols;
+        if let Some(auth) = setup_data.auth {
+            match auth.method {
+                WebSocketAuthMethod::Query => {
+                    url.query_pairs_mut()
+                        .append_pair(AUTH_QUERY_PARAMETER, &auth.token);
+                }
+                WebSocketAuthMethod::Protocol => {
+                    protocols.push(format!("bearer.{}", auth.token));
+                }
+            }
+        }
+        let interface = WebSocketClientJSInterface {
+            address,
+            url,
+            reconnect: setup_data.reconnect,
+            protocols,
+            connection: None,
+            close_info: Rc::new(RefCell::new(None)),
+            on_close: Rc::new(RefCell::new(None)),
+            handshake: setup_data.handshake,
+            info: ComInterfaceInfo::new(),
+        };
+        Ok(interface)
+    }
+
+    /// Returns the subprotocol selected by the server,
+    /// None if the interface is not connected
+    pub fn get_protocol(&self) -> Option<String> {
+        self.connection
+            .as_ref()
+            .and_then(|connection| connection.get_protocol())
+    }
+
+    /// Returns the close code, reason and wasClean flag of the last
+    /// closed or failed connection
+    pub fn get_close_info(&self) -> Option<WebSocketCloseInfo> {
+        self.close_info.borrow().clone()
+    }
+
+    /// Sets the callback that is invoked with the close info
+    /// whenever the connection is closed or could not be established
+    pub fn set_on_close(&self, callback: Function) {
+        self.on_close.replace(Some(callback));
+    }
+
+    #[create_opener]
+    async fn open(&mut self) -> Result<(), WebSocketError> {
+        let connection = WebSocketConnection {
+            address: self.address.clone(),
+            url: self.url.clone(),
+            protocols: self.protocols.clone(),
+            interface_uuid: self.get_uuid().clone(),
+            sockets: self.get_sockets().clone(),
+            set_state: state_setter(self),
+            reconnect: self.reconnect.clone(),
+            ws: Rc::new(RefCell::new(None)),
+            handlers: Rc::new(RefCell::new(None)),
+            pending_blocks: Rc::new(RefCell::new(VecDeque::new())),
+            is_reconnecting: Rc::new(Cell::new(false)),
+            closed: Rc::new(Cell::new(false)),
+            close_info: self.close_info.clone(),
+            on_close: self.on_close.clone(),
+            handshake: self.handshake.clone(),
+        };
+        connection.connect().await?;
+        self.connection = Some(connection);
+        Ok(())
+    }
+}
+
+impl ComInterfaceFactory<WebSocketClientJSInterfaceSetupData>
     for WebSocketClientJSInterface
 {
     fn create(
-        setup_data: WebSocketClientInterfaceSetupData,
+        setup_data: WebSocketClientJSInterfaceSetupData,
     ) -> Result<WebSocketClientJSInterface, ComInterfaceError> {
-        WebSocketClientJSInterface::new(&setup_data.address)
+        if !setup_data.reconnect.is_valid() {
+            error!(
+                "Reconnect multiplier must be at least 1 and jitter between 0 and 1"
+            );
+            return Err(ComInterfaceError::InvalidSetupData);
+        }
+        WebSocketClientJSInterface::new(setup_data)
             .map_err(|_| ComInterfaceError::InvalidSetupData)
     }
 
@@ -183,22 +679,25 @@ impl ComInterfaceFactory<WebSocketClientInterfaceSetupData>
     }
 }
 
-impl ComInterface for WebSocketClientJSInterface {
-    fn send_block<'a>(
+impl WebSocketClientJSInterface {
+    fn send_to_socket<'a>(
         &'a mut self,
         block: &'a [u8],
         _: ComInterfaceSocketUUID,
     ) -> Pin<Box<dyn Future<Output = bool> + 'a>> {
-        Box::pin(async move {
-            self.ws
-                .send_with_u8_array(block)
-                .map_err(|e| {
-                    error!("Error sending message: {e:?}");
-                    false
-                })
-                .is_ok()
-        })
+        let success = match &self.connection {
+            Some(connection) => connection.send(block),
+            None => {
+                error!("Error sending message: WebSocket is not connected");
+                false
+            }
+        };
+        Box::pin(async move { success })
     }
+}
+
+impl ComInterface for WebSocketClientJSInterface {
+    intercepted_send_block!(send_to_socket);
 
     fn init_properties(&self) -> InterfaceProperties {
         InterfaceProperties {
@@ -209,8 +708,54 @@ impl ComInterface for WebSocketClientJSInterface {
     fn handle_close<'a>(
         &'a mut self,
     ) -> Pin<Box<dyn Future<Output = bool> + 'a>> {
-        Box::pin(async move { self.ws.close().is_ok() })
+        let success = match self.connection.take() {
+            Some(connection) => connection.close(),
+            None => false,
+        };
+        Box::pin(async move { success })
     }
     delegate_com_interface_info!();
     set_opener!(open);
 }
+
+#[wasm_bindgen]
+impl JSComHub {
+    pub fn websocket_client_interface_get_protocol(
+        &self,
+        interface_uuid: String,
+    ) -> Result<Option<String>, JsError> {
+        let interface = self
+            .get_interface_for_uuid::<WebSocketClientJSInterface>(
+                interface_uuid,
+            )
+            .map_err(|e| JsError::new(&format!("{e:?}")))?;
+        Ok(interface.borrow().get_protocol())
+    }
+
+    pub fn websocket_client_interface_get_close_info(
+        &self,
+        interface_uuid: String,
+    ) -> Result<JsValue, JsError> {
+        let interface = self
+            .get_interface_for_uuid::<WebSocketClientJSInterface>(
+                interface_uuid,
+            )
+            .map_err(|e| JsError::new(&format!("{e:?}")))?;
+        let close_info = interface.borrow().get_close_info();
+        to_value(&close_info).map_err(js_error)
+    }
+
+    pub fn websocket_client_interface_set_on_close(
+        &self,
+        interface_uuid: String,
+        on_close: Function,
+    ) -> Result<(), JsError> {
+        let interface = self
+            .get_interface_for_uuid::<WebSocketClientJSInterface>(
+                interface_uuid,
+            )
+            .map_err(|e| JsError::new(&format!("{e:?}")))?;
+        interface.borrow().set_on_close(on_close);
+        Ok(())
+    }
+}
@@ -0,0 +1,207 @@
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use url::Url;
+use web_sys::CloseEvent;
+
+/// Close code, reason and whether the connection was closed cleanly,
+/// as reported by the last close event of the WebSocket.
+/// Connections that could not be established (e.g. because the handshake
+/// was rejected) are reported by browsers with code 1006 and no reason.
+#[derive(Serialize, Deserialize, Tsify, Debug, Clone)]
+pub struct WebSocketCloseInfo {
+    pub code: u16,
+    pub reason: String,
+    pub was_clean: bool,
+}
+
+impl From<&CloseEvent> for WebSocketCloseInfo {
+    fn from(event: &CloseEvent) -> Self {
+        WebSocketCloseInfo {
+            code: event.code(),
+            reason: event.reason(),
+            was_clean: event.was_clean(),
+        }
+    }
+}
+
+/// Prefix of the handshake messages that are exchanged before a
+/// WebSocket server with enabled handshake accepts DATEX blocks
+pub const HANDSHAKE_MAGIC: &[u8; 4] = b"DXHS";
+/// Version of the handshake protocol, part of the signed data
+pub const HANDSHAKE_VERSION: u8 = 1;
+/// Length of the random challenge sent by the server
+pub const HANDSHAKE_CHALLENGE_LENGTH: usize = 32;
+/// Length of an Ed25519 signature
+const SIGNATURE_LENGTH: usize = 64;
+
+/// Close code if the client sent blocks or invalid messages
+/// before the handshake was completed
+pub const CLOSE_CODE_HANDSHAKE_REQUIRED: u16 = 4001;
+/// Close code if the endpoint is unknown or the signature is invalid
+pub const CLOSE_CODE_HANDSHAKE_FAILED: u16 = 4003;
+/// Close code if the client did not answer the challenge in time
+pub const CLOSE_CODE_HANDSHAKE_TIMEOUT: u16 = 4008;
+
+/// Messages of the handshake in which a connecting endpoint proves that
+/// it owns its endpoint key:
+/// 1. the server sends a random challenge after the socket was opened
+/// 2. the client signs the challenge together with its endpoint and the
+///    origin of the server it connected to
+/// 3. the server verifies the signature with the public key of the
+///    endpoint and its own origin and accepts the socket
+#[derive(Debug, Clone, PartialEq)]
+pub enum WebSocketHandshakeMessage {
+    Challenge(Vec<u8>),
+    Response {
+        endpoint: String,
+        signature: Vec<u8>,
+    },
+    Accepted,
+}
+
+impl WebSocketHandshakeMessage {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = HANDSHAKE_MAGIC.to_vec();
+        match self {
+            WebSocketHandshakeMessage::Challenge(challenge) => {
+                bytes.push(0);
+                bytes.extend_from_slice(challenge);
+            }
+            WebSocketHandshakeMessage::Response {
+                endpoint,
+                signature,
+            } => {
+                bytes.push(1);
+                bytes.extend_from_slice(signature);
+                bytes.extend_from_slice(endpoint.as_bytes());
+            }
+            WebSocketHandshakeMessage::Accepted => bytes.push(2),
+        }
+        bytes
+    }
+
+    /// Parses a message, returns None if the message is not a valid
+    /// handshake message
+    pub fn from_bytes(bytes: &[u8]) -> Option<WebSocketHandshakeMessage> {
+        let bytes = bytes.strip_prefix(HANDSHAKE_MAGIC)?;
+        let (message_type, payload) = bytes.split_first()?;
+        match message_type {
+            0 => Some(WebSocketHandshakeMessage::Challenge(payload.to_vec())),
+            1 if payload.len() > SIGNATURE_LENGTH => {
+                let (signature, endpoint) = payload.split_at(SIGNATURE_LENGTH);
+                Some(WebSocketHandshakeMessage::Response {
+                    endpoint: String::from_utf8(endpoint.to_vec()).ok()?,
+                    signature: signature.to_vec(),
+                })
+            }
+            2 if payload.is_empty() => {
+                Some(WebSocketHandshakeMessage::Accepted)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the data that is signed by the client. The endpoint is
+    /// included so that a signature can not be reused for another endpoint,
+    /// the origin of the server so that a challenge can not be relayed from
+    /// another server, and the magic and version so that the signature is
+    /// not valid for any other protocol
+    pub fn get_signed_data(
+        origin: &str,
+        challenge: &[u8],
+        endpoint: &str,
+    ) -> Vec<u8> {
+        [
+            HANDSHAKE_MAGIC.as_slice(),
+            &[HANDSHAKE_VERSION],
+            origin.as_bytes(),
+            &[0],
+            challenge,
+            endpoint.as_bytes(),
+        ]
+        .concat()
+    }
+}
+
+/// Returns the origin of a WebSocket address as it is signed in the
+/// handshake, e.g. "wss://example.com" for "wss://example.com:443/path"
+pub fn get_handshake_origin(address: &str) -> Option<String> {
+    let origin = Url::parse(address).ok()?.origin();
+    origin.is_tuple().then(|| origin.ascii_serialization())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handshake_message_roundtrip() {
+        for message in [
+            WebSocketHandshakeMessage::Challenge(vec![7; 32]),
+            WebSocketHandshakeMessage::Response {
+                endpoint: "@example".to_string(),
+                signature: vec![1; 64],
+            },
+            WebSocketHandshakeMessage::Accepted,
+        ] {
+            assert_eq!(
+                WebSocketHandshakeMessage::from_bytes(&message.to_bytes()),
+                Some(message)
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_handshake_messages() {
+        // DATEX block
+        assert_eq!(WebSocketHandshakeMessage::from_bytes(&[0x01, 0x64]), None);
+        // response without endpoint
+        let mut response = b"DXHS\x01".to_vec();
+        response.extend_from_slice(&[1; 64]);
+        assert_eq!(WebSocketHandshakeMessage::from_bytes(&response), None);
+        assert_eq!(
+            WebSocketHandshakeMessage::from_bytes(b"DXHS\x02\x00"),
+            None
+        );
+    }
+
+    #[test]
+    fn signed_data_is_bound_to_server() {
+        let challenge = [7; 32];
+        let data = WebSocketHandshakeMessage::get_signed_data(
+            "wss://example.com",
+            &challenge,
+            "@example",
+        );
+        assert!(data.starts_with(HANDSHAKE_MAGIC));
+        assert_ne!(
+            data,
+            WebSocketHandshakeMessage::get_signed_data(
+                "wss://other.example.com",
+                &challenge,
+                "@example",
+            )
+        );
+        assert_ne!(
+            data,
+            WebSocketHandshakeMessage::get_signed_data(
+                "wss://example.com",
+                &challenge,
+                "@other",
+            )
+        );
+    }
+
+    #[test]
+    fn handshake_origin() {
+        assert_eq!(
+            get_handshake_origin("wss://example.com:443/path?token=1"),
+            Some("wss://example.com".to_string())
+        );
+        assert_eq!(
+            get_handshake_origin("ws://localhost:8084"),
+            Some("ws://localhost:8084".to_string())
+        );
+        assert_eq!(get_handshake_origin("not a url"), None);
+    }
+}
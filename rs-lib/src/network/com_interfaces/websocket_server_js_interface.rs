@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
 use std::rc::Rc;
+use std::str::FromStr;
 use std::sync::Mutex;
 use std::time::Duration; // FIXME no-std
 
@@ -14,23 +15,316 @@ use datex_core::network::com_interfaces::com_interface_properties::{
 use datex_core::network::com_interfaces::com_interface_socket::{
     ComInterfaceSocket, ComInterfaceSocketUUID,
 };
-use datex_core::network::com_interfaces::default_com_interfaces::websocket::websocket_common::{WebSocketError, WebSocketServerError, WebSocketServerInterfaceSetupData};
+use datex_core::network::com_interfaces::default_com_interfaces::websocket::websocket_common::{WebSocketError, WebSocketServerError};
 use datex_core::network::com_interfaces::socket_provider::MultipleSocketProvider;
 use datex_core::stdlib::sync::Arc;
 
-use crate::{define_registry, wrap_error_for_js};
+use crate::crypto::crypto_js::CryptoJS;
+use crate::intercepted_send_block;
+use crate::network::block_interceptors::receive_block;
+use crate::network::com_hub_events::{
+    notify_socket_closed, notify_socket_registered,
+};
+use crate::network::com_interfaces::websocket_js_common::{
+    CLOSE_CODE_HANDSHAKE_FAILED, CLOSE_CODE_HANDSHAKE_REQUIRED,
+    CLOSE_CODE_HANDSHAKE_TIMEOUT, HANDSHAKE_CHALLENGE_LENGTH,
+    WebSocketCloseInfo, WebSocketHandshakeMessage, get_handshake_origin,
+};
+use crate::{define_registry, js_utils::js_error, wrap_error_for_js};
+use datex_core::crypto::crypto::CryptoTrait;
+use datex_core::global::dxb_block::DXBBlock;
 use datex_core::network::com_hub::ComHubError;
 use datex_core::network::com_interfaces::com_interface::ComInterfaceState;
-use log::{debug, error, info};
+use datex_core::task::spawn_local;
+use datex_core::values::core_values::endpoint::Endpoint;
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::to_value;
+use tsify::Tsify;
 use wasm_bindgen::prelude::wasm_bindgen;
 use wasm_bindgen::{JsCast, prelude::Closure};
 use wasm_bindgen::{JsError, JsValue};
-use web_sys::{ErrorEvent, MessageEvent, js_sys};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::js_sys::{Function, Promise, Uint8Array};
+use web_sys::{CloseEvent, ErrorEvent, MessageEvent, js_sys};
+
+/// Close code used when a socket is closed without an explicit code
+const DEFAULT_CLOSE_CODE: u16 = 1000;
+/// Time in milliseconds in which a client has to answer the challenge
+const DEFAULT_HANDSHAKE_TIMEOUT: u32 = 10_000;
+
+/// Setup data for the WebSocket server interface in a JS runtime.
+/// Superset of the common WebSocket server setup data with options that
+/// are only available for the JS implementation.
+#[derive(Serialize, Deserialize, Tsify, Debug, Clone)]
+pub struct WebSocketServerJSInterfaceSetupData {
+    pub port: u16,
+    /// if true, the server will use wss (secure WebSocket). Defaults to true.
+    pub secure: Option<bool>,
+    /// If true, connecting endpoints have to sign a challenge with their
+    /// endpoint key before their blocks are accepted (default: false).
+    /// The public keys are resolved by the key resolver of the interface.
+    #[serde(default)]
+    pub handshake: bool,
+    /// Time in milliseconds in which a client has to answer the
+    /// handshake challenge (default: 10000)
+    #[serde(default)]
+    pub handshake_timeout: Option<u32>,
+    /// Address under which the clients connect to the server, e.g.
+    /// "wss://example.com". Handshakes that were signed for another server
+    /// are rejected (default: "wss://localhost:{port}", or "ws://" if
+    /// secure is false)
+    #[serde(default)]
+    pub handshake_origin: Option<String>,
+}
+
+/// A client connected to the WebSocket server interface
+#[derive(Serialize, Deserialize, Tsify, Debug, Clone)]
+pub struct WebSocketServerSocketInfo {
+    pub socket_uuid: String,
+    /// Endpoint of the client, if already known from the handshake
+    /// or a received block
+    pub endpoint: Option<String>,
+}
+
+type WebSockets =
+    Rc<RefCell<HashMap<ComInterfaceSocketUUID, web_sys::WebSocket>>>;
+
+/// Socket that is not added to the interface until the client
+/// answered the challenge
+struct PendingSocket {
+    socket: Arc<Mutex<ComInterfaceSocket>>,
+    challenge: Vec<u8>,
+}
+
+/// Authenticates connecting endpoints before their sockets are added
+/// to the interface, see [WebSocketHandshakeMessage] for the protocol.
+/// Shared with the event callbacks of the sockets.
+#[derive(Clone)]
+struct ServerHandshake {
+    timeout: Duration,
+    /// Origin of the server that the clients have to sign
+    origin: String,
+    sockets: Arc<Mutex<ComInterfaceSockets>>,
+    web_sockets: WebSockets,
+    pending: Rc<RefCell<HashMap<ComInterfaceSocketUUID, PendingSocket>>>,
+    resolve_key: Rc<RefCell<Option<Function>>>,
+    on_connect: Rc<RefCell<Option<Function>>>,
+}
+
+impl ServerHandshake {
+    fn start(
+        &self,
+        socket: Arc<Mutex<ComInterfaceSocket>>,
+        web_socket: &web_sys::WebSocket,
+    ) {
+        let socket_uuid = socket.lock().unwrap().uuid.clone();
+        let challenge = CryptoJS.random_bytes(HANDSHAKE_CHALLENGE_LENGTH);
+        let message = WebSocketHandshakeMessage::Challenge(challenge.clone());
+        self.pending
+            .borrow_mut()
+            .insert(socket_uuid.clone(), PendingSocket { socket, challenge });
+
+        // the challenge can only be sent once the socket is open
+        if web_socket.ready_state() == web_sys::WebSocket::OPEN {
+            self.send(&socket_uuid, &message);
+        } else {
+            let handshake = self.clone();
+            let open_socket_uuid = socket_uuid.clone();
+            let on_open = Closure::once(move || {
+                handshake.send(&open_socket_uuid, &message);
+            });
+            web_socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+            on_open.forget();
+        }
+        spawn_local(self.clone().close_after_timeout(socket_uuid));
+    }
+
+    fn is_pending(&self, socket_uuid: &ComInterfaceSocketUUID) -> bool {
+        self.pending.borrow().contains_key(socket_uuid)
+    }
+
+    fn send(
+        &self,
+        socket_uuid: &ComInterfaceSocketUUID,
+        message: &WebSocketHandshakeMessage,
+    ) {
+        if let Some(web_socket) = self.web_sockets.borrow().get(socket_uuid)
+            && let Err(e) = web_socket.send_with_u8_array(&message.to_bytes())
+        {
+            error!("Error sending handshake message: {e:?}");
+        }
+    }
+
+    fn close(
+        &self,
+        socket_uuid: &ComInterfaceSocketUUID,
+        code: u16,
+        reason: &str,
+    ) {
+        warn!("Handshake of socket {socket_uuid} failed: {reason}");
+        self.pending.borrow_mut().remove(socket_uuid);
+        if let Some(web_socket) = self.web_sockets.borrow().get(socket_uuid)
+            && let Err(e) = web_socket.close_with_code_and_reason(code, reason)
+        {
+            error!("Error closing socket: {e:?}");
+        }
+    }
+
+    async fn close_after_timeout(self, socket_uuid: ComInterfaceSocketUUID) {
+        futures_timer::Delay::new(self.timeout).await;
+        if self.is_pending(&socket_uuid) {
+            self.close(
+                &socket_uuid,
+                CLOSE_CODE_HANDSHAKE_TIMEOUT,
+                "Handshake timeout",
+            );
+        }
+    }
+
+    fn handle_message(
+        &self,
+        socket_uuid: &ComInterfaceSocketUUID,
+        data: &[u8],
+    ) {
+        let Some(pending) = self.pending.borrow_mut().remove(socket_uuid)
+        else {
+            return;
+        };
+        match WebSocketHandshakeMessage::from_bytes(data) {
+            Some(WebSocketHandshakeMessage::Response {
+                endpoint,
+                signature,
+            }) => {
+                spawn_local(self.clone().verify(
+                    socket_uuid.clone(),
+                    pending,
+                    endpoint,
+                    signature,
+                ));
+            }
+            _ => self.close(
+                socket_uuid,
+                CLOSE_CODE_HANDSHAKE_REQUIRED,
+                "Handshake required",
+            ),
+        }
+    }
+
+    async fn verify(
+        self,
+        socket_uuid: ComInterfaceSocketUUID,
+        pending: PendingSocket,
+        endpoint: String,
+        signature: Vec<u8>,
+    ) {
+        let endpoint = match self
+            .verify_signature(&pending.challenge, &endpoint, &signature)
+            .await
+        {
+            Ok(endpoint) => endpoint,
+            Err(reason) => {
+                self.close(&socket_uuid, CLOSE_CODE_HANDSHAKE_FAILED, &reason);
+                return;
+            }
+        };
+        // the socket may have been closed while the key was resolved
+        if !self.web_sockets.borrow().contains_key(&socket_uuid) {
+            return;
+        }
+
+        info!("Socket {socket_uuid} authenticated as {endpoint}");
+        notify_socket_registered(&pending.socket.lock().unwrap());
+        {
+            let mut sockets = self.sockets.lock().unwrap();
+            sockets.add_socket(pending.socket);
+            if sockets
+                .register_socket_endpoint(socket_uuid.clone(), endpoint, 1)
+                .is_err()
+            {
+                error!("Failed to register endpoint of socket {socket_uuid}");
+            }
+        }
+        self.send(&socket_uuid, &WebSocketHandshakeMessage::Accepted);
+        notify_connect(&self.on_connect, &socket_uuid);
+    }
+
+    /// Checks the signature of the challenge with the public key
+    /// of the endpoint, returns the endpoint if the signature is valid
+    async fn verify_signature(
+        &self,
+        challenge: &[u8],
+        endpoint: &str,
+        signature: &[u8],
+    ) -> Result<Endpoint, String> {
+        let parsed_endpoint = Endpoint::from_str(endpoint)
+            .map_err(|_| format!("Invalid endpoint {endpoint}"))?;
+        let public_key = self
+            .resolve_public_key(endpoint)
+            .await?
+            .ok_or_else(|| format!("Unknown endpoint {endpoint}"))?;
+        let data = WebSocketHandshakeMessage::get_signed_data(
+            &self.origin,
+            challenge,
+            endpoint,
+        );
+        let is_valid = CryptoJS
+            .ver_ed25519(&public_key, signature, &data)
+            .await
+            .map_err(|_| format!("Invalid public key for {endpoint}"))?;
+        if is_valid {
+            Ok(parsed_endpoint)
+        } else {
+            Err(format!("Invalid signature for {endpoint}"))
+        }
+    }
+
+    /// Calls the key resolver, which may return the public key
+    /// directly or as promise
+    async fn resolve_public_key(
+        &self,
+        endpoint: &str,
+    ) -> Result<Option<Vec<u8>>, String> {
+        let resolve_key = self
+            .resolve_key
+            .borrow()
+            .clone()
+            .ok_or_else(|| "No key resolver set".to_string())?;
+        let key = resolve_key
+            .call1(&JsValue::NULL, &JsValue::from_str(endpoint))
+            .map_err(|e| format!("Error in key resolver: {e:?}"))?;
+        let key = JsFuture::from(Promise::resolve(&key))
+            .await
+            .map_err(|e| format!("Error in key resolver: {e:?}"))?;
+        if key.is_undefined() || key.is_null() {
+            return Ok(None);
+        }
+        key.dyn_into::<Uint8Array>()
+            .map(|key| Some(key.to_vec()))
+            .map_err(|_| "Key resolver must return a Uint8Array".to_string())
+    }
+}
+
+fn notify_connect(
+    on_connect: &RefCell<Option<Function>>,
+    socket_uuid: &ComInterfaceSocketUUID,
+) {
+    if let Some(on_connect) = on_connect.borrow().as_ref()
+        && let Err(e) = on_connect
+            .call1(&JsValue::NULL, &JsValue::from_str(&socket_uuid.to_string()))
+    {
+        error!("Error in connect callback: {e:?}");
+    }
+}
 
 pub struct WebSocketServerJSInterface {
-    sockets: HashMap<ComInterfaceSocketUUID, web_sys::WebSocket>,
+    sockets: WebSockets,
     info: ComInterfaceInfo,
     port: u16,
+    handshake: Option<ServerHandshake>,
+    resolve_key: Rc<RefCell<Option<Function>>>,
+    on_connect: Rc<RefCell<Option<Function>>>,
+    on_disconnect: Rc<RefCell<Option<Function>>>,
 }
 impl MultipleSocketProvider for WebSocketServerJSInterface {
     fn provide_sockets(&self) -> Arc<Mutex<ComInterfaceSockets>> {
@@ -52,13 +346,43 @@ use datex_macros::{com_interface, create_opener};
 #[com_interface]
 impl WebSocketServerJSInterface {
     pub fn new(
-        setup_data: WebSocketServerInterfaceSetupData,
+        setup_data: WebSocketServerJSInterfaceSetupData,
     ) -> WebSocketServerJSInterface {
-        WebSocketServerJSInterface {
+        let mut interface = WebSocketServerJSInterface {
             info: ComInterfaceInfo::default(),
-            sockets: HashMap::new(),
+            sockets: Rc::new(RefCell::new(HashMap::new())),
             port: setup_data.port,
+            handshake: None,
+            resolve_key: Rc::new(RefCell::new(None)),
+            on_connect: Rc::new(RefCell::new(None)),
+            on_disconnect: Rc::new(RefCell::new(None)),
+        };
+        if setup_data.handshake {
+            let timeout = setup_data
+                .handshake_timeout
+                .unwrap_or(DEFAULT_HANDSHAKE_TIMEOUT);
+            let origin = setup_data.handshake_origin.unwrap_or_else(|| {
+                let scheme = match setup_data.secure {
+                    Some(false) => "ws",
+                    _ => "wss",
+                };
+                format!("{scheme}://localhost:{}", setup_data.port)
+            });
+            let origin = get_handshake_origin(&origin).unwrap_or_else(|| {
+                warn!("Invalid handshake origin {origin}");
+                origin
+            });
+            interface.handshake = Some(ServerHandshake {
+                timeout: Duration::from_millis(u64::from(timeout)),
+                origin,
+                sockets: interface.get_sockets().clone(),
+                web_sockets: interface.sockets.clone(),
+                pending: Rc::new(RefCell::new(HashMap::new())),
+                resolve_key: interface.resolve_key.clone(),
+                on_connect: interface.on_connect.clone(),
+            });
         }
+        interface
     }
 
     #[create_opener]
@@ -77,7 +401,7 @@ impl WebSocketServerJSInterface {
             1,
         );
         let socket_uuid = socket.uuid.clone();
-        self.add_socket(Arc::new(Mutex::new(socket)));
+        let socket = Arc::new(Mutex::new(socket));
 
         web_socket.set_binary_type(web_sys::BinaryType::Arraybuffer);
 
@@ -93,26 +417,128 @@ impl WebSocketServerJSInterface {
         on_message.forget();
         on_error.forget();
         on_close.forget();
-        self.sockets.insert(socket_uuid.clone(), web_socket);
+        self.sockets
+            .borrow_mut()
+            .insert(socket_uuid.clone(), web_socket.clone());
+
+        match &self.handshake {
+            Some(handshake) => handshake.start(socket, &web_socket),
+            None => {
+                notify_socket_registered(&socket.lock().unwrap());
+                self.add_socket(socket)
+            }
+        }
         socket_uuid
     }
 
+    /// Notifies the on_connect callback about a newly registered socket.
+    /// Sockets that have to complete the handshake are notified once
+    /// they are authenticated.
+    /// Must not be called while the interface is mutably borrowed, since
+    /// the callback may access the interface again.
+    fn notify_connect(&self, socket_uuid: &ComInterfaceSocketUUID) {
+        if self.handshake.is_none() {
+            notify_connect(&self.on_connect, socket_uuid);
+        }
+    }
+
+    /// Sets the function that resolves the public key of an endpoint
+    /// for the handshake. The function is called with the endpoint and
+    /// returns the SPKI encoded Ed25519 public key as Uint8Array (or a
+    /// promise of it), or undefined if the endpoint is unknown.
+    pub fn set_key_resolver(&self, resolve_key: Function) {
+        self.resolve_key.replace(Some(resolve_key));
+    }
+
+    /// Returns all currently connected sockets together with
+    /// the endpoints of the connected clients
+    pub fn get_socket_infos(&self) -> Vec<WebSocketServerSocketInfo> {
+        let sockets = self.get_sockets().clone();
+        let sockets = sockets.lock().unwrap();
+        self.sockets
+            .borrow()
+            .keys()
+            .map(|socket_uuid| WebSocketServerSocketInfo {
+                socket_uuid: socket_uuid.to_string(),
+                endpoint: sockets
+                    .sockets
+                    .get(socket_uuid)
+                    .and_then(|socket| {
+                        socket.lock().unwrap().direct_endpoint.clone()
+                    })
+                    .map(|endpoint| endpoint.to_string()),
+            })
+            .collect()
+    }
+
+    /// Closes the socket with the given close code and reason.
+    /// The socket is removed from the interface once the close
+    /// handshake is completed.
+    pub fn close_socket(
+        &self,
+        socket_uuid: &ComInterfaceSocketUUID,
+        code: Option<u16>,
+        reason: Option<String>,
+    ) -> Result<(), JsValue> {
+        let sockets = self.sockets.borrow();
+        let socket = sockets.get(socket_uuid).ok_or_else(|| {
+            JsValue::from_str(&format!("Socket not found: {socket_uuid}"))
+        })?;
+        socket.close_with_code_and_reason(
+            code.unwrap_or(DEFAULT_CLOSE_CODE),
+            &reason.unwrap_or_default(),
+        )
+    }
+
+    /// Sets the callback that is invoked with the socket UUID
+    /// when a new client connects
+    pub fn set_on_connect(&self, callback: Function) {
+        self.on_connect.replace(Some(callback));
+    }
+
+    /// Sets the callback that is invoked with the socket UUID
+    /// and the close info when a client disconnects
+    pub fn set_on_disconnect(&self, callback: Function) {
+        self.on_disconnect.replace(Some(callback));
+    }
+
     fn create_onmessage_callback(
         &mut self,
         socket_uuid: ComInterfaceSocketUUID,
     ) -> Closure<dyn FnMut(MessageEvent)> {
         let sockets = self.get_sockets().clone();
+        let handshake = self.handshake.clone();
         Closure::new(move |e: MessageEvent| {
-            let sockets = sockets.clone();
-            let sockets = sockets.lock().unwrap();
-            let socket = sockets.sockets.get(&socket_uuid).unwrap();
-            let receive_queue = socket.lock().unwrap().receive_queue.clone();
-            if let Ok(abuf) = e.data().dyn_into::<js_sys::ArrayBuffer>() {
-                let array = js_sys::Uint8Array::new(&abuf);
-                receive_queue.lock().unwrap().extend(array.to_vec());
-            } else {
+            let Ok(abuf) = e.data().dyn_into::<js_sys::ArrayBuffer>() else {
                 info!("message event, received Unknown: {:?}", e.data());
+                return;
+            };
+            let data = js_sys::Uint8Array::new(&abuf).to_vec();
+            if let Some(handshake) = &handshake
+                && handshake.is_pending(&socket_uuid)
+            {
+                handshake.handle_message(&socket_uuid, &data);
+                return;
+            }
+            let sockets = sockets.lock().unwrap();
+            let Some(socket) = sockets.sockets.get(&socket_uuid) else {
+                debug!(
+                    "Dropping message of unauthenticated socket {socket_uuid}"
+                );
+                return;
+            };
+            if let Some(handshake) = &handshake
+                && !is_sent_by_authenticated_endpoint(socket, &data)
+            {
+                drop(sockets);
+                handshake.close(
+                    &socket_uuid,
+                    CLOSE_CODE_HANDSHAKE_FAILED,
+                    "Block sender does not match the authenticated endpoint",
+                );
+                return;
             }
+            receive_block(socket, &data);
         })
     }
 
@@ -128,21 +554,58 @@ impl WebSocketServerJSInterface {
     fn create_onclose_callback(
         &mut self,
         socket_uuid: ComInterfaceSocketUUID,
-    ) -> Closure<dyn FnMut()> {
+    ) -> Closure<dyn FnMut(CloseEvent)> {
+        let interface_uuid = self.get_uuid().clone();
         let sockets = self.get_sockets().clone();
-        Closure::new(move || {
-            let mut sockets = sockets.lock().unwrap();
-            sockets.sockets.remove(&socket_uuid);
+        let web_sockets = self.sockets.clone();
+        let on_disconnect = self.on_disconnect.clone();
+        let handshake = self.handshake.clone();
+        Closure::new(move |e: CloseEvent| {
+            let was_connected = sockets
+                .lock()
+                .unwrap()
+                .sockets
+                .remove(&socket_uuid)
+                .is_some();
+            web_sockets.borrow_mut().remove(&socket_uuid);
+            if let Some(handshake) = &handshake {
+                handshake.pending.borrow_mut().remove(&socket_uuid);
+            }
+            // sockets that never completed the handshake were not announced
+            if !was_connected {
+                return;
+            }
+            notify_socket_closed(&interface_uuid, &socket_uuid);
+
+            let close_info = WebSocketCloseInfo::from(&e);
+            debug!(
+                "Socket {socket_uuid} closed with code {}: {}",
+                close_info.code, close_info.reason
+            );
+            if let Some(on_disconnect) = on_disconnect.borrow().as_ref() {
+                match to_value(&close_info) {
+                    Ok(value) => {
+                        if let Err(e) = on_disconnect.call2(
+                            &JsValue::NULL,
+                            &JsValue::from_str(&socket_uuid.to_string()),
+                            &value,
+                        ) {
+                            error!("Error in disconnect callback: {e:?}");
+                        }
+                    }
+                    Err(e) => error!("Failed to convert close info: {e:?}"),
+                }
+            }
         })
     }
 }
 
-impl ComInterfaceFactory<WebSocketServerInterfaceSetupData>
+impl ComInterfaceFactory<WebSocketServerJSInterfaceSetupData>
     for WebSocketServerJSInterface
 {
     // TODO: how to handle create and bind to Deno.serve?
     fn create(
-        setup_data: WebSocketServerInterfaceSetupData,
+        setup_data: WebSocketServerJSInterfaceSetupData,
     ) -> Result<WebSocketServerJSInterface, ComInterfaceError> {
         Ok(WebSocketServerJSInterface::new(setup_data))
     }
@@ -158,8 +621,8 @@ impl ComInterfaceFactory<WebSocketServerInterfaceSetupData>
     }
 }
 
-impl ComInterface for WebSocketServerJSInterface {
-    fn send_block<'a>(
+impl WebSocketServerJSInterface {
+    fn send_to_socket<'a>(
         &'a mut self,
         block: &'a [u8],
         socket_uuid: ComInterfaceSocketUUID,
@@ -167,6 +630,7 @@ impl ComInterface for WebSocketServerJSInterface {
         Box::pin(async move {
             debug!("Sending block: {block:?}");
             self.sockets
+                .borrow()
                 .get(&socket_uuid)
                 .ok_or_else(|| {
                     error!("Socket not found: {socket_uuid:?}");
@@ -181,6 +645,10 @@ impl ComInterface for WebSocketServerJSInterface {
                 .is_ok()
         })
     }
+}
+
+impl ComInterface for WebSocketServerJSInterface {
+    intercepted_send_block!(send_to_socket);
 
     fn init_properties(&self) -> InterfaceProperties {
         InterfaceProperties {
@@ -192,7 +660,7 @@ impl ComInterface for WebSocketServerJSInterface {
     fn handle_close<'a>(
         &'a mut self,
     ) -> Pin<Box<dyn Future<Output = bool> + 'a>> {
-        for (_, socket) in self.sockets.iter() {
+        for (_, socket) in self.sockets.borrow().iter() {
             // FIXME
             // Do we have to remove the event listeners here
             // or is this done automatically when the socket is closed?
@@ -204,6 +672,19 @@ impl ComInterface for WebSocketServerJSInterface {
     set_sync_opener!(open);
 }
 
+/// After the handshake, the socket may only receive blocks that were
+/// sent by the endpoint that it was authenticated as
+fn is_sent_by_authenticated_endpoint(
+    socket: &Arc<Mutex<ComInterfaceSocket>>,
+    data: &[u8],
+) -> bool {
+    let Some(endpoint) = socket.lock().unwrap().direct_endpoint.clone() else {
+        return false;
+    };
+    DXBBlock::from_bytes(data)
+        .is_ok_and(|block| block.routing_header.sender == endpoint)
+}
+
 define_registry!(WebSocketServerRegistry, WebSocketServerJSInterface);
 
 #[wasm_bindgen]
@@ -218,9 +699,86 @@ impl JSComHub {
                 interface_uuid,
             )?;
 
-        Ok(interface
-            .borrow_mut()
-            .register_socket(websocket)
-            .to_string())
+        let socket_uuid = interface.borrow_mut().register_socket(websocket);
+        interface.borrow().notify_connect(&socket_uuid);
+        Ok(socket_uuid.to_string())
+    }
+
+    /// Returns a list of all clients connected to the WebSocket server
+    /// interface with their socket UUIDs and endpoints
+    pub fn websocket_server_interface_get_sockets(
+        &self,
+        interface_uuid: String,
+    ) -> Result<JsValue, JsError> {
+        let interface = self
+            .get_interface_for_uuid::<WebSocketServerJSInterface>(
+                interface_uuid,
+            )
+            .map_err(|e| JsError::new(&format!("{e:?}")))?;
+        let sockets = interface.borrow().get_socket_infos();
+        to_value(&sockets).map_err(js_error)
+    }
+
+    /// Closes the connection to a client with an optional close code
+    /// (1000 or 3000-4999, default: 1000) and reason
+    pub fn websocket_server_interface_close_socket(
+        &self,
+        interface_uuid: String,
+        socket_uuid: String,
+        code: Option<u16>,
+        reason: Option<String>,
+    ) -> Result<(), JsError> {
+        let interface = self
+            .get_interface_for_uuid::<WebSocketServerJSInterface>(
+                interface_uuid,
+            )
+            .map_err(|e| JsError::new(&format!("{e:?}")))?;
+        let socket_uuid = ComInterfaceSocketUUID::from_string(socket_uuid);
+        interface
+            .borrow()
+            .close_socket(&socket_uuid, code, reason)
+            .map_err(|e| JsError::new(&format!("{e:?}")))
+    }
+
+    /// Sets the function that resolves the public key of an endpoint for
+    /// the handshake, called with the endpoint string and returning the
+    /// SPKI encoded Ed25519 key (or a promise of it), or undefined
+    pub fn websocket_server_interface_set_key_resolver(
+        &self,
+        interface_uuid: String,
+        resolve_key: Function,
+    ) -> Result<(), JSWebSocketServerError> {
+        let interface = self
+            .get_interface_for_uuid::<WebSocketServerJSInterface>(
+                interface_uuid,
+            )?;
+        interface.borrow().set_key_resolver(resolve_key);
+        Ok(())
+    }
+
+    pub fn websocket_server_interface_set_on_connect(
+        &self,
+        interface_uuid: String,
+        on_connect: Function,
+    ) -> Result<(), JSWebSocketServerError> {
+        let interface = self
+            .get_interface_for_uuid::<WebSocketServerJSInterface>(
+                interface_uuid,
+            )?;
+        interface.borrow().set_on_connect(on_connect);
+        Ok(())
+    }
+
+    pub fn websocket_server_interface_set_on_disconnect(
+        &self,
+        interface_uuid: String,
+        on_disconnect: Function,
+    ) -> Result<(), JSWebSocketServerError> {
+        let interface = self
+            .get_interface_for_uuid::<WebSocketServerJSInterface>(
+                interface_uuid,
+            )?;
+        interface.borrow().set_on_disconnect(on_disconnect);
+        Ok(())
     }
 }
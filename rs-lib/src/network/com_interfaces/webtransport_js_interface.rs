@@ -0,0 +1,385 @@
+use std::cell::Cell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Mutex;
+use std::time::Duration; // FIXME no-std
+
+use datex_core::network::com_interfaces::com_interface::{
+    ComInterface, ComInterfaceError, ComInterfaceFactory, ComInterfaceInfo,
+    ComInterfaceSockets, ComInterfaceState, ComInterfaceUUID,
+};
+use datex_core::network::com_interfaces::com_interface_properties::{
+    InterfaceDirection, InterfaceProperties,
+};
+use datex_core::network::com_interfaces::com_interface_socket::{
+    ComInterfaceSocket, ComInterfaceSocketUUID,
+};
+use datex_core::network::com_interfaces::socket_provider::SingleSocketProvider;
+use datex_core::stdlib::sync::Arc;
+use datex_core::task::spawn_with_panic_notify_default;
+use datex_core::{delegate_com_interface_info, set_opener};
+use datex_macros::{com_interface, create_opener};
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::js_sys::{Array, Reflect, Uint8Array};
+use web_sys::{
+    ReadableStream, ReadableStreamDefaultReader, WebTransport,
+    WebTransportBidirectionalStream, WebTransportHash, WebTransportOptions,
+    WritableStreamDefaultWriter,
+};
+
+use crate::intercepted_send_block;
+use crate::network::block_interceptors::receive_block;
+use crate::network::com_hub_events::{
+    notify_socket_closed, notify_socket_registered, state_setter,
+};
+
+/// Setup data for the WebTransport client interface
+#[derive(Serialize, Deserialize, Tsify, Debug, Clone)]
+pub struct WebTransportClientInterfaceSetupData {
+    /// URL of the WebTransport server, e.g. "https://example.com:4433/datex"
+    pub address: String,
+    /// If true, blocks that fit into a single datagram are sent as
+    /// unreliable datagrams, larger blocks are still sent over streams
+    /// (default: false)
+    #[serde(default)]
+    pub datagrams: bool,
+    /// SHA-256 hashes of self-signed server certificates that are
+    /// accepted, e.g. for a local development server
+    #[serde(default)]
+    pub server_certificate_hashes: Vec<Vec<u8>>,
+}
+
+/// Reads the next chunk of the reader, returns None if the stream is done
+async fn read_chunk(
+    reader: &ReadableStreamDefaultReader,
+) -> Result<Option<JsValue>, JsValue> {
+    let result = JsFuture::from(reader.read()).await?;
+    if Reflect::get(&result, &"done".into())?.is_truthy() {
+        return Ok(None);
+    }
+    Ok(Some(Reflect::get(&result, &"value".into())?))
+}
+
+fn get_reader(stream: &ReadableStream) -> ReadableStreamDefaultReader {
+    stream
+        .get_reader()
+        .unchecked_into::<ReadableStreamDefaultReader>()
+}
+
+#[derive(Clone)]
+struct WebTransportConnection {
+    transport: WebTransport,
+    datagram_writer: Option<WritableStreamDefaultWriter>,
+    max_datagram_size: usize,
+    interface_uuid: ComInterfaceUUID,
+    sockets: Arc<Mutex<ComInterfaceSockets>>,
+    set_state: Rc<dyn Fn(ComInterfaceState)>,
+    socket_uuid: ComInterfaceSocketUUID,
+    closed: Rc<Cell<bool>>,
+}
+
+impl WebTransportConnection {
+    async fn send(&self, block: &[u8]) -> bool {
+        // larger blocks are sent over a reliable stream
+        let result = match &self.datagram_writer {
+            Some(writer) if block.len() <= self.max_datagram_size => {
+                let array = Uint8Array::from(block);
+                JsFuture::from(writer.write_with_chunk(&array))
+                    .await
+                    .map(|_| ())
+            }
+            _ => self.send_stream(block).await,
+        };
+        result
+            .map_err(|e| error!("Error sending block: {e:?}"))
+            .is_ok()
+    }
+
+    /// Each block is sent over its own stream, so that a lost packet
+    /// does not delay the blocks on other streams
+    async fn send_stream(&self, block: &[u8]) -> Result<(), JsValue> {
+        let stream =
+            JsFuture::from(self.transport.create_bidirectional_stream())
+                .await?
+                .unchecked_into::<WebTransportBidirectionalStream>();
+        self.receive_stream(stream.readable().into());
+        let writer = stream.writable().get_writer()?;
+        JsFuture::from(writer.write_with_chunk(&Uint8Array::from(block)))
+            .await?;
+        JsFuture::from(writer.close()).await?;
+        Ok(())
+    }
+
+    /// Streams can end in the middle of a block, so the bytes of a
+    /// stream are only queued after it was completely received
+    fn receive_stream(&self, stream: ReadableStream) {
+        let connection = self.clone();
+        spawn_with_panic_notify_default(async move {
+            let reader = get_reader(&stream);
+            let mut bytes = Vec::new();
+            loop {
+                match read_chunk(&reader).await {
+                    Ok(Some(chunk)) => {
+                        if let Some(chunk) = chunk.dyn_ref::<Uint8Array>() {
+                            bytes.extend(chunk.to_vec());
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        debug!("WebTransport stream was aborted: {e:?}");
+                        return;
+                    }
+                }
+            }
+            connection.receive(&bytes);
+        });
+    }
+
+    /// Accepts the streams opened by the server until the
+    /// transport is closed
+    fn accept_streams(&self, incoming: ReadableStream, bidirectional: bool) {
+        let connection = self.clone();
+        spawn_with_panic_notify_default(async move {
+            let reader = get_reader(&incoming);
+            while let Ok(Some(stream)) = read_chunk(&reader).await {
+                let readable = if bidirectional {
+                    stream
+                        .unchecked_into::<WebTransportBidirectionalStream>()
+                        .readable()
+                        .into()
+                } else {
+                    stream.unchecked_into::<ReadableStream>()
+                };
+                connection.receive_stream(readable);
+            }
+        });
+    }
+
+    /// Each datagram contains a single block
+    fn receive_datagrams(&self) {
+        let connection = self.clone();
+        spawn_with_panic_notify_default(async move {
+            let reader =
+                get_reader(&connection.transport.datagrams().readable());
+            while let Ok(Some(datagram)) = read_chunk(&reader).await {
+                if let Some(datagram) = datagram.dyn_ref::<Uint8Array>() {
+                    connection.receive(&datagram.to_vec());
+                }
+            }
+        });
+    }
+
+    fn receive(&self, bytes: &[u8]) {
+        if bytes.is_empty() {
+            return;
+        }
+        let sockets = self.sockets.lock().unwrap();
+        if let Some(socket) = sockets.sockets.get(&self.socket_uuid) {
+            receive_block(socket, bytes);
+        }
+    }
+
+    /// Removes the socket once the transport is closed by either side
+    fn watch_closed(&self) {
+        let connection = self.clone();
+        spawn_with_panic_notify_default(async move {
+            let result = JsFuture::from(connection.transport.closed()).await;
+            connection
+                .sockets
+                .lock()
+                .unwrap()
+                .remove_socket(&connection.socket_uuid);
+            notify_socket_closed(
+                &connection.interface_uuid,
+                &connection.socket_uuid,
+            );
+            if !connection.closed.get() {
+                warn!("WebTransport connection closed: {result:?}");
+                (connection.set_state)(ComInterfaceState::NotConnected);
+            }
+        });
+    }
+
+    fn close(&self) -> bool {
+        self.closed.set(true);
+        if let Some(writer) = &self.datagram_writer {
+            writer.release_lock();
+        }
+        self.transport.close();
+        true
+    }
+}
+
+pub struct WebTransportClientJSInterface {
+    address: String,
+    datagrams: bool,
+    server_certificate_hashes: Vec<Vec<u8>>,
+    connection: Option<WebTransportConnection>,
+    info: ComInterfaceInfo,
+}
+
+impl SingleSocketProvider for WebTransportClientJSInterface {
+    fn provide_sockets(&self) -> Arc<Mutex<ComInterfaceSockets>> {
+        self.get_sockets().clone()
+    }
+}
+
+#[com_interface]
+impl WebTransportClientJSInterface {
+    pub fn new(
+        setup_data: WebTransportClientInterfaceSetupData,
+    ) -> WebTransportClientJSInterface {
+        WebTransportClientJSInterface {
+            address: setup_data.address,
+            datagrams: setup_data.datagrams,
+            server_certificate_hashes: setup_data.server_certificate_hashes,
+            connection: None,
+            info: ComInterfaceInfo::default(),
+        }
+    }
+
+    fn create_options(&self) -> WebTransportOptions {
+        let options = WebTransportOptions::new();
+        if !self.server_certificate_hashes.is_empty() {
+            let hashes = Array::new();
+            for hash in &self.server_certificate_hashes {
+                let entry = WebTransportHash::new();
+                entry.set_algorithm("sha-256");
+                entry.set_value(&Uint8Array::from(&hash[..]));
+                hashes.push(&entry);
+            }
+            options.set_server_certificate_hashes(&hashes);
+        }
+        options
+    }
+
+    #[create_opener]
+    async fn open(&mut self) -> Result<(), ()> {
+        let transport = WebTransport::new_with_options(
+            &self.address,
+            &self.create_options(),
+        )
+        .map_err(|e| {
+            error!("Failed to create WebTransport: {e:?}");
+        })?;
+        JsFuture::from(transport.ready()).await.map_err(|e| {
+            error!("Failed to connect to {}: {e:?}", self.address);
+        })?;
+
+        let datagram_writer =
+            if self.datagrams {
+                Some(transport.datagrams().writable().get_writer().map_err(
+                    |e| error!("Failed to open datagram writer: {e:?}"),
+                )?)
+            } else {
+                None
+            };
+        let socket = ComInterfaceSocket::new(
+            self.get_uuid().clone(),
+            InterfaceDirection::InOut,
+            1,
+        );
+        let connection = WebTransportConnection {
+            max_datagram_size: transport.datagrams().max_datagram_size()
+                as usize,
+            transport,
+            datagram_writer,
+            interface_uuid: self.get_uuid().clone(),
+            sockets: self.get_sockets().clone(),
+            set_state: state_setter(self),
+            socket_uuid: socket.uuid.clone(),
+            closed: Rc::new(Cell::new(false)),
+        };
+        notify_socket_registered(&socket);
+        self.get_sockets()
+            .lock()
+            .unwrap()
+            .add_socket(Arc::new(Mutex::new(socket)));
+
+        connection.accept_streams(
+            connection.transport.incoming_bidirectional_streams(),
+            true,
+        );
+        connection.accept_streams(
+            connection.transport.incoming_unidirectional_streams(),
+            false,
+        );
+        connection.receive_datagrams();
+        connection.watch_closed();
+        info!("Connected to {} via WebTransport", self.address);
+        self.connection = Some(connection);
+        Ok(())
+    }
+}
+
+impl ComInterfaceFactory<WebTransportClientInterfaceSetupData>
+    for WebTransportClientJSInterface
+{
+    fn create(
+        setup_data: WebTransportClientInterfaceSetupData,
+    ) -> Result<WebTransportClientJSInterface, ComInterfaceError> {
+        if !setup_data.address.starts_with("https://") {
+            error!("WebTransport address must start with https://");
+            return Err(ComInterfaceError::InvalidSetupData);
+        }
+        Ok(WebTransportClientJSInterface::new(setup_data))
+    }
+
+    fn get_default_properties() -> InterfaceProperties {
+        InterfaceProperties {
+            interface_type: "webtransport".to_string(),
+            channel: "webtransport".to_string(),
+            round_trip_time: Duration::from_millis(30),
+            max_bandwidth: 1000,
+            ..InterfaceProperties::default()
+        }
+    }
+}
+
+impl WebTransportClientJSInterface {
+    fn send_to_socket<'a>(
+        &'a mut self,
+        block: &'a [u8],
+        _: ComInterfaceSocketUUID,
+    ) -> Pin<Box<dyn Future<Output = bool> + 'a>> {
+        let connection = self.connection.clone();
+        Box::pin(async move {
+            match connection {
+                Some(connection) => connection.send(block).await,
+                None => {
+                    error!(
+                        "Error sending block: WebTransport is not connected"
+                    );
+                    false
+                }
+            }
+        })
+    }
+}
+
+impl ComInterface for WebTransportClientJSInterface {
+    intercepted_send_block!(send_to_socket);
+
+    fn init_properties(&self) -> InterfaceProperties {
+        InterfaceProperties {
+            name: Some(self.address.clone()),
+            ..Self::get_default_properties()
+        }
+    }
+    fn handle_close<'a>(
+        &'a mut self,
+    ) -> Pin<Box<dyn Future<Output = bool> + 'a>> {
+        let success = match self.connection.take() {
+            Some(connection) => connection.close(),
+            None => false,
+        };
+        Box::pin(async move { success })
+    }
+    delegate_com_interface_info!();
+    set_opener!(open);
+}
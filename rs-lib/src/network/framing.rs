@@ -0,0 +1,274 @@
+use log::warn;
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+/// Framing mode used to delimit blocks on byte-stream interfaces
+/// (e.g. serial) that do not preserve message boundaries.
+#[derive(
+    Serialize, Deserialize, Tsify, Debug, Clone, Copy, Default, PartialEq, Eq,
+)]
+pub enum FramingMode {
+    /// No framing, bytes are sent and received as-is
+    #[default]
+    None,
+    /// Consistent Overhead Byte Stuffing, frames are terminated by 0x00
+    Cobs,
+    /// Serial Line Internet Protocol (RFC 1055), frames are delimited by 0xC0
+    Slip,
+}
+
+#[derive(Serialize, Deserialize, Tsify, Debug, Clone, Copy, Default)]
+pub struct FramingConfig {
+    pub mode: FramingMode,
+    /// If true, a CRC-16/CCITT-FALSE checksum is appended to each frame
+    /// and frames with an invalid checksum are dropped.
+    /// Ignored if the framing mode is `None`.
+    #[serde(default)]
+    pub crc: bool,
+}
+
+const SLIP_END: u8 = 0xC0;
+const SLIP_ESC: u8 = 0xDB;
+const SLIP_ESC_END: u8 = 0xDC;
+const SLIP_ESC_ESC: u8 = 0xDD;
+
+/// Stateful encoder/decoder for framed byte streams.
+/// Incoming chunks can be split at arbitrary boundaries, the decoder buffers
+/// partial frames until the next delimiter is received. Corrupted frames are
+/// dropped and decoding resynchronizes at the next delimiter.
+#[derive(Debug, Default)]
+pub struct FrameCodec {
+    config: FramingConfig,
+    buffer: Vec<u8>,
+}
+
+impl FrameCodec {
+    pub fn new(config: FramingConfig) -> FrameCodec {
+        FrameCodec {
+            config,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Encodes a payload into a single frame, including delimiters
+    pub fn encode(&self, payload: &[u8]) -> Vec<u8> {
+        match self.config.mode {
+            FramingMode::None => payload.to_vec(),
+            FramingMode::Cobs => cobs_encode(&self.with_crc(payload)),
+            FramingMode::Slip => slip_encode(&self.with_crc(payload)),
+        }
+    }
+
+    /// Feeds a chunk of received bytes into the decoder and returns
+    /// all payloads of frames that were completed by this chunk
+    pub fn decode(&mut self, chunk: &[u8]) -> Vec<Vec<u8>> {
+        let delimiter = match self.config.mode {
+            FramingMode::None => return vec![chunk.to_vec()],
+            FramingMode::Cobs => 0x00,
+            FramingMode::Slip => SLIP_END,
+        };
+        let mut payloads = vec![];
+        for &byte in chunk {
+            if byte != delimiter {
+                self.buffer.push(byte);
+                continue;
+            }
+            // empty frames are used to flush line noise and are skipped
+            if self.buffer.is_empty() {
+                continue;
+            }
+            let frame = std::mem::take(&mut self.buffer);
+            let decoded = match self.config.mode {
+                FramingMode::Cobs => cobs_decode(&frame),
+                FramingMode::Slip => slip_decode(&frame),
+                FramingMode::None => unreachable!(),
+            };
+            match decoded.and_then(|frame| self.without_crc(frame)) {
+                Some(payload) => payloads.push(payload),
+                None => {
+                    warn!("Dropping corrupted frame ({} bytes)", frame.len())
+                }
+            }
+        }
+        payloads
+    }
+
+    fn with_crc(&self, payload: &[u8]) -> Vec<u8> {
+        let mut data = payload.to_vec();
+        if self.config.crc {
+            data.extend_from_slice(&crc16(payload).to_be_bytes());
+        }
+        data
+    }
+
+    fn without_crc(&self, mut frame: Vec<u8>) -> Option<Vec<u8>> {
+        if !self.config.crc {
+            return Some(frame);
+        }
+        if frame.len() < 2 {
+            return None;
+        }
+        let checksum = frame.split_off(frame.len() - 2);
+        if crc16(&frame).to_be_bytes() == checksum[..] {
+            Some(frame)
+        } else {
+            None
+        }
+    }
+}
+
+/// CRC-16/CCITT-FALSE (poly 0x1021, init 0xFFFF)
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+fn cobs_encode(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() + data.len() / 254 + 2);
+    let mut code_index = 0;
+    let mut code: u8 = 1;
+    out.push(0);
+    for &byte in data {
+        if byte == 0 {
+            out[code_index] = code;
+            code_index = out.len();
+            out.push(0);
+            code = 1;
+        } else {
+            out.push(byte);
+            code += 1;
+            if code == 0xFF {
+                out[code_index] = code;
+                code_index = out.len();
+                out.push(0);
+                code = 1;
+            }
+        }
+    }
+    out[code_index] = code;
+    out.push(0x00);
+    out
+}
+
+fn cobs_decode(frame: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(frame.len());
+    let mut index = 0;
+    while index < frame.len() {
+        let code = frame[index] as usize;
+        if code == 0 || index + code > frame.len() {
+            return None;
+        }
+        out.extend_from_slice(&frame[index + 1..index + code]);
+        index += code;
+        if code < 0xFF && index < frame.len() {
+            out.push(0);
+        }
+    }
+    Some(out)
+}
+
+fn slip_encode(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() + 2);
+    // leading END flushes any line noise accumulated on the receiver side
+    out.push(SLIP_END);
+    for &byte in data {
+        match byte {
+            SLIP_END => out.extend_from_slice(&[SLIP_ESC, SLIP_ESC_END]),
+            SLIP_ESC => out.extend_from_slice(&[SLIP_ESC, SLIP_ESC_ESC]),
+            _ => out.push(byte),
+        }
+    }
+    out.push(SLIP_END);
+    out
+}
+
+fn slip_decode(frame: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(frame.len());
+    let mut bytes = frame.iter();
+    while let Some(&byte) = bytes.next() {
+        if byte == SLIP_ESC {
+            match bytes.next() {
+                Some(&SLIP_ESC_END) => out.push(SLIP_END),
+                Some(&SLIP_ESC_ESC) => out.push(SLIP_ESC),
+                _ => return None,
+            }
+        } else {
+            out.push(byte);
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codec(mode: FramingMode, crc: bool) -> FrameCodec {
+        FrameCodec::new(FramingConfig { mode, crc })
+    }
+
+    #[test]
+    fn cobs_roundtrip() {
+        let payload = vec![0x11, 0x00, 0x00, 0x22, 0x00];
+        let mut codec = codec(FramingMode::Cobs, false);
+        let frame = codec.encode(&payload);
+        assert!(!frame[..frame.len() - 1].contains(&0));
+        assert_eq!(codec.decode(&frame), vec![payload]);
+    }
+
+    #[test]
+    fn cobs_long_run_without_zeros() {
+        let payload = (1..=255u8).cycle().take(600).collect::<Vec<_>>();
+        let mut codec = codec(FramingMode::Cobs, true);
+        let frame = codec.encode(&payload);
+        assert_eq!(codec.decode(&frame), vec![payload]);
+    }
+
+    #[test]
+    fn slip_roundtrip_with_escapes() {
+        let payload = vec![SLIP_END, 0x01, SLIP_ESC, SLIP_ESC_END];
+        let mut codec = codec(FramingMode::Slip, true);
+        let frame = codec.encode(&payload);
+        assert_eq!(codec.decode(&frame), vec![payload]);
+    }
+
+    #[test]
+    fn decode_across_partial_reads() {
+        let mut codec = codec(FramingMode::Slip, true);
+        let mut stream = codec.encode(&[1, 2, 3]);
+        stream.extend(codec.encode(&[4, 5]));
+        let mut payloads = vec![];
+        for chunk in stream.chunks(3) {
+            payloads.extend(codec.decode(chunk));
+        }
+        assert_eq!(payloads, vec![vec![1, 2, 3], vec![4, 5]]);
+    }
+
+    #[test]
+    fn resynchronizes_after_corruption() {
+        let mut codec = codec(FramingMode::Cobs, true);
+        let mut corrupted = codec.encode(&[1, 2, 3, 4]);
+        corrupted[2] ^= 0xFF;
+        let mut stream = vec![0x42, 0x13];
+        stream.extend(corrupted);
+        stream.extend(codec.encode(&[5, 6, 7]));
+        assert_eq!(codec.decode(&stream), vec![vec![5, 6, 7]]);
+    }
+
+    #[test]
+    fn no_framing_passes_through() {
+        let mut codec = codec(FramingMode::None, true);
+        assert_eq!(codec.encode(&[0, 1]), vec![0, 1]);
+        assert_eq!(codec.decode(&[0, 1]), vec![vec![0, 1]]);
+    }
+}
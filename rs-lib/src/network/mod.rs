@@ -1,2 +1,10 @@
+pub mod block_interceptors;
 pub mod com_hub;
+pub mod com_hub_events;
+pub mod com_hub_metadata;
+pub mod com_hub_statistics;
+pub mod com_hub_trace;
 pub mod com_interfaces;
+pub mod framing;
+#[cfg(feature = "wasm_webrtc")]
+pub mod webrtc_signaling;
@@ -0,0 +1,759 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use datex_core::global::dxb_block::DXBBlock;
+use datex_core::global::protocol_structures::block_header::{
+    BlockHeader, FlagsAndTimestamp,
+};
+use datex_core::network::com_interfaces::com_interface::ComInterfaceUUID;
+use datex_core::network::com_interfaces::default_com_interfaces::webrtc::webrtc_common::webrtc_trait::WebRTCTrait;
+use datex_core::crypto::crypto::CryptoTrait;
+use datex_core::runtime::Runtime;
+use datex_core::task::spawn_local;
+use datex_core::values::core_values::endpoint::Endpoint;
+use log::{error, info, warn};
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::{JsCast, JsError, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::js_sys::{Function, Promise, Uint8Array};
+
+use crate::crypto::crypto_js::CryptoJS;
+use crate::network::block_interceptors::BlockInterceptors;
+use crate::network::com_hub::{
+    JSComHub, create_interface_with_setup_data, setup_data_to_value_container,
+};
+use crate::network::com_interfaces::webrtc_js_interface::{
+    WebRTCJSInterface, WebRTCJSInterfaceSetupData,
+};
+
+/// Prefix of block bodies that contain WebRTC signaling messages
+const SIGNAL_MAGIC: &[u8; 4] = b"DXRS";
+
+/// Length of the Ed25519 signature of a signaling message
+const SIGNATURE_LENGTH: usize = 64;
+
+/// Maximum number of ICE candidates that are queued for an endpoint
+/// before its offer was received
+const MAX_PENDING_CANDIDATES: usize = 64;
+
+/// Signaling message exchanged between two endpoints over an
+/// existing DATEX route to negotiate a WebRTC connection.
+/// The payloads are the serialized offer, answer or ICE candidate
+/// as produced by the WebRTC interface.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WebRTCSignal {
+    Offer(Vec<u8>),
+    Answer(Vec<u8>),
+    IceCandidate(Vec<u8>),
+}
+
+impl WebRTCSignal {
+    fn signal_type(&self) -> u8 {
+        match self {
+            WebRTCSignal::Offer(_) => 0,
+            WebRTCSignal::Answer(_) => 1,
+            WebRTCSignal::IceCandidate(_) => 2,
+        }
+    }
+
+    fn payload(&self) -> &[u8] {
+        match self {
+            WebRTCSignal::Offer(payload)
+            | WebRTCSignal::Answer(payload)
+            | WebRTCSignal::IceCandidate(payload) => payload,
+        }
+    }
+
+    /// Serializes the signal together with the signature of the sender
+    pub fn to_bytes(&self, signature: &[u8; SIGNATURE_LENGTH]) -> Vec<u8> {
+        let payload = self.payload();
+        let mut bytes = Vec::with_capacity(
+            SIGNAL_MAGIC.len() + 1 + SIGNATURE_LENGTH + payload.len(),
+        );
+        bytes.extend_from_slice(SIGNAL_MAGIC);
+        bytes.push(self.signal_type());
+        bytes.extend_from_slice(signature);
+        bytes.extend_from_slice(payload);
+        bytes
+    }
+
+    /// Parses a block body into the signal and the signature of the sender,
+    /// returns None if the body is not a signaling message
+    pub fn from_bytes(
+        bytes: &[u8],
+    ) -> Option<(WebRTCSignal, [u8; SIGNATURE_LENGTH])> {
+        let bytes = bytes.strip_prefix(SIGNAL_MAGIC)?;
+        let (signal_type, bytes) = bytes.split_first()?;
+        let (signature, payload) = bytes.split_at_checked(SIGNATURE_LENGTH)?;
+        let payload = payload.to_vec();
+        let signal = match signal_type {
+            0 => WebRTCSignal::Offer(payload),
+            1 => WebRTCSignal::Answer(payload),
+            2 => WebRTCSignal::IceCandidate(payload),
+            _ => return None,
+        };
+        Some((signal, signature.try_into().ok()?))
+    }
+
+    /// Returns the data that is signed by the sender. The sender and
+    /// receiver are part of the signed data, so that a signal can not be
+    /// replayed for another endpoint
+    pub fn get_signed_data(
+        &self,
+        sender: &Endpoint,
+        receiver: &Endpoint,
+    ) -> Vec<u8> {
+        [
+            SIGNAL_MAGIC.as_slice(),
+            &[self.signal_type()],
+            sender.to_string().as_bytes(),
+            &[0],
+            receiver.to_string().as_bytes(),
+            &[0],
+            self.payload(),
+        ]
+        .concat()
+    }
+}
+
+#[derive(Debug)]
+struct SignalingPeer {
+    /// None while the interface for an incoming offer is being created
+    interface_uuid: Option<ComInterfaceUUID>,
+    /// Whether the interface was created for an incoming offer
+    is_incoming: bool,
+    /// ICE candidates can only be added after the remote description
+    /// was set, candidates received earlier are queued
+    has_remote_description: bool,
+    pending_candidates: Vec<Vec<u8>>,
+}
+
+/// Keys used to sign outgoing and verify incoming signaling messages
+struct SignalingKeys {
+    /// PKCS#8 encoded Ed25519 key of the local endpoint
+    private_key: Vec<u8>,
+    /// Resolves the SPKI encoded Ed25519 key of an endpoint
+    resolve_key: Function,
+}
+
+/// Negotiates WebRTC connections in-band: offers, answers and ICE candidates
+/// are sent as DATEX blocks to the peer endpoint over whatever route the
+/// ComHub already has (e.g. a websocket relay), so no external signaling
+/// server is required. Each signal is signed with the key of the sender
+/// and only handled if the signature matches the key of the sender endpoint.
+#[derive(Clone)]
+pub struct WebRTCSignaling {
+    runtime: Runtime,
+    interceptors: BlockInterceptors,
+    inner: Rc<WebRTCSignalingInner>,
+}
+
+#[derive(Default)]
+struct WebRTCSignalingInner {
+    is_enabled: Cell<bool>,
+    accept_incoming: Cell<bool>,
+    keys: RefCell<Option<Rc<SignalingKeys>>>,
+    peers: RefCell<HashMap<Endpoint, SignalingPeer>>,
+    /// ICE candidates of endpoints that have no peer yet, since the
+    /// candidates can arrive before the offer
+    pending_candidates: RefCell<HashMap<Endpoint, Vec<Vec<u8>>>>,
+}
+
+impl WebRTCSignaling {
+    pub fn new(
+        runtime: Runtime,
+        interceptors: BlockInterceptors,
+    ) -> WebRTCSignaling {
+        WebRTCSignaling {
+            runtime,
+            interceptors,
+            inner: Rc::new(WebRTCSignalingInner::default()),
+        }
+    }
+
+    /// Starts listening for incoming signaling messages.
+    /// Outgoing signals are signed with the private key, the public keys
+    /// of other endpoints are resolved with resolve_key.
+    /// If accept_incoming is true, offers from endpoints without an
+    /// existing WebRTC interface create a new interface automatically.
+    pub fn enable(
+        &self,
+        accept_incoming: bool,
+        private_key: Vec<u8>,
+        resolve_key: Function,
+    ) {
+        self.inner.accept_incoming.set(accept_incoming);
+        self.inner.keys.replace(Some(Rc::new(SignalingKeys {
+            private_key,
+            resolve_key,
+        })));
+        if self.inner.is_enabled.replace(true) {
+            return;
+        }
+        let signaling = self.clone();
+        self.interceptors
+            .add_block_handler(move |block| signaling.handle_block(block));
+    }
+
+    /// Handles the block if it is a signaling message for the local
+    /// endpoint. Returns false if the block is processed by the ComHub,
+    /// e.g. to forward signaling messages for other endpoints.
+    /// The signal is only acted on after its signature was verified
+    fn handle_block(&self, block: &DXBBlock) -> bool {
+        let Some((signal, signature)) = WebRTCSignal::from_bytes(&block.body)
+        else {
+            return false;
+        };
+        let own_endpoint = self.runtime.endpoint();
+        if !block.receiver_endpoints().contains(&own_endpoint) {
+            return false;
+        }
+        let sender = block.routing_header.sender.clone();
+        let signaling = self.clone();
+        spawn_local(async move {
+            let data = signal.get_signed_data(&sender, &own_endpoint);
+            if let Err(e) =
+                signaling.verify_signature(&sender, &signature, &data).await
+            {
+                warn!("Ignoring WebRTC signal from {sender}: {e}");
+                return;
+            }
+            if let Err(e) = signaling.handle_signal(&sender, signal).await {
+                error!("Failed to handle WebRTC signal from {sender}: {e}");
+                signaling.remove_peer(&sender).await;
+            }
+        });
+        true
+    }
+
+    /// Creates a WebRTC interface for the given endpoint and connects to it
+    pub async fn connect_to_endpoint(
+        &self,
+        endpoint: Endpoint,
+    ) -> Result<ComInterfaceUUID, String> {
+        let interface_uuid = self.create_interface(&endpoint).await?;
+        self.connect(interface_uuid.clone()).await?;
+        Ok(interface_uuid)
+    }
+
+    /// Negotiates the connection for an existing WebRTC interface by sending
+    /// an offer to its remote endpoint and waits until the connection is
+    /// established
+    pub async fn connect(
+        &self,
+        interface_uuid: ComInterfaceUUID,
+    ) -> Result<(), String> {
+        if !self.inner.is_enabled.get() {
+            return Err("In-band signaling is not enabled".to_string());
+        }
+        let interface = self.get_interface(&interface_uuid)?;
+        let remote_endpoint = interface.borrow().remote_endpoint().clone();
+        self.register_peer(&remote_endpoint, interface_uuid, true, false);
+        let result = async {
+            self.send_offer(&remote_endpoint).await?;
+            interface
+                .borrow()
+                .wait_for_connection()
+                .await
+                .map_err(|e| format!("{e:?}"))
+        }
+        .await;
+        if result.is_err() {
+            self.remove_peer(&remote_endpoint).await;
+        }
+        result
+    }
+
+    /// Removes the peer that uses the interface, called when the
+    /// interface is closed
+    pub fn remove_interface(&self, interface_uuid: &ComInterfaceUUID) {
+        let mut peers = self.inner.peers.borrow_mut();
+        let Some(endpoint) = peers
+            .iter()
+            .find(|(_, peer)| {
+                peer.interface_uuid.as_ref() == Some(interface_uuid)
+            })
+            .map(|(endpoint, _)| endpoint.clone())
+        else {
+            return;
+        };
+        peers.remove(&endpoint);
+        self.inner.pending_candidates.borrow_mut().remove(&endpoint);
+    }
+
+    /// Removes the peer and its queued candidates after a failed
+    /// negotiation, so that the endpoint can connect again. The interface
+    /// is closed if it was created for an incoming offer
+    async fn remove_peer(&self, endpoint: &Endpoint) {
+        self.inner.pending_candidates.borrow_mut().remove(endpoint);
+        let Some(peer) = self.inner.peers.borrow_mut().remove(endpoint) else {
+            return;
+        };
+        if !peer.is_incoming {
+            return;
+        }
+        let Some(interface_uuid) = peer.interface_uuid else {
+            return;
+        };
+        let com_hub = self.runtime.com_hub();
+        if com_hub.has_interface(&interface_uuid)
+            && let Err(e) = com_hub.remove_interface(interface_uuid).await
+        {
+            error!("Failed to close WebRTC interface for {endpoint}: {e:?}");
+        }
+    }
+
+    /// Creates a new offer and sends it to the given endpoint, used for the
+    /// initial connection as well as for renegotiation after an ICE restart
+    async fn send_offer(&self, endpoint: &Endpoint) -> Result<(), String> {
+        let interface_uuid = {
+            let mut peers = self.inner.peers.borrow_mut();
+            let peer = peers
+                .get_mut(endpoint)
+                .ok_or_else(|| format!("No WebRTC peer for {endpoint}"))?;
+            peer.has_remote_description = false;
+            peer.interface_uuid
+                .clone()
+                .ok_or_else(|| format!("No WebRTC interface for {endpoint}"))?
+        };
+        let offer = self
+            .get_interface(&interface_uuid)?
+            .borrow()
+            .create_offer()
+            .await
+            .map_err(|e| format!("{e:?}"))?;
+        info!("Sending WebRTC offer to {endpoint}");
+        self.send_signal(endpoint, WebRTCSignal::Offer(offer)).await;
+        Ok(())
+    }
+
+    fn register_peer(
+        &self,
+        endpoint: &Endpoint,
+        interface_uuid: ComInterfaceUUID,
+        is_offerer: bool,
+        is_incoming: bool,
+    ) {
+        let Ok(interface) = self.get_interface(&interface_uuid) else {
+            return;
+        };
+        let signaling = self.clone();
+        let receiver = endpoint.clone();
+        interface
+            .borrow()
+            .set_on_ice_candidate(Box::new(move |candidate| {
+                let signaling = signaling.clone();
+                let receiver = receiver.clone();
+                spawn_local(async move {
+                    signaling
+                        .send_signal(
+                            &receiver,
+                            WebRTCSignal::IceCandidate(candidate),
+                        )
+                        .await;
+                });
+            }));
+        // only the peer that sent the initial offer renegotiates after an
+        // ICE restart, so both peers do not send offers at the same time
+        if is_offerer {
+            let signaling = self.clone();
+            let receiver = endpoint.clone();
+            interface
+                .borrow()
+                .set_on_negotiation_needed(Box::new(move || {
+                    let signaling = signaling.clone();
+                    let receiver = receiver.clone();
+                    spawn_local(async move {
+                        if let Err(e) = signaling.send_offer(&receiver).await {
+                            error!(
+                                "Failed to renegotiate with {receiver}: {e}"
+                            );
+                        }
+                    });
+                }));
+        }
+        let mut pending_candidates = self
+            .inner
+            .pending_candidates
+            .borrow_mut()
+            .remove(endpoint)
+            .unwrap_or_default();
+        let mut peers = self.inner.peers.borrow_mut();
+        // keep the candidates that were queued on the placeholder peer
+        if let Some(peer) = peers.remove(endpoint) {
+            pending_candidates.extend(peer.pending_candidates);
+        }
+        peers.insert(
+            endpoint.clone(),
+            SignalingPeer {
+                interface_uuid: Some(interface_uuid),
+                is_incoming,
+                has_remote_description: false,
+                pending_candidates,
+            },
+        );
+    }
+
+    /// Returns the peer state of the endpoint. A peer whose interface
+    /// no longer exists is removed, e.g. after the interface was
+    /// closed by the ComHub
+    fn get_peer_interface(
+        &self,
+        endpoint: &Endpoint,
+    ) -> Option<Option<ComInterfaceUUID>> {
+        let mut peers = self.inner.peers.borrow_mut();
+        let interface_uuid = peers.get(endpoint)?.interface_uuid.clone();
+        if let Some(uuid) = &interface_uuid
+            && self.get_interface(uuid).is_err()
+        {
+            peers.remove(endpoint);
+            return None;
+        }
+        Some(interface_uuid)
+    }
+
+    async fn handle_signal(
+        &self,
+        sender: &Endpoint,
+        signal: WebRTCSignal,
+    ) -> Result<(), String> {
+        let peer_interface = self.get_peer_interface(sender);
+
+        match signal {
+            WebRTCSignal::Offer(offer) => {
+                let interface_uuid = match peer_interface {
+                    Some(Some(interface_uuid)) => interface_uuid,
+                    Some(None) => {
+                        warn!(
+                            "Ignoring WebRTC offer from {sender}, \
+                             a connection is already being created"
+                        );
+                        return Ok(());
+                    }
+                    None if self.inner.accept_incoming.get() => {
+                        info!("Accepting WebRTC connection from {sender}");
+                        // the placeholder prevents that concurrent offers
+                        // create multiple interfaces
+                        self.inner.peers.borrow_mut().insert(
+                            sender.clone(),
+                            SignalingPeer {
+                                interface_uuid: None,
+                                is_incoming: true,
+                                has_remote_description: false,
+                                pending_candidates: Vec::new(),
+                            },
+                        );
+                        let interface_uuid =
+                            self.create_interface(sender).await?;
+                        self.register_peer(
+                            sender,
+                            interface_uuid.clone(),
+                            false,
+                            true,
+                        );
+                        interface_uuid
+                    }
+                    None => {
+                        warn!("Ignoring WebRTC offer from {sender}");
+                        return Ok(());
+                    }
+                };
+                let interface = self.get_interface(&interface_uuid)?;
+                let answer = interface
+                    .borrow()
+                    .create_answer(offer)
+                    .await
+                    .map_err(|e| format!("{e:?}"))?;
+                self.send_signal(sender, WebRTCSignal::Answer(answer)).await;
+                self.flush_pending_candidates(sender).await
+            }
+            WebRTCSignal::Answer(answer) => {
+                let Some(Some(interface_uuid)) = peer_interface else {
+                    warn!("Ignoring WebRTC answer from unknown peer {sender}");
+                    return Ok(());
+                };
+                self.get_interface(&interface_uuid)?
+                    .borrow()
+                    .set_answer(answer)
+                    .await
+                    .map_err(|e| format!("{e:?}"))?;
+                self.flush_pending_candidates(sender).await
+            }
+            WebRTCSignal::IceCandidate(candidate) => {
+                if peer_interface.is_none() {
+                    // the interface is created when the offer is received
+                    if self.inner.accept_incoming.get() {
+                        let mut pending =
+                            self.inner.pending_candidates.borrow_mut();
+                        let candidates =
+                            pending.entry(sender.clone()).or_default();
+                        if candidates.len() < MAX_PENDING_CANDIDATES {
+                            candidates.push(candidate);
+                        }
+                    }
+                    return Ok(());
+                }
+                let interface_uuid = {
+                    let mut peers = self.inner.peers.borrow_mut();
+                    let peer = peers.get_mut(sender).unwrap();
+                    match &peer.interface_uuid {
+                        Some(interface_uuid) if peer.has_remote_description => {
+                            interface_uuid.clone()
+                        }
+                        _ => {
+                            if peer.pending_candidates.len()
+                                < MAX_PENDING_CANDIDATES
+                            {
+                                peer.pending_candidates.push(candidate);
+                            }
+                            return Ok(());
+                        }
+                    }
+                };
+                self.get_interface(&interface_uuid)?
+                    .borrow()
+                    .add_ice_candidate(candidate)
+                    .await
+                    .map_err(|e| format!("{e:?}"))
+            }
+        }
+    }
+
+    async fn flush_pending_candidates(
+        &self,
+        endpoint: &Endpoint,
+    ) -> Result<(), String> {
+        let (interface_uuid, candidates) = {
+            let mut peers = self.inner.peers.borrow_mut();
+            let Some(peer) = peers.get_mut(endpoint) else {
+                return Ok(());
+            };
+            let Some(interface_uuid) = peer.interface_uuid.clone() else {
+                return Ok(());
+            };
+            peer.has_remote_description = true;
+            (interface_uuid, std::mem::take(&mut peer.pending_candidates))
+        };
+        let interface = self.get_interface(&interface_uuid)?;
+        for candidate in candidates {
+            interface
+                .borrow()
+                .add_ice_candidate(candidate)
+                .await
+                .map_err(|e| format!("{e:?}"))?;
+        }
+        Ok(())
+    }
+
+    async fn create_interface(
+        &self,
+        endpoint: &Endpoint,
+    ) -> Result<ComInterfaceUUID, String> {
+        let setup_data =
+            setup_data_to_value_container(&WebRTCJSInterfaceSetupData {
+                peer_endpoint: endpoint.to_string(),
+                ice_servers: None,
+                data_channel: Default::default(),
+                ice_restart: None,
+                stats_interval: None,
+            })?;
+        create_interface_with_setup_data(
+            &self.runtime,
+            "webrtc",
+            setup_data,
+            None,
+        )
+        .await
+    }
+
+    fn get_interface(
+        &self,
+        interface_uuid: &ComInterfaceUUID,
+    ) -> Result<Rc<RefCell<WebRTCJSInterface>>, String> {
+        self.runtime
+            .com_hub()
+            .get_interface_by_uuid::<WebRTCJSInterface>(interface_uuid)
+            .ok_or_else(|| "WebRTC interface not found".to_string())
+    }
+
+    fn get_keys(&self) -> Result<Rc<SignalingKeys>, String> {
+        self.inner
+            .keys
+            .borrow()
+            .clone()
+            .ok_or_else(|| "In-band signaling is not enabled".to_string())
+    }
+
+    /// Checks the signature of a signal with the public key of the sender
+    async fn verify_signature(
+        &self,
+        sender: &Endpoint,
+        signature: &[u8],
+        data: &[u8],
+    ) -> Result<(), String> {
+        let public_key = self
+            .resolve_public_key(sender)
+            .await?
+            .ok_or_else(|| format!("Unknown endpoint {sender}"))?;
+        let is_valid = CryptoJS
+            .ver_ed25519(&public_key, signature, data)
+            .await
+            .map_err(|_| format!("Invalid public key for {sender}"))?;
+        if is_valid {
+            Ok(())
+        } else {
+            Err("Invalid signature".to_string())
+        }
+    }
+
+    /// Calls the key resolver, which may return the public key
+    /// directly or as promise
+    async fn resolve_public_key(
+        &self,
+        endpoint: &Endpoint,
+    ) -> Result<Option<Vec<u8>>, String> {
+        let resolve_key = self.get_keys()?.resolve_key.clone();
+        let key = resolve_key
+            .call1(&JsValue::NULL, &JsValue::from_str(&endpoint.to_string()))
+            .map_err(|e| format!("Error in key resolver: {e:?}"))?;
+        let key = JsFuture::from(Promise::resolve(&key))
+            .await
+            .map_err(|e| format!("Error in key resolver: {e:?}"))?;
+        if key.is_undefined() || key.is_null() {
+            return Ok(None);
+        }
+        key.dyn_into::<Uint8Array>()
+            .map(|key| Some(key.to_vec()))
+            .map_err(|_| "Key resolver must return a Uint8Array".to_string())
+    }
+
+    async fn send_signal(&self, receiver: &Endpoint, signal: WebRTCSignal) {
+        let keys = match self.get_keys() {
+            Ok(keys) => keys,
+            Err(e) => {
+                error!("Failed to send WebRTC signal to {receiver}: {e}");
+                return;
+            }
+        };
+        let data = signal.get_signed_data(&self.runtime.endpoint(), receiver);
+        let signature =
+            match CryptoJS.sig_ed25519(&keys.private_key, &data).await {
+                Ok(signature) => signature,
+                Err(e) => {
+                    error!("Failed to sign WebRTC signal to {receiver}: {e:?}");
+                    return;
+                }
+            };
+        let mut block = DXBBlock {
+            block_header: BlockHeader {
+                flags_and_timestamp: FlagsAndTimestamp::default()
+                    .with_is_end_of_context(true)
+                    .with_is_end_of_section(true),
+                ..BlockHeader::default()
+            },
+            body: signal.to_bytes(&signature),
+            ..DXBBlock::default()
+        };
+        block.recalculate_struct();
+        block.set_receivers(vec![receiver.clone()]);
+        if self.runtime.com_hub().send_own_block(block).is_err() {
+            error!("Failed to send WebRTC signal to {receiver}");
+        }
+    }
+}
+
+/**
+ * In-band signaling methods exposed to JavaScript
+ */
+#[wasm_bindgen]
+impl JSComHub {
+    /// Enables handling of WebRTC signaling messages received over existing
+    /// DATEX connections. Outgoing signals are signed with the PKCS#8
+    /// encoded Ed25519 private key of the own endpoint, incoming signals are
+    /// verified with the SPKI encoded key that resolve_key returns (or a
+    /// promise of it) for the sender endpoint.
+    /// If accept_incoming is true, incoming offers from endpoints without
+    /// a WebRTC interface are accepted automatically.
+    pub fn webrtc_enable_in_band_signaling(
+        &self,
+        accept_incoming: bool,
+        private_key: Vec<u8>,
+        resolve_key: Function,
+    ) {
+        self.webrtc_signaling
+            .enable(accept_incoming, private_key, resolve_key);
+    }
+
+    /// Connects an existing WebRTC interface to its remote endpoint,
+    /// exchanging offer, answer and ICE candidates over the ComHub
+    pub async fn webrtc_interface_connect(
+        &self,
+        interface_uuid: String,
+    ) -> Result<(), JsError> {
+        self.webrtc_signaling
+            .connect(ComInterfaceUUID::from_string(interface_uuid))
+            .await
+            .map_err(|e| JsError::new(&e))
+    }
+
+    /// Creates a WebRTC interface for the given endpoint and connects to it
+    /// using in-band signaling. Returns the UUID of the created interface.
+    pub async fn webrtc_connect(
+        &self,
+        endpoint: String,
+    ) -> Result<String, JsError> {
+        let endpoint = Endpoint::from_str(&endpoint)
+            .map_err(|e| JsError::new(&format!("Invalid endpoint: {e:?}")))?;
+        let interface_uuid = self
+            .webrtc_signaling
+            .connect_to_endpoint(endpoint)
+            .await
+            .map_err(|e| JsError::new(&e))?;
+        Ok(interface_uuid.0.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signal_roundtrip() {
+        let signature = [0xAB; SIGNATURE_LENGTH];
+        for signal in [
+            WebRTCSignal::Offer(vec![1, 2, 3]),
+            WebRTCSignal::Answer(vec![]),
+            WebRTCSignal::IceCandidate(vec![0xFF; 10]),
+        ] {
+            assert_eq!(
+                WebRTCSignal::from_bytes(&signal.to_bytes(&signature)),
+                Some((signal, signature))
+            );
+        }
+    }
+
+    #[test]
+    fn ignores_other_blocks() {
+        assert_eq!(WebRTCSignal::from_bytes(&[0x01, 0x02]), None);
+        assert_eq!(WebRTCSignal::from_bytes(b"DXRS\x07"), None);
+        // the signature is missing
+        assert_eq!(WebRTCSignal::from_bytes(b"DXRS\x00\x01\x02"), None);
+    }
+
+    #[test]
+    fn signed_data_contains_endpoints() {
+        let signal = WebRTCSignal::Offer(vec![1, 2, 3]);
+        let alice = Endpoint::from_str("@alice").unwrap();
+        let bob = Endpoint::from_str("@bob").unwrap();
+        let carol = Endpoint::from_str("@carol").unwrap();
+        let data = signal.get_signed_data(&alice, &bob);
+        assert_ne!(data, signal.get_signed_data(&alice, &carol));
+        assert_ne!(data, signal.get_signed_data(&bob, &alice));
+        assert_ne!(
+            data,
+            WebRTCSignal::Answer(vec![1, 2, 3]).get_signed_data(&alice, &bob)
+        );
+    }
+}
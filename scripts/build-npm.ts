@@ -27,6 +27,10 @@ await build({
             name: "./interface-impls/websocket-client",
             path: "./src/network/interface-impls/websocket-client.ts",
         },
+        {
+            name: "./interface-impls/http-client",
+            path: "./src/network/interface-impls/http-client.ts",
+        },
     ],
     outDir: "./npm",
     shims: { deno: false },
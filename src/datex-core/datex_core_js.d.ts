@@ -17,6 +17,207 @@ export interface WebRTCInterfaceSetupData {
     ice_servers: RTCIceServer[] | undefined;
 }
 
+/**
+ * Setup data for an interface that connects all runtimes in the same
+ * origin (e.g. browser tabs or workers) that use the same channel name.
+ */
+export interface BroadcastChannelInterfaceSetupData {
+    /**
+     * Name of the BroadcastChannel (default: \"datex\")
+     */
+    name: string | undefined;
+}
+
+/**
+ * Setup data for an interface that connects to another runtime over a
+ * MessagePort, a Worker or the global scope of a worker.
+ * The port itself is passed to the interface after it was created.
+ */
+export interface MessagePortInterfaceSetupData {
+    /**
+     * Name of the interface, e.g. the name of the worker
+     */
+    name: string | undefined;
+}
+
+/**
+ * Setup data for an in-memory interface that is connected to a loopback
+ * interface of another runtime in the same wasm instance.
+ * The link conditions apply to all blocks sent by this interface, so
+ * both directions of a connection can be configured independently.
+ */
+export interface LoopbackInterfaceSetupData {
+    /**
+     * Name of the interface
+     */
+    name?: string;
+    /**
+     * Delay of each block in milliseconds (default: 0)
+     */
+    latency?: number;
+    /**
+     * Maximum random deviation of the latency in milliseconds (default: 0)
+     */
+    jitter?: number;
+    /**
+     * Probability that a block is dropped, between 0 and 1 (default: 0)
+     */
+    packet_loss?: number;
+    /**
+     * Maximum bandwidth in bytes per second, unlimited if not set
+     */
+    bandwidth?: number;
+    /**
+     * Seed for the random jitter and packet loss. Links with the same
+     * seed and conditions drop and delay the same blocks.
+     */
+    seed?: number;
+}
+
+/**
+ * Setup data for an interface that sends blocks via HTTP POST requests
+ * and receives blocks via long-polling or a streaming response.
+ * Used as fallback if WebSockets are blocked, e.g. by a proxy.
+ */
+export interface HttpClientInterfaceSetupData {
+    /**
+     * URL of the HTTP server interface, e.g. \"https://example.com/datex\"
+     */
+    address: string;
+    /**
+     * If true, blocks are received over a single chunked response instead
+     * of repeated long-polling requests (default: false). Some proxies
+     * buffer chunked responses, so long-polling is the safer default.
+     */
+    streaming?: boolean;
+}
+
+/**
+ * Setup data for the HTTP server interface that answers the requests of
+ * HTTP client interfaces. The requests are passed to the interface by
+ * the request handler of the JS implementation.
+ */
+export interface HttpServerJSInterfaceSetupData {
+    /**
+     * Port on which the JS implementation serves the interface.
+     * If not set, requests must be passed to the request handler.
+     */
+    port?: number;
+    /**
+     * Time in milliseconds after which a poll request is answered
+     * without blocks (default: 25000)
+     */
+    poll_timeout?: number;
+}
+
+/**
+ * Setup data for the WebTransport client interface
+ */
+export interface WebTransportClientInterfaceSetupData {
+    /**
+     * URL of the WebTransport server, e.g. \"https://example.com:4433/datex\"
+     */
+    address: string;
+    /**
+     * If true, blocks that fit into a single datagram are sent as
+     * unreliable datagrams, larger blocks are still sent over streams
+     * (default: false)
+     */
+    datagrams?: boolean;
+    /**
+     * SHA-256 hashes of self-signed server certificates that are
+     * accepted, e.g. for a local development server
+     */
+    server_certificate_hashes?: number[][];
+}
+
+/**
+ * Setup data for a WebRTC interface that uses a matchbox signaling
+ * server to connect to all peers in a room.
+ */
+export interface MatchboxJSInterfaceSetupData {
+    /**
+     * URL of the matchbox signaling server, e.g. \"wss://example.com\"
+     */
+    address: string;
+    /**
+     * Name of the room to join, appended to the address as path.
+     * If not set, the address is expected to contain the room.
+     */
+    room: string | undefined;
+    /**
+     * If true, blocks are sent over an unordered channel without
+     * retransmissions (default: false)
+     */
+    unreliable?: boolean;
+    /**
+     * ICE server used to establish connections to the peers
+     */
+    ice_server: RTCIceServer | undefined;
+}
+
+/**
+ * Setup data for the WebRTC interface in the browser.
+ * Superset of the common WebRTC setup data with options that are
+ * only available for the JS implementation.
+ */
+export interface WebRTCJSInterfaceSetupData {
+    peer_endpoint: string;
+    ice_servers: RTCIceServer[] | undefined;
+    /**
+     * Reliability settings of the DATEX data channel
+     * (default: ordered and reliable)
+     */
+    data_channel?: DataChannelConfig;
+    /**
+     * If true, an ICE restart is triggered when the ICE connection
+     * fails, e.g. after a network change (default: true)
+     */
+    ice_restart: boolean | undefined;
+    /**
+     * Interval in milliseconds in which connection statistics are
+     * collected, 0 disables statistics (default: 5000)
+     */
+    stats_interval: number | undefined;
+}
+
+/**
+ * Statistics of the candidate pair that is currently used
+ * for the peer connection
+ */
+export interface WebRTCStats {
+    /**
+     * Round trip time in milliseconds
+     */
+    round_trip_time: number | undefined;
+    /**
+     * Estimated available outgoing bandwidth in bits per second
+     */
+    available_outgoing_bitrate: number | undefined;
+    bytes_sent: number;
+    bytes_received: number;
+}
+
+/**
+ * Reliability settings of a WebRTC data channel.
+ * If neither `max_retransmits` nor `max_packet_life_time` is set,
+ * the channel is reliable. Both options are mutually exclusive.
+ */
+export interface DataChannelConfig {
+    /**
+     * If false, messages may arrive out of order (default: true)
+     */
+    ordered: boolean | undefined;
+    /**
+     * Maximum number of retransmissions of a message
+     */
+    max_retransmits: number | undefined;
+    /**
+     * Maximum time in milliseconds a message is retransmitted
+     */
+    max_packet_life_time: number | undefined;
+}
+
 export type BaseInterfaceSetupData = InterfaceProperties;
 
 export type IndentType = "Spaces" | "Tabs";
@@ -47,6 +248,76 @@ export interface SerialInterfaceSetupData {
     baud_rate: number;
 }
 
+/**
+ * Framing mode used to delimit blocks on byte-stream interfaces
+ * (e.g. serial) that do not preserve message boundaries.
+ */
+export type FramingMode = "None" | "Cobs" | "Slip";
+
+export interface FramingConfig {
+    mode: FramingMode;
+    /**
+     * If true, a CRC-16/CCITT-FALSE checksum is appended to each frame
+     * and frames with an invalid checksum are dropped.
+     * Ignored if the framing mode is `None`.
+     */
+    crc?: boolean;
+}
+
+/**
+ * Setup data for the Web Serial interface.
+ * Superset of the common serial setup data with options that are
+ * only available for the JS implementation.
+ */
+export interface SerialJSInterfaceSetupData {
+    port_name: string | undefined;
+    baud_rate: number;
+    /**
+     * Framing used to delimit blocks on the serial line (default: none)
+     */
+    framing?: FramingConfig;
+    /**
+     * USB filters for the port selection. If a previously granted port
+     * matches one of the filters, it is reused without user interaction
+     * when `reuse_granted_port` is enabled.
+     */
+    filters?: SerialPortFilterDX[];
+    /**
+     * If true, an already granted port (`navigator.serial.getPorts()`) is
+     * opened instead of prompting the user (default: false)
+     */
+    reuse_granted_port?: boolean;
+    /**
+     * Number of data bits per frame, 7 or 8 (default: 8)
+     */
+    data_bits: number | undefined;
+    /**
+     * Number of stop bits, 1 or 2 (default: 1)
+     */
+    stop_bits: number | undefined;
+    /**
+     * Parity mode (default: none)
+     */
+    parity: SerialParity | undefined;
+    /**
+     * Size of the read and write buffers in bytes (default: 255)
+     */
+    buffer_size: number | undefined;
+    /**
+     * Flow control mode (default: none)
+     */
+    flow_control: SerialFlowControl | undefined;
+}
+
+export interface SerialPortFilterDX {
+    usb_vendor_id: number;
+    usb_product_id: number | undefined;
+}
+
+export type SerialParity = "None" | "Even" | "Odd";
+
+export type SerialFlowControl = "None" | "Hardware";
+
 export interface WebSocketServerInterfaceSetupData {
     port: number;
     /**
@@ -55,16 +326,333 @@ export interface WebSocketServerInterfaceSetupData {
     secure: boolean | undefined;
 }
 
+/**
+ * Setup data for the WebSocket server interface in a JS runtime.
+ * Superset of the common WebSocket server setup data with options that
+ * are only available for the JS implementation.
+ */
+export interface WebSocketServerJSInterfaceSetupData {
+    port: number;
+    /**
+     * if true, the server will use wss (secure WebSocket). Defaults to true.
+     */
+    secure: boolean | undefined;
+    /**
+     * If true, connecting endpoints have to sign a challenge with their
+     * endpoint key before their blocks are accepted (default: false).
+     * The public keys are resolved by the key resolver of the interface.
+     */
+    handshake?: boolean;
+    /**
+     * Time in milliseconds in which a client has to answer the
+     * handshake challenge (default: 10000)
+     */
+    handshake_timeout?: number;
+    /**
+     * Address under which the clients connect to the server, e.g.
+     * \"wss://example.com\". Handshakes that were signed for another server
+     * are rejected (default: \"wss://localhost:{port}\", or \"ws://\" if
+     * secure is false)
+     */
+    handshake_origin?: string;
+}
+
 export interface WebSocketClientInterfaceSetupData {
     address: string;
 }
 
+/**
+ * Reconnection behaviour after an established connection was lost.
+ * The delay before each attempt grows exponentially up to `max_delay`
+ * and is randomized by `jitter`, so that clients do not all reconnect
+ * at the same time after a server restart.
+ */
+export interface WebSocketReconnectConfig {
+    /**
+     * If false, the connection is not reestablished (default: true)
+     */
+    enabled?: boolean;
+    /**
+     * Delay before the first attempt in milliseconds (default: 500)
+     */
+    initial_delay?: number;
+    /**
+     * Maximum delay between two attempts in milliseconds (default: 30000)
+     */
+    max_delay?: number;
+    /**
+     * Factor by which the delay grows after each attempt (default: 2)
+     */
+    multiplier?: number;
+    /**
+     * Maximum random deviation of the delay as a fraction of the
+     * delay, between 0 and 1 (default: 0.2)
+     */
+    jitter?: number;
+    /**
+     * Maximum number of attempts, unlimited if not set
+     */
+    max_attempts?: number;
+}
+
+/**
+ * Setup data for the WebSocket client interface in the browser.
+ * Superset of the common WebSocket client setup data with options that
+ * are only available for the JS implementation.
+ */
+export interface WebSocketClientJSInterfaceSetupData {
+    address: string;
+    /**
+     * Reconnection behaviour after the connection was lost
+     * (default: reconnect with exponential backoff)
+     */
+    reconnect?: WebSocketReconnectConfig;
+    /**
+     * Subprotocols offered to the server, the selected protocol
+     * is available after the connection was opened
+     */
+    protocols?: string[];
+    /**
+     * Token that is sent to authenticate the client
+     */
+    auth?: WebSocketAuth;
+    /**
+     * Credentials for servers that require the endpoint to sign a
+     * challenge before blocks are accepted
+     */
+    handshake?: WebSocketHandshakeCredentials;
+}
+
+/**
+ * Credentials with which the client answers the challenge of a
+ * WebSocket server with enabled handshake
+ */
+export interface WebSocketHandshakeCredentials {
+    /**
+     * Endpoint of the client, must be the endpoint of the runtime
+     */
+    endpoint: string;
+    /**
+     * PKCS#8 encoded Ed25519 private key of the endpoint
+     */
+    private_key: number[];
+}
+
+/**
+ * Browsers do not allow custom headers for WebSocket connections,
+ * so the token is sent either as query parameter or as subprotocol
+ */
+export type WebSocketAuthMethod = "Query" | "Protocol";
+
+export interface WebSocketAuth {
+    /**
+     * Bearer token, e.g. a JWT
+     */
+    token: string;
+    method?: WebSocketAuthMethod;
+}
+
+/**
+ * Close code, reason and whether the connection was closed cleanly,
+ * as reported by the last close event of the WebSocket.
+ * Connections that could not be established (e.g. because the handshake
+ * was rejected) are reported by browsers with code 1006 and no reason.
+ */
+export interface WebSocketCloseInfo {
+    code: number;
+    reason: string;
+    was_clean: boolean;
+}
+
+/**
+ * A client connected to the WebSocket server interface
+ */
+export interface WebSocketServerSocketInfo {
+    socket_uuid: string;
+    /**
+     * Endpoint of the client, if already known from the handshake
+     * or a received block
+     */
+    endpoint: string | undefined;
+}
+
 export interface RTCIceServer {
     urls: string[];
     username: string | undefined;
     credential: string | undefined;
 }
 
+/**
+ * Socket of an interface in the ComHub metadata
+ */
+export interface JSSocketMetadata {
+    uuid: string;
+    direction: InterfaceDirection;
+    /**
+     * Endpoint that is connected via the socket, if already known
+     */
+    endpoint: string | undefined;
+}
+
+/**
+ * Interface in the ComHub metadata
+ */
+export interface JSInterfaceMetadata {
+    uuid: string;
+    /**
+     * State of the interface, e.g. \"Connected\". Not available while
+     * the interface is busy sending a block
+     */
+    state: string | undefined;
+    /**
+     * Priority for fallback routing that was passed to create_interface
+     */
+    priority: number | undefined;
+    properties: InterfaceProperties;
+    sockets: JSSocketMetadata[];
+}
+
+/**
+ * Interfaces and sockets of the ComHub
+ */
+export interface JSComHubMetadata {
+    /**
+     * Endpoint of the runtime
+     */
+    endpoint: string;
+    interfaces: JSInterfaceMetadata[];
+}
+
+/**
+ * Traffic of an interface or socket since it was created
+ */
+export interface JSTrafficStatistics {
+    blocks_sent: number;
+    bytes_sent: number;
+    blocks_received: number;
+    bytes_received: number;
+    /**
+     * Blocks that the interface failed to send
+     */
+    failed_sends: number;
+    /**
+     * Received bytes that were not yet processed by the ComHub, not set
+     * if the receive queues could not be read because the interface
+     * was busy
+     */
+    queue_depth: number | undefined;
+    sent_blocks_per_second: number;
+    sent_bytes_per_second: number;
+    received_blocks_per_second: number;
+    received_bytes_per_second: number;
+}
+
+/**
+ * Traffic of a socket in the ComHub statistics
+ */
+export interface JSSocketStatistics {
+    uuid: string;
+    /**
+     * Endpoint that is connected via the socket, if already known
+     */
+    endpoint: string | undefined;
+    traffic: JSTrafficStatistics;
+}
+
+/**
+ * Traffic of an interface in the ComHub statistics, which is the sum
+ * of the traffic of its sockets
+ */
+export interface JSInterfaceStatistics {
+    uuid: string;
+    interface_type: string;
+    name: string | undefined;
+    traffic: JSTrafficStatistics;
+    sockets: JSSocketStatistics[];
+}
+
+/**
+ * Traffic of all interfaces and sockets of the ComHub
+ */
+export interface JSComHubStatistics {
+    interfaces: JSInterfaceStatistics[];
+}
+
+/**
+ * Result of a ping to an endpoint
+ */
+export interface JSPingResult {
+    endpoint: string;
+    /**
+     * Round trip time in milliseconds
+     */
+    round_trip_time: number;
+}
+
+/**
+ * Hop of a trace, recorded by each endpoint that the trace block passed
+ */
+export interface JSTraceHop {
+    /**
+     * Endpoint that recorded the hop
+     */
+    endpoint: string;
+    distance: number;
+    /**
+     * \"Outgoing\" or \"Incoming\"
+     */
+    direction: string;
+    interface_type: string;
+    interface_name: string | undefined;
+    channel: string;
+    socket_uuid: string;
+    /**
+     * Round trip time to the endpoint of the hop in milliseconds,
+     * not available if the endpoint did not answer a ping in time
+     */
+    latency: number | undefined;
+}
+
+/**
+ * Route of a trace block to an endpoint and back
+ */
+export interface JSTraceResult {
+    sender: string;
+    receiver: string;
+    /**
+     * Round trip time in milliseconds
+     */
+    round_trip_time: number;
+    hops: JSTraceHop[];
+}
+
+/**
+ * Lifecycle event of an interface or socket of the ComHub
+ */
+export type ComHubEvent =
+    | {
+        type: "interface-added";
+        interface_uuid: string;
+        interface_type: string;
+        name: string | undefined;
+        state: string;
+    }
+    | { type: "interface-removed"; interface_uuid: string }
+    | { type: "interface-state-changed"; interface_uuid: string; state: string }
+    | {
+        type: "socket-registered";
+        interface_uuid: string;
+        socket_uuid: string;
+        direction: string;
+    }
+    | { type: "socket-closed"; interface_uuid: string; socket_uuid: string }
+    | {
+        type: "socket-endpoint-bound";
+        interface_uuid: string;
+        socket_uuid: string;
+        endpoint: string;
+    };
+
 export type InterfaceDirection = "In" | "Out" | "InOut";
 
 export interface InterfaceProperties {
@@ -143,6 +731,29 @@ export class BaseJSInterface {
     free(): void;
     [Symbol.dispose](): void;
 }
+export class CustomJSInterfaceController {
+    private constructor();
+    free(): void;
+    [Symbol.dispose](): void;
+    /**
+     * Adds a new socket, the endpoint of the socket can be set if it is
+     * already known, otherwise it is detected from the received blocks
+     */
+    add_socket(direction: string, endpoint?: string | null): string;
+    remove_socket(socket_uuid: string): void;
+    /**
+     * Passes the received bytes to the socket, the bytes do not have to
+     * contain complete blocks
+     */
+    receive(socket_uuid: string, data: Uint8Array): void;
+    /**
+     * Marks the interface as disconnected, e.g. when the underlying
+     * transport was closed by the other side, so that the ComHub can
+     * reconnect it according to its reconnection config
+     */
+    set_disconnected(): void;
+    readonly interface_uuid: string;
+}
 export class JSComHub {
     private constructor();
     free(): void;
@@ -157,6 +768,33 @@ export class JSComHub {
         answer: Uint8Array,
     ): Promise<void>;
     webrtc_interface_wait_for_connection(interface_uuid: string): Promise<void>;
+    webrtc_interface_create_data_channel(
+        interface_uuid: string,
+        label: string,
+        config: any,
+    ): Promise<void>;
+    webrtc_interface_set_send_channel(
+        interface_uuid: string,
+        label: string,
+    ): void;
+    /**
+     * Sets the function that is called with each block before it is sent
+     * and returns the label of the data channel for the block, or
+     * undefined to use the send channel
+     */
+    webrtc_interface_set_channel_selector(
+        interface_uuid: string,
+        selector: Function,
+    ): void;
+    webrtc_interface_add_local_stream(
+        interface_uuid: string,
+        stream: MediaStream,
+    ): void;
+    webrtc_interface_get_remote_streams(interface_uuid: string): Array<any>;
+    webrtc_interface_set_on_remote_stream(
+        interface_uuid: string,
+        on_remote_stream: Function,
+    ): void;
     webrtc_interface_add_ice_candidate(
         interface_uuid: string,
         candidate: Uint8Array,
@@ -165,14 +803,230 @@ export class JSComHub {
         interface_uuid: string,
         offer: Uint8Array,
     ): Promise<Uint8Array>;
+    webrtc_interface_set_on_connection_state_change(
+        interface_uuid: string,
+        on_connection_state_change: Function,
+    ): void;
+    /**
+     * Sets the callback that is invoked after an ICE restart was
+     * triggered. The callback must send a new offer to the remote peer.
+     */
+    webrtc_interface_set_on_negotiation_needed(
+        interface_uuid: string,
+        on_negotiation_needed: Function,
+    ): void;
+    /**
+     * Returns the latest connection statistics and applies them
+     * to the interface properties
+     */
+    webrtc_interface_get_stats(interface_uuid: string): any;
+    /**
+     * Enables handling of WebRTC signaling messages received over existing
+     * DATEX connections. Outgoing signals are signed with the PKCS#8
+     * encoded Ed25519 private key of the own endpoint, incoming signals are
+     * verified with the SPKI encoded key that resolve_key returns (or a
+     * promise of it) for the sender endpoint.
+     * If accept_incoming is true, incoming offers from endpoints without
+     * a WebRTC interface are accepted automatically.
+     */
+    webrtc_enable_in_band_signaling(
+        accept_incoming: boolean,
+        private_key: Uint8Array,
+        resolve_key: Function,
+    ): void;
+    /**
+     * Connects an existing WebRTC interface to its remote endpoint,
+     * exchanging offer, answer and ICE candidates over the ComHub
+     */
+    webrtc_interface_connect(interface_uuid: string): Promise<void>;
+    /**
+     * Creates a WebRTC interface for the given endpoint and connects to it
+     * using in-band signaling. Returns the UUID of the created interface.
+     */
+    webrtc_connect(endpoint: string): Promise<string>;
+    /**
+     * Announces the own endpoint on the channel of the BroadcastChannel
+     * interface, so that the other runtimes on the channel connect to it
+     */
+    broadcast_channel_interface_announce(interface_uuid: string): boolean;
+    /**
+     * Connects the message port interface to a MessagePort, a Worker or
+     * the global scope of a worker. The runtime on the other side must
+     * connect a message port interface to the corresponding port.
+     */
+    message_port_interface_set_port(interface_uuid: string, port: any): void;
+    /**
+     * Connects a loopback interface to a loopback interface of another
+     * runtime in the same wasm instance. Blocks are passed in memory,
+     * delayed and dropped as configured in the setup data of the sender.
+     */
+    loopback_interface_connect(
+        interface_uuid: string,
+        other_com_hub: JSComHub,
+        other_interface_uuid: string,
+    ): void;
+    /**
+     * Passes the body of a POST request of the given session to the
+     * HTTP server interface, an unknown session is opened
+     */
+    http_server_interface_receive(
+        interface_uuid: string,
+        session_id: string,
+        body: Uint8Array,
+    ): void;
+    /**
+     * Waits for blocks for the given session and returns the bytes for
+     * the response of a GET request. The bytes are empty if the poll
+     * timed out and undefined if the session was closed.
+     */
+    http_server_interface_poll(
+        interface_uuid: string,
+        session_id: string,
+    ): Promise<Uint8Array | undefined>;
+    /**
+     * Closes the given session, e.g. for a DELETE request.
+     * Returns false if the session does not exist.
+     */
+    http_server_interface_close_session(
+        interface_uuid: string,
+        session_id: string,
+    ): boolean;
+    /**
+     * Closes all sessions, so that pending poll requests are answered
+     * before the server is shut down
+     */
+    http_server_interface_close_all_sessions(interface_uuid: string): void;
+    /**
+     * Registers a factory for interfaces that are implemented in JS.
+     * The factory has a `create(setupData, controller)` method that
+     * returns the implementation with `open`, `send` and `close` methods,
+     * and optional `properties` that override the default properties
+     */
+    register_interface_factory(interface_type: string, factory: any): void;
+    /**
+     * Subscribes to the lifecycle events of interfaces and sockets,
+     * returns the id of the subscription. The existing interfaces and
+     * sockets are passed to the callback as added and registered events
+     */
+    subscribe_events(callback: Function): number;
+    /**
+     * Removes the subscription with the given id, returns false
+     * if there is no such subscription
+     */
+    unsubscribe_events(subscription_id: number): boolean;
+    /**
+     * Returns the interfaces and sockets of the ComHub
+     * with their properties
+     */
+    get_metadata(): any;
+    /**
+     * Sends a trace block to the endpoint and returns the round trip time.
+     * The timeout is given in milliseconds (default: 5000)
+     */
+    ping(endpoint: string, timeout?: number | null): Promise<any>;
+    /**
+     * Records the route of a trace block to the endpoint and back.
+     * The latency of each hop is measured with a ping to the endpoint
+     * of the hop, like in a traceroute. The hops are pinged concurrently.
+     * The timeout is given in milliseconds and applies to the trace and
+     * the pings together (default: 5000)
+     */
+    trace(endpoint: string, timeout?: number | null): Promise<any>;
+    /**
+     * Returns the number of blocks and bytes that were sent and received
+     * by each interface and socket, the failed sends, the receive queue
+     * depth and the rates over the last 10 seconds.
+     * The blocks of matchbox interfaces are counted as sent when they are
+     * handed to the interface, failed sends are not counted for them
+     */
+    get_traffic_statistics(): any;
+    /**
+     * Registers a callback that is called with (block, socket_uuid) for
+     * each complete block that is received by an interface, before it is
+     * processed.
+     * The callback can return false to drop the block, a Uint8Array to
+     * replace it, or a Promise to delay it until the Promise resolves.
+     * Blocks of matchbox interfaces can only be observed, the return value
+     * is ignored for them. Returns the id of the interceptor
+     */
+    register_incoming_block_interceptor(callback: Function): number;
+    /**
+     * Registers a callback that is called with (block, socket_uuid,
+     * endpoints) for each block before it is sent by an interface.
+     * The callback can return false to drop the block, a Uint8Array to
+     * replace it, or a Promise to delay it until the Promise resolves.
+     * A dropped block is reported as handled, not as a failed send.
+     * Blocks of matchbox interfaces can only be observed, the return value
+     * is ignored for them. Returns the id of the interceptor
+     */
+    register_outgoing_block_interceptor(callback: Function): number;
+    /**
+     * Removes the interceptor with the given id, returns false
+     * if there is no such interceptor
+     */
+    unregister_block_interceptor(interceptor_id: number): boolean;
+    /**
+     * Sets the callback that is invoked with (\"join\" | \"leave\", socket_uuid)
+     * when a peer joins or leaves the room of the matchbox interface.
+     * Each peer is connected via its own socket. A previously set
+     * callback of the interface is replaced.
+     *
+     * The events are detected by comparing the sockets of the interface
+     * every 250ms, so they are delayed by up to 250ms and a peer that
+     * joins and leaves within one interval is not reported. When the
+     * interface is removed, a leave event is emitted for each peer.
+     */
+    matchbox_interface_set_on_peer_event(
+        interface_uuid: string,
+        on_peer_event: Function,
+    ): void;
+    websocket_client_interface_get_protocol(
+        interface_uuid: string,
+    ): string | undefined;
+    websocket_client_interface_get_close_info(interface_uuid: string): any;
+    websocket_client_interface_set_on_close(
+        interface_uuid: string,
+        on_close: Function,
+    ): void;
     websocket_server_interface_add_socket(
         interface_uuid: string,
         websocket: WebSocket,
     ): string;
+    /**
+     * Returns a list of all clients connected to the WebSocket server
+     * interface with their socket UUIDs and endpoints
+     */
+    websocket_server_interface_get_sockets(interface_uuid: string): any;
+    /**
+     * Closes the connection to a client with an optional close code
+     * (1000 or 3000-4999, default: 1000) and reason
+     */
+    websocket_server_interface_close_socket(
+        interface_uuid: string,
+        socket_uuid: string,
+        code?: number | null,
+        reason?: string | null,
+    ): void;
+    /**
+     * Sets the function that resolves the public key of an endpoint for
+     * the handshake, called with the endpoint string and returning the
+     * SPKI encoded Ed25519 key (or a promise of it), or undefined
+     */
+    websocket_server_interface_set_key_resolver(
+        interface_uuid: string,
+        resolve_key: Function,
+    ): void;
+    websocket_server_interface_set_on_connect(
+        interface_uuid: string,
+        on_connect: Function,
+    ): void;
+    websocket_server_interface_set_on_disconnect(
+        interface_uuid: string,
+        on_disconnect: Function,
+    ): void;
     update(): Promise<void>;
     close_interface(interface_uuid: string): Promise<any>;
     get_metadata_string(): string;
-    register_outgoing_block_interceptor(callback: Function): void;
     /**
      * Send a block to the given interface and socket
      * This does not involve the routing on the ComHub level.
@@ -186,9 +1040,18 @@ export class JSComHub {
     ): Promise<boolean>;
     get_trace_string(endpoint: string): Promise<string | undefined>;
     register_default_interface_factories(): void;
-    register_incoming_block_interceptor(callback: Function): void;
     _drain_incoming_blocks(): Uint8Array[];
-    create_interface(interface_type: string, properties: string): Promise<any>;
+    /**
+     * Creates an interface from a plain JS object as setup data, which is
+     * validated against the setup data type of the interface factory.
+     * The priority is used for fallback routing, the default priority is
+     * used if it is not set
+     */
+    create_interface(
+        interface_type: string,
+        setup_data: any,
+        priority?: number | null,
+    ): Promise<any>;
     base_interface_receive(
         uuid: string,
         socket_uuid: string,
@@ -244,6 +1107,13 @@ export class JSRuntime {
     com_hub: JSComHub;
     readonly version: string;
 }
+export class MatchboxClientRegistry {
+    private constructor();
+    free(): void;
+    [Symbol.dispose](): void;
+    close(interface_uuid: string): Promise<any>;
+    register(address: string): Promise<Promise<any>>;
+}
 export class RuntimeDIFHandle {
     private constructor();
     free(): void;
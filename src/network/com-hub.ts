@@ -1,5 +1,69 @@
-import type { JSComHub } from "../datex-core/datex_core_js.d.ts";
+import type {
+    ComHubEvent,
+    JSComHub,
+    JSComHubMetadata,
+    JSComHubStatistics,
+    JSPingResult,
+    JSTraceResult,
+} from "../datex-core/datex_core_js.d.ts";
 import { ComInterface, type ComInterfaceImpl } from "./com-interface.ts";
+import {
+    type CustomInterfaceFactory,
+    CustomInterfaceImpl,
+} from "./custom-interface.ts";
+
+export type {
+    /**
+     * Lifecycle event of an interface or socket of the ComHub.
+     */
+    ComHubEvent,
+    /**
+     * Interfaces and sockets of the ComHub.
+     */
+    JSComHubMetadata,
+    /**
+     * Traffic of the interfaces and sockets of the ComHub.
+     */
+    JSComHubStatistics,
+    /**
+     * Result of a ping to an endpoint.
+     */
+    JSPingResult,
+    /**
+     * Route of a trace block to an endpoint and back.
+     */
+    JSTraceResult,
+};
+
+/**
+ * Callback that intercepts a received block. Returning false drops the
+ * block, returning a Uint8Array replaces it, and any other value passes it
+ * on unchanged. A returned Promise delays the block until it resolves.
+ */
+export type BlockInterceptor = (
+    block: Uint8Array,
+    socketUUID: string,
+) => BlockInterceptorResult;
+
+/**
+ * Callback that intercepts a block before it is sent, called with the
+ * receiver endpoints of the block. The return value is handled like for
+ * a {@link BlockInterceptor}.
+ */
+export type OutgoingBlockInterceptor = (
+    block: Uint8Array,
+    socketUUID: string,
+    endpoints: string[],
+) => BlockInterceptorResult;
+
+/**
+ * Return value of a block interceptor.
+ */
+export type BlockInterceptorResult =
+    | boolean
+    | Uint8Array
+    | void
+    | Promise<boolean | Uint8Array | void>;
 
 /**
  * Communication hub for managing communication interfaces.
@@ -20,6 +84,9 @@ export class ComHub {
     /** The JS communication hub. */
     readonly #jsComHub: JSComHub;
 
+    /** Interface types with a custom factory registered on this hub. */
+    readonly #customInterfaceTypes = new Set<string>();
+
     constructor(jsComHub: JSComHub) {
         this.#jsComHub = jsComHub;
     }
@@ -42,24 +109,49 @@ export class ComHub {
         this.#interfaceImplsByClass.set(impl, interfaceType);
     }
 
+    /**
+     * Registers a factory for a custom communication interface that is
+     * implemented in JavaScript. Interfaces of the type can then be created
+     * with {@link createInterface} like the built-in interfaces.
+     * @param interfaceType The type of the interface.
+     * @param factory The factory that creates the interface implementations.
+     */
+    registerInterfaceFactory<SetupData>(
+        interfaceType: string,
+        factory: CustomInterfaceFactory<SetupData>,
+    ): void {
+        if (ComHub.#interfaceImpls.has(interfaceType)) {
+            throw new Error(
+                `Interface implementation for ${interfaceType} already registered.`,
+            );
+        }
+        this.#jsComHub.register_interface_factory(interfaceType, factory);
+        this.#customInterfaceTypes.add(interfaceType);
+    }
+
     /**
      * Creates a new communication interface.
      * @param interfaceType The type of the interface to create.
      * @param setupData The setup data for the interface.
+     * @param priority The priority of the interface for fallback routing.
+     * If not set, the default priority is used.
      */
     async createInterface<T extends typeof ComInterfaceImpl<unknown>>(
         interfaceType: T,
         setupData: T extends typeof ComInterfaceImpl<infer P> ? P : never,
+        priority?: number,
     ): Promise<ComInterface<InstanceType<T>>>;
     async createInterface<
         T extends ComInterfaceImpl<unknown>,
     >(
         interfaceType: string,
         setupData: T extends ComInterfaceImpl<infer P> ? P : never,
+        priority?: number,
     ): Promise<ComInterface<T>>;
     async createInterface(
         interfaceType: string | typeof ComInterfaceImpl,
         setupData: unknown,
+        priority?: number,
     ): Promise<ComInterface<ComInterfaceImpl<unknown>>> {
         const type = typeof interfaceType === "string"
             ? interfaceType
@@ -71,7 +163,10 @@ export class ComHub {
                 } not registered.`,
             );
         }
-        const implClass = ComHub.#interfaceImpls.get(type);
+        const implClass = ComHub.#interfaceImpls.get(type) ??
+            (this.#customInterfaceTypes.has(type)
+                ? CustomInterfaceImpl
+                : undefined);
         if (implClass === undefined) {
             throw new Error(
                 `Interface implementation for ${type} not registered.`,
@@ -79,7 +174,8 @@ export class ComHub {
         }
         const uuid = await this.#jsComHub.create_interface(
             type,
-            JSON.stringify(setupData),
+            setupData,
+            priority,
         );
         const impl = new (implClass as (new (
             uuid: string,
@@ -90,6 +186,112 @@ export class ComHub {
         return new ComInterface(uuid, impl, this.#jsComHub);
     }
 
+    /**
+     * Creates the first communication interface that can be opened, e.g.
+     * a WebSocket client interface with an HTTP client interface as
+     * fallback for networks that block WebSockets.
+     * @param candidates The interface types, setup data and optional
+     * priorities, in the order in which they are tried.
+     * @returns The interface that was created.
+     */
+    async createInterfaceWithFallback(
+        candidates: {
+            interfaceType: string;
+            setupData: unknown;
+            priority?: number;
+        }[],
+    ): Promise<ComInterface<ComInterfaceImpl<unknown>>> {
+        const errors: unknown[] = [];
+        for (const { interfaceType, setupData, priority } of candidates) {
+            try {
+                return await this.createInterface(
+                    interfaceType,
+                    setupData as never,
+                    priority,
+                );
+            } catch (error) {
+                console.warn(
+                    `Failed to create ${interfaceType} interface, trying next fallback`,
+                );
+                errors.push(error);
+            }
+        }
+        throw new AggregateError(errors, "Failed to create any interface");
+    }
+
+    /**
+     * Enables WebRTC signaling over existing DATEX connections, so that
+     * other endpoints can upgrade their connection to peer-to-peer.
+     * Only signaling messages that are addressed to the own endpoint and
+     * signed with the key of the sender endpoint are handled, messages for
+     * other endpoints are forwarded.
+     * @param privateKey The PKCS#8 encoded Ed25519 private key of the own
+     * endpoint, used to sign outgoing signaling messages.
+     * @param resolveKey Returns the SPKI encoded Ed25519 public key of an
+     * endpoint (or a promise of it), or undefined if the endpoint is unknown.
+     * @param acceptIncoming If true, incoming connection offers are
+     * accepted automatically (default: false).
+     */
+    public enableWebRTCSignaling(
+        privateKey: Uint8Array,
+        resolveKey: (
+            endpoint: string,
+        ) =>
+            | Uint8Array
+            | undefined
+            | Promise<Uint8Array | undefined>,
+        acceptIncoming = false,
+    ): void {
+        this.#jsComHub.webrtc_enable_in_band_signaling(
+            acceptIncoming,
+            privateKey,
+            resolveKey,
+        );
+    }
+
+    /**
+     * Creates a WebRTC interface for the given endpoint and negotiates
+     * the peer-to-peer connection over the existing DATEX connections.
+     * @param endpoint The endpoint to connect to.
+     * @returns A promise that resolves to the connected interface.
+     */
+    async connectWebRTC<T extends ComInterfaceImpl<unknown>>(
+        endpoint: string,
+    ): Promise<ComInterface<T>> {
+        const implClass = ComHub.#interfaceImpls.get("webrtc");
+        if (implClass === undefined) {
+            throw new Error(
+                "Interface implementation for webrtc not registered.",
+            );
+        }
+        const uuid = await this.#jsComHub.webrtc_connect(endpoint);
+        const impl = new (implClass as (new (
+            uuid: string,
+            setupData: unknown,
+            comHub: JSComHub,
+        ) => ComInterfaceImpl<unknown>))(
+            uuid,
+            { peer_endpoint: endpoint },
+            this.#jsComHub,
+        );
+        return new ComInterface(uuid, impl as T, this.#jsComHub);
+    }
+
+    /**
+     * Subscribes to the lifecycle events of interfaces and sockets, e.g.
+     * to show the connection state in the UI. The existing interfaces and
+     * sockets are passed to the callback as added and registered events
+     * right away.
+     * @param callback The callback to be invoked for each event.
+     * @returns A function that removes the subscription.
+     */
+    public onEvent(callback: (event: ComHubEvent) => void): () => void {
+        const subscriptionId = this.#jsComHub.subscribe_events(callback);
+        return () => {
+            this.#jsComHub.unsubscribe_events(subscriptionId);
+        };
+    }
+
     public _update(): Promise<void> {
         return this.#jsComHub.update();
     }
@@ -98,6 +300,50 @@ export class ComHub {
         return this.#jsComHub._drain_incoming_blocks();
     }
 
+    /**
+     * Returns the interfaces of the ComHub with their state, priority,
+     * properties and sockets, e.g. for a diagnostics page.
+     */
+    public getMetadata(): JSComHubMetadata {
+        return this.#jsComHub.get_metadata();
+    }
+
+    /**
+     * Returns the number of blocks and bytes that were sent and received
+     * by each interface and socket, the number of failed sends, the
+     * receive queue depth and the rates over the last 10 seconds,
+     * e.g. to find a peer that floods a relay.
+     * Blocks are counted when they are handed to the transport or
+     * received, after they passed the block interceptors. The blocks of
+     * matchbox interfaces are counted as sent when they are handed to the
+     * interface, failed sends are not counted for them.
+     */
+    public getTrafficStatistics(): JSComHubStatistics {
+        return this.#jsComHub.get_traffic_statistics();
+    }
+
+    /**
+     * Measures the round trip time to an endpoint.
+     * @param endpoint The endpoint to ping.
+     * @param timeout The timeout in milliseconds (default: 5000).
+     * @returns The round trip time in milliseconds.
+     */
+    public ping(endpoint: string, timeout?: number): Promise<JSPingResult> {
+        return this.#jsComHub.ping(endpoint, timeout);
+    }
+
+    /**
+     * Records the route to an endpoint and back, including the interface,
+     * socket and latency of each hop.
+     * @param endpoint The endpoint to trace.
+     * @param timeout The timeout in milliseconds for the trace and the
+     * concurrent pings to the hops together (default: 5000).
+     * @returns The hops of the route.
+     */
+    public trace(endpoint: string, timeout?: number): Promise<JSTraceResult> {
+        return this.#jsComHub.trace(endpoint, timeout);
+    }
+
     /**
      * Prints the metadata of the ComHub. Only available in debug builds.
      */
@@ -139,26 +385,48 @@ export class ComHub {
     }
 
     /**
-     * Registers a callback to intercept incoming blocks.
+     * Registers a callback to intercept incoming blocks before they are
+     * processed, e.g. for a firewall or a traffic recorder. The callback
+     * is called once for each complete block, even if the transport
+     * delivers it in multiple chunks.
+     * The callback can return false to drop the block, a Uint8Array to
+     * replace it, or a Promise to delay it until the Promise resolves.
+     * Interceptors are called in the order of registration.
+     * Blocks of matchbox interfaces are passed to the interceptors as well,
+     * but can only be observed: the return value is ignored for them.
      * @param callback The callback to be invoked for each incoming block.
+     * @returns A function that removes the interceptor.
      */
     public registerIncomingBlockInterceptor(
-        callback: (block: Uint8Array, socket_uuid: string) => void,
-    ): void {
-        this.#jsComHub.register_incoming_block_interceptor(callback);
+        callback: BlockInterceptor,
+    ): () => void {
+        const interceptorId = this.#jsComHub
+            .register_incoming_block_interceptor(callback);
+        return () => {
+            this.#jsComHub.unregister_block_interceptor(interceptorId);
+        };
     }
 
     /**
-     * Registers a callback to intercept outgoing blocks.
+     * Registers a callback to intercept outgoing blocks before they are
+     * sent by an interface.
+     * The callback can return false to drop the block, a Uint8Array to
+     * replace it, or a Promise to delay it until the Promise resolves.
+     * A dropped block is reported as handled by the interface, so it is
+     * not counted as a failed send.
+     * Interceptors are called in the order of registration.
+     * Like for incoming interceptors, blocks of matchbox interfaces can
+     * only be observed.
      * @param callback The callback to be invoked for each outgoing block.
+     * @returns A function that removes the interceptor.
      */
     public registerOutgoingBlockInterceptor(
-        callback: (
-            block: Uint8Array,
-            socket_uuid: string,
-            endpoints: string[],
-        ) => void,
-    ): void {
-        this.#jsComHub.register_outgoing_block_interceptor(callback);
+        callback: OutgoingBlockInterceptor,
+    ): () => void {
+        const interceptorId = this.#jsComHub
+            .register_outgoing_block_interceptor(callback);
+        return () => {
+            this.#jsComHub.unregister_block_interceptor(interceptorId);
+        };
     }
 }
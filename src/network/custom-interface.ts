@@ -0,0 +1,63 @@
+import { ComInterfaceImpl } from "./com-interface.ts";
+import type {
+    CustomJSInterfaceController,
+    InterfaceProperties,
+} from "../datex-core/datex_core_js.d.ts";
+
+export type {
+    /**
+     * Handle to manage the sockets of a custom interface.
+     */
+    CustomJSInterfaceController,
+};
+
+/**
+ * Implementation of a custom communication interface, created by a
+ * {@link CustomInterfaceFactory} for each new interface.
+ */
+export interface CustomInterface {
+    /**
+     * Opens the interface, e.g. connects to a server. The interface is
+     * not created if this throws or rejects.
+     */
+    open?(): Promise<void> | void;
+    /**
+     * Sends a block to a socket of the interface.
+     * @param block The data block to send.
+     * @param socketUUID The UUID of the socket to send the block to.
+     * @returns True if the block was sent successfully, false otherwise.
+     */
+    send(block: Uint8Array, socketUUID: string): Promise<boolean> | boolean;
+    /**
+     * Closes the interface.
+     */
+    close?(): Promise<void> | void;
+}
+
+/**
+ * Factory for a custom communication interface that is implemented in
+ * JavaScript, e.g. for a proprietary transport.
+ */
+export interface CustomInterfaceFactory<SetupData> {
+    /**
+     * Properties of the created interfaces that override the defaults,
+     * e.g. the channel or the round trip time.
+     */
+    properties?: Partial<InterfaceProperties>;
+    /**
+     * Creates the implementation of a new interface. The controller is
+     * used to add sockets and to pass the received data to the sockets.
+     * @param setupData The setup data for the interface.
+     * @param controller The controller of the interface.
+     */
+    create(
+        setupData: SetupData,
+        controller: CustomJSInterfaceController,
+    ): CustomInterface;
+}
+
+/**
+ * Implementation class for interfaces created by a custom factory, the
+ * interface itself is managed by the factory.
+ */
+export class CustomInterfaceImpl extends ComInterfaceImpl<unknown> {}
@@ -0,0 +1,28 @@
+import { ComInterfaceImpl } from "../com-interface.ts";
+import { ComHub } from "../com-hub.ts";
+import type { BroadcastChannelInterfaceSetupData } from "../../datex-core.ts";
+
+/**
+ * Implementation of the BroadcastChannel communication interface.
+ * Connects all runtimes in the same origin (e.g. browser tabs) that use
+ * the same channel name, each peer is connected via its own socket.
+ */
+export class BroadcastChannelInterfaceImpl
+    extends ComInterfaceImpl<BroadcastChannelInterfaceSetupData> {
+    #onPageHide = () => this.jsComHub.close_interface(this.uuid);
+
+    override init() {
+        // the other runtimes connect once the own endpoint is announced
+        this.jsComHub.broadcast_channel_interface_announce(this.uuid);
+        globalThis.addEventListener?.("pagehide", this.#onPageHide);
+    }
+
+    override cleanup() {
+        globalThis.removeEventListener?.("pagehide", this.#onPageHide);
+    }
+}
+
+ComHub.registerInterfaceImpl(
+    "broadcast-channel",
+    BroadcastChannelInterfaceImpl,
+);
@@ -0,0 +1,13 @@
+import { ComInterfaceImpl } from "../com-interface.ts";
+import { ComHub } from "../com-hub.ts";
+import type { HttpClientInterfaceSetupData } from "../../datex-core.ts";
+
+/**
+ * Implementation of the HTTP client communication interface.
+ * Sends blocks via POST requests and receives blocks via long-polling
+ * or a streaming response, e.g. if WebSockets are blocked by a proxy.
+ */
+export class HttpClientInterfaceImpl
+    extends ComInterfaceImpl<HttpClientInterfaceSetupData> {}
+
+ComHub.registerInterfaceImpl("http-client", HttpClientInterfaceImpl);
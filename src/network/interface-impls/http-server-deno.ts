@@ -0,0 +1,151 @@
+import { ComInterfaceImpl } from "../com-interface.ts";
+import { ComHub } from "../com-hub.ts";
+import type { HttpServerJSInterfaceSetupData } from "../../datex-core/datex_core_js.d.ts";
+
+/**
+ * Headers that allow HTTP client interfaces on other origins
+ * to connect to the server.
+ */
+const CORS_HEADERS = {
+    "Access-Control-Allow-Origin": "*",
+    "Access-Control-Allow-Methods": "GET, POST, DELETE, OPTIONS",
+    "Access-Control-Allow-Headers": "Content-Type",
+};
+
+const BLOCK_HEADERS = {
+    ...CORS_HEADERS,
+    "Content-Type": "application/octet-stream",
+    "Cache-Control": "no-store",
+};
+
+/**
+ * Implementation of the HTTP server communication interface for Deno.
+ * If a port is set, the interface serves the requests itself, otherwise
+ * requests can be passed to `handleRequest` from an existing server.
+ */
+export class HttpServerDenoInterfaceImpl
+    extends ComInterfaceImpl<HttpServerJSInterfaceSetupData> {
+    #server?: Deno.HttpServer;
+
+    override init() {
+        if (this.setupData.port !== undefined) {
+            this.#server = Deno.serve(
+                { port: this.setupData.port },
+                (req) => this.handleRequest(req),
+            );
+        }
+    }
+
+    /**
+     * Handles a request of an HTTP client interface.
+     * POST requests pass blocks to the interface, GET requests wait for
+     * blocks (long-polling, or a streaming response if the `stream`
+     * query parameter is set) and DELETE requests end the session.
+     * @param request The request, identified by the `session` query parameter.
+     * @returns The response for the client.
+     */
+    public async handleRequest(request: Request): Promise<Response> {
+        if (request.method === "OPTIONS") {
+            return new Response(null, { status: 204, headers: CORS_HEADERS });
+        }
+        const url = new URL(request.url);
+        const session = url.searchParams.get("session");
+        if (session === null) {
+            return new Response("Missing session", {
+                status: 400,
+                headers: CORS_HEADERS,
+            });
+        }
+        try {
+            switch (request.method) {
+                case "POST": {
+                    const body = new Uint8Array(await request.arrayBuffer());
+                    this.jsComHub.http_server_interface_receive(
+                        this.uuid,
+                        session,
+                        body,
+                    );
+                    return new Response(null, {
+                        status: 204,
+                        headers: CORS_HEADERS,
+                    });
+                }
+                case "GET": {
+                    if (url.searchParams.has("stream")) {
+                        return new Response(this.#createStream(session), {
+                            headers: BLOCK_HEADERS,
+                        });
+                    }
+                    const blocks = await this.jsComHub
+                        .http_server_interface_poll(this.uuid, session);
+                    if (blocks === undefined) {
+                        return new Response(null, {
+                            status: 410,
+                            headers: CORS_HEADERS,
+                        });
+                    }
+                    return new Response(blocks, { headers: BLOCK_HEADERS });
+                }
+                case "DELETE":
+                    this.jsComHub.http_server_interface_close_session(
+                        this.uuid,
+                        session,
+                    );
+                    return new Response(null, {
+                        status: 204,
+                        headers: CORS_HEADERS,
+                    });
+                default:
+                    return new Response(null, {
+                        status: 405,
+                        headers: CORS_HEADERS,
+                    });
+            }
+        } catch (e) {
+            return new Response(String(e), {
+                status: 400,
+                headers: CORS_HEADERS,
+            });
+        }
+    }
+
+    /**
+     * Creates a chunked response body that receives the blocks of the
+     * session until the session is closed or the client disconnects.
+     */
+    #createStream(session: string): ReadableStream<Uint8Array> {
+        let cancelled = false;
+        return new ReadableStream({
+            pull: async (controller) => {
+                // a pull must enqueue a chunk, otherwise it is not repeated
+                while (!cancelled) {
+                    const blocks = await this.jsComHub
+                        .http_server_interface_poll(this.uuid, session)
+                        .catch(() => undefined);
+                    if (blocks === undefined) {
+                        controller.close();
+                        return;
+                    }
+                    if (blocks.length > 0) {
+                        controller.enqueue(blocks);
+                        return;
+                    }
+                }
+            },
+            cancel: () => {
+                cancelled = true;
+            },
+        });
+    }
+
+    override async cleanup() {
+        if (this.#server) {
+            // pending poll requests would delay the shutdown
+            this.jsComHub.http_server_interface_close_all_sessions(this.uuid);
+            await this.#server.shutdown();
+            this.#server = undefined;
+        }
+    }
+}
+
+ComHub.registerInterfaceImpl("http-server", HttpServerDenoInterfaceImpl);
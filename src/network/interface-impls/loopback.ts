@@ -0,0 +1,25 @@
+import { ComInterfaceImpl } from "../com-interface.ts";
+import { ComHub } from "../com-hub.ts";
+import type { LoopbackInterfaceSetupData } from "../../datex-core.ts";
+
+/**
+ * Implementation of the loopback communication interface.
+ * Connects two runtimes in the same wasm instance in memory, optionally
+ * simulating latency, jitter, packet loss and limited bandwidth.
+ */
+export class LoopbackInterfaceImpl
+    extends ComInterfaceImpl<LoopbackInterfaceSetupData> {
+    /**
+     * Connects this interface to the loopback interface of another runtime.
+     * @param other The loopback interface of the other runtime.
+     */
+    connect(other: LoopbackInterfaceImpl) {
+        this.jsComHub.loopback_interface_connect(
+            this.uuid,
+            other.jsComHub,
+            other.uuid,
+        );
+    }
+}
+
+ComHub.registerInterfaceImpl("loopback", LoopbackInterfaceImpl);
@@ -0,0 +1,32 @@
+import { ComInterfaceImpl } from "../com-interface.ts";
+import { ComHub } from "../com-hub.ts";
+import type { MatchboxJSInterfaceSetupData } from "../../datex-core.ts";
+
+/**
+ * Implementation of the matchbox communication interface.
+ * Connects to all peers in a room of a matchbox signaling server via WebRTC.
+ */
+export class MatchboxInterfaceImpl
+    extends ComInterfaceImpl<MatchboxJSInterfaceSetupData> {
+    /**
+     * Sets the callback to be invoked when a peer joins or leaves the room.
+     * Each peer is connected via its own socket.
+     *
+     * The matchbox interface does not notify about peer state changes, so
+     * the events are detected by comparing its sockets every 250ms. They are
+     * delayed by up to 250ms and a peer that joins and leaves within one
+     * interval is not reported. When the interface is removed, a leave event
+     * is emitted for each peer.
+     * @param onPeerEvent The callback to be invoked with the event type and the socket UUID of the peer.
+     */
+    public setOnPeerEvent(
+        onPeerEvent: (event: "join" | "leave", socketUuid: string) => void,
+    ): void {
+        this.jsComHub.matchbox_interface_set_on_peer_event(
+            this.uuid,
+            onPeerEvent,
+        );
+    }
+}
+
+ComHub.registerInterfaceImpl("matchbox", MatchboxInterfaceImpl);
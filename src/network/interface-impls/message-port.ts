@@ -0,0 +1,39 @@
+import { ComInterfaceImpl } from "../com-interface.ts";
+import { ComHub } from "../com-hub.ts";
+import type { MessagePortInterfaceSetupData } from "../../datex-core.ts";
+
+/**
+ * An object that runtimes can exchange messages with,
+ * e.g. a MessagePort, a Worker or the global scope of a worker.
+ */
+export type MessagePortLike = EventTarget & {
+    postMessage(message: unknown, transfer: Transferable[]): void;
+};
+
+/**
+ * Setup data for the message port interface.
+ */
+export type MessagePortInterfaceImplSetupData =
+    & MessagePortInterfaceSetupData
+    & {
+        /**
+         * Port to the other runtime
+         */
+        port: MessagePortLike;
+    };
+
+/**
+ * Implementation of the message port communication interface.
+ * Connects to a runtime in a worker or on the other side of a MessageChannel.
+ */
+export class MessagePortInterfaceImpl
+    extends ComInterfaceImpl<MessagePortInterfaceImplSetupData> {
+    override init() {
+        this.jsComHub.message_port_interface_set_port(
+            this.uuid,
+            this.setupData.port,
+        );
+    }
+}
+
+ComHub.registerInterfaceImpl("message-port", MessagePortInterfaceImpl);
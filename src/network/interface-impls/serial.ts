@@ -1,12 +1,12 @@
 import { ComInterfaceImpl } from "../com-interface.ts";
 import { ComHub } from "../com-hub.ts";
-import type { SerialInterfaceSetupData } from "../../datex-core.ts";
+import type { SerialJSInterfaceSetupData } from "../../datex-core.ts";
 
 /**
  * Implementation of the serial communication interface.
  */
 export class SerialInterfaceImpl
-    extends ComInterfaceImpl<SerialInterfaceSetupData> {
+    extends ComInterfaceImpl<SerialJSInterfaceSetupData> {
 }
 
 ComHub.registerInterfaceImpl("serial", SerialInterfaceImpl);
@@ -1,12 +1,16 @@
 import { ComInterfaceImpl } from "../com-interface.ts";
 import { ComHub } from "../com-hub.ts";
-import type { WebRTCInterfaceSetupData } from "../../datex-core.ts";
+import type {
+    DataChannelConfig,
+    WebRTCJSInterfaceSetupData,
+    WebRTCStats,
+} from "../../datex-core.ts";
 
 /**
  * Implementation of the WebRTC communication interface.
  */
 export class WebRTCInterfaceImpl
-    extends ComInterfaceImpl<WebRTCInterfaceSetupData> {
+    extends ComInterfaceImpl<WebRTCJSInterfaceSetupData> {
     /**
      * Sets the callback to be invoked when an ICE candidate is received.
      * @param onIceCandidate The callback to be invoked when an ICE candidate is received.
@@ -61,6 +65,130 @@ export class WebRTCInterfaceImpl
         return this.jsComHub.webrtc_interface_set_answer(this.uuid, answer);
     }
 
+    /**
+     * Creates an additional data channel with its own reliability settings.
+     * Blocks received on the channel are handled like blocks received on
+     * the default DATEX channel.
+     * @param label The label of the data channel.
+     * @param config The reliability settings of the data channel.
+     * @returns A promise that resolves when the data channel has been created.
+     */
+    public createDataChannel(
+        label: string,
+        config?: DataChannelConfig,
+    ): Promise<void> {
+        return this.jsComHub.webrtc_interface_create_data_channel(
+            this.uuid,
+            label,
+            config,
+        );
+    }
+
+    /**
+     * Selects the data channel that is used to send blocks for which
+     * the channel selector does not select a channel.
+     * @param label The label of the data channel.
+     */
+    public setSendChannel(label: string): void {
+        this.jsComHub.webrtc_interface_set_send_channel(this.uuid, label);
+    }
+
+    /**
+     * Sets the function that selects the data channel for each block,
+     * e.g. to send latency-sensitive blocks over an unreliable channel.
+     * @param selector Called with each block before it is sent, returns
+     * the label of the data channel or undefined to use the send channel.
+     */
+    public setChannelSelector(
+        selector: (block: Uint8Array) => string | undefined,
+    ): void {
+        this.jsComHub.webrtc_interface_set_channel_selector(
+            this.uuid,
+            selector,
+        );
+    }
+
+    /**
+     * Adds all tracks of a local media stream (e.g. from getUserMedia)
+     * to the connection. If the connection is already established,
+     * a new offer has to be negotiated for the tracks to be sent.
+     * @param stream The local media stream to add.
+     */
+    public addLocalStream(stream: MediaStream): void {
+        this.jsComHub.webrtc_interface_add_local_stream(this.uuid, stream);
+    }
+
+    /**
+     * Returns all media streams received from the remote peer.
+     * @returns The remote media streams.
+     */
+    public getRemoteStreams(): MediaStream[] {
+        return this.jsComHub.webrtc_interface_get_remote_streams(this.uuid);
+    }
+
+    /**
+     * Sets the callback to be invoked when a new remote media stream is received.
+     * @param onRemoteStream The callback to be invoked with the remote stream.
+     */
+    public setOnRemoteStream(
+        onRemoteStream: (stream: MediaStream) => void,
+    ): void {
+        this.jsComHub.webrtc_interface_set_on_remote_stream(
+            this.uuid,
+            onRemoteStream,
+        );
+    }
+
+    /**
+     * Sets the callback to be invoked when the connection state or the
+     * ICE connection state of the peer connection changes.
+     * @param onConnectionStateChange The callback to be invoked with the
+     * connection state and the ICE connection state.
+     */
+    public setOnConnectionStateChange(
+        onConnectionStateChange: (
+            connectionState: RTCPeerConnectionState,
+            iceConnectionState: RTCIceConnectionState,
+        ) => void,
+    ): void {
+        this.jsComHub.webrtc_interface_set_on_connection_state_change(
+            this.uuid,
+            onConnectionStateChange,
+        );
+    }
+
+    /**
+     * Sets the callback to be invoked after an ICE restart was triggered.
+     * The callback must create a new offer and send it to the remote peer.
+     * Not required if the connection was established with connect().
+     * @param onNegotiationNeeded The callback to be invoked.
+     */
+    public setOnNegotiationNeeded(onNegotiationNeeded: () => void): void {
+        this.jsComHub.webrtc_interface_set_on_negotiation_needed(
+            this.uuid,
+            onNegotiationNeeded,
+        );
+    }
+
+    /**
+     * Returns the latest statistics of the connection, or undefined if
+     * no statistics have been collected yet.
+     * @returns The round trip time, bandwidth and transferred bytes.
+     */
+    public getStats(): WebRTCStats | undefined {
+        return this.jsComHub.webrtc_interface_get_stats(this.uuid);
+    }
+
+    /**
+     * Connects to the remote endpoint by exchanging offer, answer and
+     * ICE candidates over the existing DATEX connections of the ComHub.
+     * In-band signaling must be enabled on both endpoints.
+     * @returns A promise that resolves when the connection is established.
+     */
+    public connect(): Promise<void> {
+        return this.jsComHub.webrtc_interface_connect(this.uuid);
+    }
+
     /**
      * Waits for the WebRTC connection to be established.
      * @returns A promise that resolves when the connection is established.
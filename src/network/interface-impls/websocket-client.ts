@@ -1,12 +1,47 @@
 import { ComInterfaceImpl } from "../com-interface.ts";
-import type { WebSocketClientInterfaceSetupData } from "../../datex-core/datex_core_js.d.ts";
+import type {
+    WebSocketClientJSInterfaceSetupData,
+    WebSocketCloseInfo,
+} from "../../datex-core/datex_core_js.d.ts";
 import { ComHub } from "../com-hub.ts";
 
 /**
  * Implementation of the WebSocket client communication interface.
  */
 export class WebSocketClientInterfaceImpl
-    extends ComInterfaceImpl<WebSocketClientInterfaceSetupData> {
+    extends ComInterfaceImpl<WebSocketClientJSInterfaceSetupData> {
+    /**
+     * Returns the subprotocol selected by the server.
+     * @returns The selected protocol, or undefined if not connected.
+     */
+    public getProtocol(): string | undefined {
+        return this.jsComHub.websocket_client_interface_get_protocol(
+            this.uuid,
+        );
+    }
+
+    /**
+     * Returns the close code, reason and wasClean flag of the last
+     * closed or failed connection.
+     * @returns The close info, or undefined if the connection was never closed.
+     */
+    public getCloseInfo(): WebSocketCloseInfo | undefined {
+        return this.jsComHub.websocket_client_interface_get_close_info(
+            this.uuid,
+        );
+    }
+
+    /**
+     * Sets the callback to be invoked whenever the connection is closed
+     * or could not be established.
+     * @param onClose The callback to be invoked with the close info.
+     */
+    public setOnClose(onClose: (closeInfo: WebSocketCloseInfo) => void): void {
+        this.jsComHub.websocket_client_interface_set_on_close(
+            this.uuid,
+            onClose,
+        );
+    }
 }
 
 ComHub.registerInterfaceImpl("websocket-client", WebSocketClientInterfaceImpl);
@@ -1,15 +1,42 @@
 import { ComInterfaceImpl } from "../com-interface.ts";
 import { ComHub } from "../com-hub.ts";
-import type { WebSocketServerInterfaceSetupData } from "../../datex-core/datex_core_js.d.ts";
+import type {
+    WebSocketCloseInfo,
+    WebSocketServerJSInterfaceSetupData,
+    WebSocketServerSocketInfo,
+} from "../../datex-core/datex_core_js.d.ts";
+
+/**
+ * Setup data for the WebSocket server interface for Deno.
+ */
+export type WebSocketServerDenoInterfaceSetupData =
+    & WebSocketServerJSInterfaceSetupData
+    & {
+        /**
+         * Resolves the SPKI encoded Ed25519 public key of an endpoint,
+         * required if the handshake is enabled.
+         * Returns undefined if the endpoint is unknown.
+         */
+        resolveKey?: (
+            endpoint: string,
+        ) => Uint8Array | undefined | Promise<Uint8Array | undefined>;
+    };
 
 /**
  * Implementation of the WebSocket server communication interface for Deno.
  */
 export class WebSockerServerDenoInterfaceImpl
-    extends ComInterfaceImpl<WebSocketServerInterfaceSetupData> {
+    extends ComInterfaceImpl<WebSocketServerDenoInterfaceSetupData> {
     #server?: Deno.HttpServer;
 
     override init() {
+        // the resolver must be set before the first client connects
+        if (this.setupData.resolveKey) {
+            this.jsComHub.websocket_server_interface_set_key_resolver(
+                this.uuid,
+                this.setupData.resolveKey,
+            );
+        }
         this.#server = Deno.serve({
             port: this.setupData.port,
         }, (req) => {
@@ -33,6 +60,54 @@ export class WebSockerServerDenoInterfaceImpl
         });
     }
 
+    /**
+     * Returns all clients that are currently connected to the server.
+     * @returns The socket UUIDs and endpoints of the connected clients.
+     */
+    public getSockets(): WebSocketServerSocketInfo[] {
+        return this.jsComHub.websocket_server_interface_get_sockets(this.uuid);
+    }
+
+    /**
+     * Closes the connection to a client.
+     * @param socketUUID The UUID of the socket to close.
+     * @param code The close code (1000 or 3000-4999, default: 1000).
+     * @param reason The close reason sent to the client.
+     */
+    public closeSocket(socketUUID: string, code?: number, reason?: string) {
+        this.jsComHub.websocket_server_interface_close_socket(
+            this.uuid,
+            socketUUID,
+            code,
+            reason,
+        );
+    }
+
+    /**
+     * Sets the callback to be invoked when a client connects.
+     * @param onConnect The callback to be invoked with the socket UUID.
+     */
+    public setOnConnect(onConnect: (socketUUID: string) => void): void {
+        this.jsComHub.websocket_server_interface_set_on_connect(
+            this.uuid,
+            onConnect,
+        );
+    }
+
+    /**
+     * Sets the callback to be invoked when a client disconnects.
+     * @param onDisconnect The callback to be invoked with the socket UUID
+     * and the close info.
+     */
+    public setOnDisconnect(
+        onDisconnect: (socketUUID: string, closeInfo: WebSocketCloseInfo) => void,
+    ): void {
+        this.jsComHub.websocket_server_interface_set_on_disconnect(
+            this.uuid,
+            onDisconnect,
+        );
+    }
+
     override async cleanup() {
         if (this.#server) {
             await this.#server.shutdown();
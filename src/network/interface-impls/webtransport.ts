@@ -0,0 +1,13 @@
+import { ComInterfaceImpl } from "../com-interface.ts";
+import { ComHub } from "../com-hub.ts";
+import type { WebTransportClientInterfaceSetupData } from "../../datex-core.ts";
+
+/**
+ * Implementation of the WebTransport client communication interface.
+ * Sends blocks over QUIC streams, or as unreliable datagrams if enabled
+ * and the block fits into a single datagram.
+ */
+export class WebTransportClientInterfaceImpl
+    extends ComInterfaceImpl<WebTransportClientInterfaceSetupData> {}
+
+ComHub.registerInterfaceImpl("webtransport", WebTransportClientInterfaceImpl);
@@ -7,3 +7,4 @@
 
 export * from "./com-hub.ts";
 export * from "./com-interface.ts";
+export * from "./custom-interface.ts";
@@ -0,0 +1,45 @@
+import { assert } from "@std/assert/assert";
+import { Runtime } from "../../src/runtime/runtime.ts";
+import * as uuid from "@std/uuid";
+import "../../src/network/interface-impls/broadcast-channel.ts";
+import { sleep } from "../utils.ts";
+
+Deno.test("add and close interface", async () => {
+    const runtime = new Runtime({ endpoint: "@unyt" });
+    const channelInterface = await runtime.comHub.createInterface(
+        "broadcast-channel",
+        { name: "datex-test-close" },
+    );
+    assert(uuid.validate(channelInterface.uuid), "Invalid UUID");
+    await channelInterface.close();
+});
+
+Deno.test("send data between two runtimes", async () => {
+    const name = "datex-test-send";
+    const runtimeA = await Runtime.create({ endpoint: "@test_a" }, {
+        allow_unsigned_blocks: true,
+    });
+    const interfaceA = await runtimeA.comHub.createInterface(
+        "broadcast-channel",
+        { name },
+    );
+
+    const runtimeB = await Runtime.create({ endpoint: "@test_b" }, {
+        allow_unsigned_blocks: true,
+    });
+    const interfaceB = await runtimeB.comHub.createInterface(
+        "broadcast-channel",
+        { name },
+    );
+
+    await sleep(1000);
+
+    const res = await runtimeA.executeWithStringResult("@test_b :: 1 + 2");
+    assert(res === "3", "Expected result from remote execution to be 3");
+
+    await interfaceA.close();
+    await interfaceB.close();
+
+    await runtimeA._stop();
+    await runtimeB._stop();
+});
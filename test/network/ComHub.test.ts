@@ -1,5 +1,53 @@
+import { assert } from "@std/assert/assert";
+import { assertEquals } from "@std/assert/equals";
+import { assertFalse } from "@std/assert/false";
+import { assertRejects } from "@std/assert/rejects";
+import * as uuid from "@std/uuid";
 import { sleep } from "../utils.ts";
 import { Runtime } from "../../src/runtime/runtime.ts";
+import "../../src/network/interface-impls/base.ts";
+import "../../src/network/interface-impls/loopback.ts";
+import type { BaseInterfaceImpl } from "../../src/network/interface-impls/base.ts";
+import type { LoopbackInterfaceImpl } from "../../src/network/interface-impls/loopback.ts";
+import type { ComHubEvent } from "../../src/network/com-hub.ts";
+
+/**
+ * Creates two runtimes that are connected via loopback interfaces.
+ * @param latency Simulated latency of the loopback interfaces in ms.
+ */
+async function createConnectedRuntimes(
+    endpointA: string,
+    endpointB: string,
+    latency?: number,
+) {
+    const runtimeA = await Runtime.create({ endpoint: endpointA }, {
+        allow_unsigned_blocks: true,
+    });
+    const interfaceA = await runtimeA.comHub.createInterface<
+        LoopbackInterfaceImpl
+    >(
+        "loopback",
+        { name: "b", latency },
+    );
+    const runtimeB = await Runtime.create({ endpoint: endpointB }, {
+        allow_unsigned_blocks: true,
+    });
+    const interfaceB = await runtimeB.comHub.createInterface<
+        LoopbackInterfaceImpl
+    >(
+        "loopback",
+        { name: "a", latency },
+    );
+    interfaceA.impl.connect(interfaceB.impl);
+    await sleep(100);
+    const close = async () => {
+        await interfaceA.close();
+        await interfaceB.close();
+        await runtimeA._stop();
+        await runtimeB._stop();
+    };
+    return { runtimeA, runtimeB, interfaceA, interfaceB, close };
+}
 
 Deno.test("update loop", async () => {
     const runtime = await Runtime.create({ endpoint: "@unyt" });
@@ -7,3 +55,229 @@ Deno.test("update loop", async () => {
     await sleep(1000);
     await runtime._stop();
 });
+
+Deno.test("create interface with priority", async () => {
+    const runtime = new Runtime({ endpoint: "@unyt" });
+    const loopbackInterface = await runtime.comHub.createInterface(
+        "loopback",
+        { name: "priority", latency: 5 },
+        10,
+    );
+    assert(uuid.validate(loopbackInterface.uuid), "Invalid UUID");
+    const defaultInterface = await runtime.comHub.createInterface(
+        "loopback",
+        { name: "default-priority" },
+    );
+    const interfaces = runtime.comHub.getMetadata().interfaces;
+    const priorityOf = (interfaceUUID: string) =>
+        interfaces.find((entry) => entry.uuid === interfaceUUID)?.priority;
+    assertEquals(priorityOf(loopbackInterface.uuid), 10);
+    assertEquals(priorityOf(defaultInterface.uuid), undefined);
+    await loopbackInterface.close();
+    await defaultInterface.close();
+});
+
+Deno.test("metadata", async () => {
+    const runtime = new Runtime({ endpoint: "@unyt" });
+    const loopbackInterface = await runtime.comHub.createInterface(
+        "loopback",
+        { name: "metadata" },
+        3,
+    );
+    const metadata = runtime.comHub.getMetadata();
+    assertEquals(metadata.endpoint, "@unyt");
+    const entry = metadata.interfaces.find((entry) =>
+        entry.uuid === loopbackInterface.uuid
+    );
+    assert(entry !== undefined, "Interface missing in metadata");
+    assertEquals(entry.priority, 3);
+    assertEquals(entry.properties.interface_type, "loopback");
+    assertEquals(entry.properties.name, "metadata");
+
+    await loopbackInterface.close();
+    assertFalse(
+        runtime.comHub.getMetadata().interfaces.some((entry) =>
+            entry.uuid === loopbackInterface.uuid
+        ),
+    );
+});
+
+Deno.test("invalid setup data", async () => {
+    const runtime = new Runtime({ endpoint: "@unyt" });
+    await assertRejects(
+        () =>
+            runtime.comHub.createInterface("loopback", {
+                latency: "fast",
+            } as never),
+        Error,
+        "Invalid setup data for loopback interface: latency",
+    );
+});
+
+Deno.test("interface and socket events", async () => {
+    const runtime = new Runtime({ endpoint: "@unyt" });
+    const events: ComHubEvent[] = [];
+    const unsubscribe = runtime.comHub.onEvent((event) => events.push(event));
+    // the existing interfaces of the runtime are passed to new subscribers
+    events.length = 0;
+
+    const baseInterface = await runtime.comHub.createInterface<
+        BaseInterfaceImpl
+    >("base", {
+        name: "events",
+        interface_type: "base",
+        channel: "test",
+        direction: "InOut",
+        round_trip_time: 5,
+        max_bandwidth: 1,
+        continuous_connection: true,
+        allow_redirects: true,
+        is_secure_channel: true,
+        reconnection_config: "NoReconnect",
+        reconnect_attempts: undefined,
+        close_timestamp: undefined,
+    });
+    const socket = baseInterface.impl.registerSocket("InOut");
+    await sleep(300);
+    assertEquals(events.map((event) => event.type), [
+        "interface-added",
+        "socket-registered",
+    ]);
+    assertEquals(events[1], {
+        type: "socket-registered",
+        interface_uuid: baseInterface.uuid,
+        socket_uuid: socket,
+        direction: "InOut",
+    });
+
+    events.length = 0;
+    await baseInterface.close();
+    await sleep(300);
+    assertEquals(events.map((event) => event.type), [
+        "socket-closed",
+        "interface-removed",
+    ]);
+
+    unsubscribe();
+});
+
+Deno.test("existing interfaces are passed to new subscribers", async () => {
+    const runtime = new Runtime({ endpoint: "@unyt" });
+    const loopbackInterface = await runtime.comHub.createInterface(
+        "loopback",
+        { name: "existing" },
+    );
+    const events: ComHubEvent[] = [];
+    const unsubscribe = runtime.comHub.onEvent((event) => events.push(event));
+    assert(
+        events.some((event) =>
+            event.type === "interface-added" &&
+            event.interface_uuid === loopbackInterface.uuid
+        ),
+        "Existing interface missing in initial events",
+    );
+    unsubscribe();
+    await loopbackInterface.close();
+});
+
+Deno.test("ping and trace", async () => {
+    const { runtimeA, close } = await createConnectedRuntimes(
+        "@trace_a",
+        "@trace_b",
+        10,
+    );
+
+    const ping = await runtimeA.comHub.ping("@trace_b");
+    assertEquals(ping.endpoint, "@trace_b");
+    assert(ping.round_trip_time >= 20, "Expected simulated latency");
+
+    const trace = await runtimeA.comHub.trace("@trace_b");
+    assertEquals(trace.receiver, "@trace_b");
+    assert(trace.hops.length > 0, "Expected hops in trace");
+    for (const hop of trace.hops) {
+        assertEquals(hop.interface_type, "loopback");
+        assert(hop.latency !== undefined, "Expected latency of hop");
+    }
+
+    await assertRejects(() => runtimeA.comHub.ping("@unknown", 200));
+
+    await close();
+});
+
+Deno.test("traffic statistics", async () => {
+    const { runtimeA, interfaceA, close } = await createConnectedRuntimes(
+        "@traffic_a",
+        "@traffic_b",
+    );
+
+    const res = await runtimeA.executeWithStringResult("@traffic_b :: 1 + 2");
+    assertEquals(res, "3");
+
+    const statistics = runtimeA.comHub.getTrafficStatistics();
+    const loopback = statistics.interfaces.find((i) =>
+        i.uuid === interfaceA.uuid
+    );
+    assert(loopback !== undefined, "Expected statistics of interface");
+    assertEquals(loopback.interface_type, "loopback");
+    assert(loopback.traffic.blocks_sent > 0, "Expected sent blocks");
+    assert(loopback.traffic.blocks_received > 0, "Expected received blocks");
+    assert(loopback.traffic.bytes_sent > 0, "Expected sent bytes");
+    assert(loopback.traffic.sent_bytes_per_second > 0, "Expected send rate");
+    assertEquals(loopback.traffic.failed_sends, 0);
+    assertEquals(
+        loopback.sockets.reduce((sum, s) => sum + s.traffic.bytes_sent, 0),
+        loopback.traffic.bytes_sent,
+    );
+
+    await close();
+});
+
+Deno.test("block interceptors", async () => {
+    const { runtimeA, runtimeB, interfaceA, close } =
+        await createConnectedRuntimes(
+            "@intercept_a",
+            "@intercept_b",
+        );
+
+    // async interceptors delay blocks, rewritten blocks are passed on
+    let received = 0;
+    const removeIncoming = runtimeB.comHub.registerIncomingBlockInterceptor(
+        async (block) => {
+            received++;
+            await sleep(10);
+            return new Uint8Array(block);
+        },
+    );
+    const res = await runtimeA.executeWithStringResult(
+        "@intercept_b :: 1 + 2",
+    );
+    assertEquals(res, "3");
+    assert(received > 0, "Expected intercepted incoming blocks");
+
+    removeIncoming();
+    const receivedBeforeRemoval = received;
+    await runtimeA.comHub.ping("@intercept_b");
+    assertEquals(received, receivedBeforeRemoval);
+
+    // dropped blocks never reach the other side
+    const receivers: string[] = [];
+    const removeOutgoing = runtimeA.comHub.registerOutgoingBlockInterceptor(
+        (_block, _socketUUID, endpoints) => {
+            receivers.push(...endpoints);
+            return false;
+        },
+    );
+    await assertRejects(() => runtimeA.comHub.ping("@intercept_b", 200));
+    assert(receivers.includes("@intercept_b"), "Expected receiver endpoint");
+
+    // dropped blocks are not counted as failed sends
+    const statistics = runtimeA.comHub.getTrafficStatistics();
+    const loopback = statistics.interfaces.find((i) =>
+        i.uuid === interfaceA.uuid
+    );
+    assertEquals(loopback?.traffic.failed_sends, 0);
+    removeOutgoing();
+    await runtimeA.comHub.ping("@intercept_b");
+
+    await close();
+});
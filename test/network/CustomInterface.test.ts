@@ -0,0 +1,134 @@
+import { assert } from "@std/assert/assert";
+import { assertEquals } from "@std/assert/equals";
+import { assertRejects } from "@std/assert/rejects";
+import { assertThrows } from "@std/assert/throws";
+import * as uuid from "@std/uuid";
+import { Runtime } from "../../src/runtime/runtime.ts";
+import type {
+    CustomInterface,
+    CustomInterfaceFactory,
+    CustomJSInterfaceController,
+} from "../../src/network/custom-interface.ts";
+import { sleep } from "../utils.ts";
+
+type PipeSetupData = { pipe: string };
+
+/**
+ * In-memory transport that connects the two ends of a named pipe
+ */
+class PipeEnd implements CustomInterface {
+    static ends = new Map<string, PipeEnd[]>();
+
+    readonly controller: CustomJSInterfaceController;
+    readonly pipe: string;
+    socket?: string;
+    closed = false;
+
+    constructor(pipe: string, controller: CustomJSInterfaceController) {
+        this.pipe = pipe;
+        this.controller = controller;
+    }
+
+    open() {
+        this.socket = this.controller.add_socket("InOut");
+        const ends = PipeEnd.ends.get(this.pipe) ?? [];
+        ends.push(this);
+        PipeEnd.ends.set(this.pipe, ends);
+    }
+
+    send(block: Uint8Array) {
+        const other = PipeEnd.ends.get(this.pipe)?.find((end) =>
+            end !== this
+        );
+        if (!other?.socket) return false;
+        other.controller.receive(other.socket, block);
+        return true;
+    }
+
+    close() {
+        this.closed = true;
+        const ends = PipeEnd.ends.get(this.pipe) ?? [];
+        PipeEnd.ends.set(this.pipe, ends.filter((end) => end !== this));
+    }
+}
+
+const pipeFactory: CustomInterfaceFactory<PipeSetupData> = {
+    properties: { channel: "pipe", round_trip_time: 1 },
+    create: ({ pipe }, controller) => new PipeEnd(pipe, controller),
+};
+
+Deno.test("create and close custom interface", async () => {
+    const runtime = new Runtime({ endpoint: "@unyt" });
+    let end: PipeEnd | undefined;
+    runtime.comHub.registerInterfaceFactory<PipeSetupData>("pipe", {
+        ...pipeFactory,
+        create: ({ pipe }, controller) => {
+            end = new PipeEnd(pipe, controller);
+            return end;
+        },
+    });
+    assertThrows(() =>
+        runtime.comHub.registerInterfaceFactory("pipe", pipeFactory)
+    );
+    // built-in interface types can not be replaced
+    assertThrows(() =>
+        runtime.comHub.registerInterfaceFactory("loopback", pipeFactory)
+    );
+
+    const pipeInterface = await runtime.comHub.createInterface(
+        "pipe",
+        { pipe: "create" },
+    );
+    assert(uuid.validate(pipeInterface.uuid), "Invalid UUID");
+    assert(end !== undefined, "Factory was not called");
+    assertEquals(end.pipe, "create");
+    assertEquals(end.controller.interface_uuid, pipeInterface.uuid);
+    assert(uuid.validate(end.socket!), "Invalid socket UUID");
+
+    await pipeInterface.close();
+    assert(end.closed, "Implementation was not closed");
+});
+
+Deno.test("custom interface fails to open", async () => {
+    const runtime = new Runtime({ endpoint: "@unyt" });
+    runtime.comHub.registerInterfaceFactory("failing", {
+        create: () => ({
+            open: () => Promise.reject(new Error("unreachable")),
+            send: () => false,
+        }),
+    });
+    await assertRejects(() =>
+        runtime.comHub.createInterface("failing", {})
+    );
+});
+
+Deno.test("send data between two runtimes", async () => {
+    const runtimeA = await Runtime.create({ endpoint: "@test_a" }, {
+        allow_unsigned_blocks: true,
+    });
+    const runtimeB = await Runtime.create({ endpoint: "@test_b" }, {
+        allow_unsigned_blocks: true,
+    });
+    runtimeA.comHub.registerInterfaceFactory("pipe", pipeFactory);
+    runtimeB.comHub.registerInterfaceFactory("pipe", pipeFactory);
+
+    const interfaceA = await runtimeA.comHub.createInterface(
+        "pipe",
+        { pipe: "runtimes" },
+    );
+    const interfaceB = await runtimeB.comHub.createInterface(
+        "pipe",
+        { pipe: "runtimes" },
+    );
+
+    await sleep(100);
+
+    const res = await runtimeA.executeWithStringResult("@test_b :: 1 + 2");
+    assert(res === "3", "Expected result from remote execution to be 3");
+
+    await interfaceA.close();
+    await interfaceB.close();
+
+    await runtimeA._stop();
+    await runtimeB._stop();
+});
@@ -0,0 +1,100 @@
+import { assert } from "@std/assert/assert";
+import { Runtime } from "../../src/runtime/runtime.ts";
+import { isNodeOrBun } from "../is-node.ts";
+import "../../src/network/interface-impls/http-server-deno.ts";
+import "../../src/network/interface-impls/websocket-client.ts";
+import { sleep } from "../utils.ts";
+import { HttpClientInterfaceImpl } from "../../src/network/interface-impls/http-client.ts";
+import type { HttpServerDenoInterfaceImpl } from "../../src/network/interface-impls/http-server-deno.ts";
+
+for (const streaming of [false, true]) {
+    Deno.test(
+        `send data between two runtimes (${
+            streaming ? "streaming" : "long-polling"
+        })`,
+        async () => {
+            // FIXME: temporarily disabled because Deno.serve is not yet supported for node.js/dnt
+            if (isNodeOrBun) {
+                console.warn(
+                    "HTTP server tests are currently disabled in Node.js or Bun environments.",
+                );
+                return;
+            }
+
+            const PORT = streaming ? 8087 : 8086;
+            const runtimeA = await Runtime.create({ endpoint: "@test_a" }, {
+                allow_unsigned_blocks: true,
+            });
+            const serverInterface = await runtimeA.comHub.createInterface<
+                HttpServerDenoInterfaceImpl
+            >(
+                "http-server",
+                { port: PORT },
+            );
+
+            const runtimeB = await Runtime.create({ endpoint: "@test_b" }, {
+                allow_unsigned_blocks: true,
+            });
+            const clientInterface = await runtimeB.comHub.createInterface<
+                HttpClientInterfaceImpl
+            >(
+                "http-client",
+                { address: `http://localhost:${PORT}`, streaming },
+            );
+
+            await sleep(500);
+
+            const res = await runtimeA.executeWithStringResult(
+                "@test_b :: 1 + 2",
+            );
+            assert(
+                res === "3",
+                "Expected result from remote execution to be 3",
+            );
+
+            await clientInterface.close();
+            await serverInterface.close();
+
+            await runtimeA._stop();
+            await runtimeB._stop();
+        },
+    );
+}
+
+Deno.test("fall back to http if websocket is blocked", async () => {
+    // FIXME: temporarily disabled because Deno.serve is not yet supported for node.js/dnt
+    if (isNodeOrBun) {
+        console.warn(
+            "HTTP server tests are currently disabled in Node.js or Bun environments.",
+        );
+        return;
+    }
+
+    const PORT = 8088;
+    const runtimeA = new Runtime({ endpoint: "@test_a" });
+    const serverInterface = await runtimeA.comHub.createInterface<
+        HttpServerDenoInterfaceImpl
+    >(
+        "http-server",
+        { port: PORT },
+    );
+
+    const runtimeB = new Runtime({ endpoint: "@test_b" });
+    const clientInterface = await runtimeB.comHub.createInterfaceWithFallback([
+        {
+            interfaceType: "websocket-client",
+            setupData: {
+                address: `ws://localhost:${PORT}`,
+                reconnect: { enabled: false },
+            },
+        },
+        {
+            interfaceType: "http-client",
+            setupData: { address: `http://localhost:${PORT}` },
+        },
+    ]);
+    assert(clientInterface.impl instanceof HttpClientInterfaceImpl);
+
+    await clientInterface.close();
+    await serverInterface.close();
+});
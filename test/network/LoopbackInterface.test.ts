@@ -0,0 +1,54 @@
+import { assert } from "@std/assert/assert";
+import { assertThrows } from "@std/assert";
+import { Runtime } from "../../src/runtime/runtime.ts";
+import * as uuid from "@std/uuid";
+import "../../src/network/interface-impls/loopback.ts";
+import { sleep } from "../utils.ts";
+import type { LoopbackInterfaceImpl } from "../../src/network/interface-impls/loopback.ts";
+
+Deno.test("add and close interface", async () => {
+    const runtime = new Runtime({ endpoint: "@unyt" });
+    const loopbackInterface = await runtime.comHub.createInterface<
+        LoopbackInterfaceImpl
+    >(
+        "loopback",
+        {},
+    );
+    assert(uuid.validate(loopbackInterface.uuid), "Invalid UUID");
+    await loopbackInterface.close();
+});
+
+Deno.test("send data between two runtimes", async () => {
+    const runtimeA = await Runtime.create({ endpoint: "@test_a" }, {
+        allow_unsigned_blocks: true,
+    });
+    const interfaceA = await runtimeA.comHub.createInterface<
+        LoopbackInterfaceImpl
+    >(
+        "loopback",
+        { name: "b", latency: 20, jitter: 5, seed: 1 },
+    );
+
+    const runtimeB = await Runtime.create({ endpoint: "@test_b" }, {
+        allow_unsigned_blocks: true,
+    });
+    const interfaceB = await runtimeB.comHub.createInterface<
+        LoopbackInterfaceImpl
+    >(
+        "loopback",
+        { name: "a", latency: 20, jitter: 5, seed: 2 },
+    );
+    interfaceA.impl.connect(interfaceB.impl);
+    assertThrows(() => interfaceB.impl.connect(interfaceA.impl));
+
+    await sleep(100);
+
+    const res = await runtimeA.executeWithStringResult("@test_b :: 1 + 2");
+    assert(res === "3", "Expected result from remote execution to be 3");
+
+    await interfaceA.close();
+    await interfaceB.close();
+
+    await runtimeA._stop();
+    await runtimeB._stop();
+});
@@ -0,0 +1,53 @@
+import { assert } from "@std/assert/assert";
+import { Runtime } from "../../src/runtime/runtime.ts";
+import * as uuid from "@std/uuid";
+import "../../src/network/interface-impls/message-port.ts";
+import { sleep } from "../utils.ts";
+import type { MessagePortInterfaceImpl } from "../../src/network/interface-impls/message-port.ts";
+
+Deno.test("add and close interface", async () => {
+    const runtime = new Runtime({ endpoint: "@unyt" });
+    const { port1 } = new MessageChannel();
+    const portInterface = await runtime.comHub.createInterface<
+        MessagePortInterfaceImpl
+    >(
+        "message-port",
+        { name: undefined, port: port1 },
+    );
+    assert(uuid.validate(portInterface.uuid), "Invalid UUID");
+    await portInterface.close();
+});
+
+Deno.test("send data between two runtimes", async () => {
+    const { port1, port2 } = new MessageChannel();
+    const runtimeA = await Runtime.create({ endpoint: "@test_a" }, {
+        allow_unsigned_blocks: true,
+    });
+    const interfaceA = await runtimeA.comHub.createInterface<
+        MessagePortInterfaceImpl
+    >(
+        "message-port",
+        { name: "b", port: port1 },
+    );
+
+    const runtimeB = await Runtime.create({ endpoint: "@test_b" }, {
+        allow_unsigned_blocks: true,
+    });
+    const interfaceB = await runtimeB.comHub.createInterface<
+        MessagePortInterfaceImpl
+    >(
+        "message-port",
+        { name: "a", port: port2 },
+    );
+
+    await sleep(100);
+
+    const res = await runtimeA.executeWithStringResult("@test_b :: 1 + 2");
+    assert(res === "3", "Expected result from remote execution to be 3");
+
+    await interfaceA.close();
+    await interfaceB.close();
+
+    await runtimeA._stop();
+    await runtimeB._stop();
+});
@@ -0,0 +1,73 @@
+import { assertArrayIncludes, assertEquals } from "@std/assert";
+import { Runtime } from "../../src/runtime/runtime.ts";
+import "../../src/network/interface-impls/webtransport.ts";
+import { createWebTransportMockupServer } from "./WebTransportMockupServer.ts";
+import { sleep } from "../utils.ts";
+
+const ADDRESS = "https://localhost:4433/datex";
+
+Deno.test("webtransport send blocks over streams and datagrams", async () => {
+    using server = createWebTransportMockupServer();
+    const runtime = new Runtime({ endpoint: "@unyt" });
+    const connection = await runtime.comHub.createInterface(
+        "webtransport",
+        { address: ADDRESS, datagrams: true },
+    );
+    assertEquals(server.connections, [ADDRESS]);
+
+    const small = new Uint8Array(100).fill(1);
+    const large = new Uint8Array(server.maxDatagramSize + 1).fill(2);
+    await runtime.comHub.sendBlock(small, connection.uuid, "");
+    await runtime.comHub.sendBlock(large, connection.uuid, "");
+    await sleep(10);
+
+    assertArrayIncludes(server.receivedDatagrams, [small]);
+    assertArrayIncludes(server.receivedStreams, [large]);
+    await connection.close();
+});
+
+Deno.test("webtransport send blocks over streams only", async () => {
+    using server = createWebTransportMockupServer();
+    const runtime = new Runtime({ endpoint: "@unyt" });
+    const connection = await runtime.comHub.createInterface(
+        "webtransport",
+        { address: ADDRESS },
+    );
+
+    const small = new Uint8Array(100).fill(1);
+    await runtime.comHub.sendBlock(small, connection.uuid, "");
+    await sleep(10);
+
+    assertEquals(server.receivedDatagrams, []);
+    assertArrayIncludes(server.receivedStreams, [small]);
+    await connection.close();
+});
+
+Deno.test("webtransport block retrieval", async () => {
+    using server = createWebTransportMockupServer();
+    const runtime = new Runtime({ endpoint: "@unyt" }, {
+        allow_unsigned_blocks: true,
+    });
+    const connection = await runtime.comHub.createInterface(
+        "webtransport",
+        { address: ADDRESS, datagrams: true },
+    );
+
+    const datagramBlock = runtime._runtime._create_block(
+        new Uint8Array([0x01, 0x02, 0x03, 0x04]),
+        ["@unyt"],
+    );
+    const streamBlock = runtime._runtime._create_block(
+        new Uint8Array([0x05, 0x06, 0x07, 0x08]),
+        ["@unyt"],
+    );
+    server.sendDatagram(datagramBlock);
+    server.sendStream(streamBlock);
+    await sleep(10);
+    await runtime.comHub._update();
+
+    const blocks = runtime.comHub._drain_incoming_blocks();
+    assertEquals(blocks.length, 2);
+    assertArrayIncludes(blocks, [datagramBlock, streamBlock]);
+    await connection.close();
+});
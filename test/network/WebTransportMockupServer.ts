@@ -0,0 +1,105 @@
+const MAX_DATAGRAM_SIZE = 1200;
+
+function concat(chunks: Uint8Array[]) {
+    const bytes = new Uint8Array(
+        chunks.reduce((length, chunk) => length + chunk.length, 0),
+    );
+    let offset = 0;
+    for (const chunk of chunks) {
+        bytes.set(chunk, offset);
+        offset += chunk.length;
+    }
+    return bytes;
+}
+
+function createReadable<T>(chunks: T[]) {
+    return new ReadableStream<T>({
+        start(controller) {
+            chunks.forEach((chunk) => controller.enqueue(chunk));
+            controller.close();
+        },
+    });
+}
+
+/**
+ * Stand-in for a WebTransport server, replaces the global WebTransport
+ * class with an in-memory connection until it is disposed.
+ */
+export const createWebTransportMockupServer = () => {
+    const originalWebTransport = globalThis.WebTransport;
+    const receivedStreams: Uint8Array[] = [];
+    const receivedDatagrams: Uint8Array[] = [];
+    const connections: string[] = [];
+    let datagrams: ReadableStreamDefaultController<Uint8Array> | undefined;
+    let streams: ReadableStreamDefaultController<unknown> | undefined;
+
+    class MockupWebTransport {
+        readonly ready = Promise.resolve();
+        readonly #closed = Promise.withResolvers<void>();
+        readonly closed = this.#closed.promise;
+        readonly datagrams = {
+            maxDatagramSize: MAX_DATAGRAM_SIZE,
+            readable: new ReadableStream<Uint8Array>({
+                start: (controller) => datagrams = controller,
+            }),
+            writable: new WritableStream<Uint8Array>({
+                write: (chunk) => {
+                    receivedDatagrams.push(chunk);
+                },
+            }),
+        };
+        readonly incomingBidirectionalStreams = new ReadableStream({
+            start: (controller) => streams = controller,
+        });
+        readonly incomingUnidirectionalStreams = new ReadableStream();
+
+        constructor(url: string) {
+            connections.push(url);
+        }
+
+        createBidirectionalStream() {
+            const chunks: Uint8Array[] = [];
+            return Promise.resolve({
+                readable: createReadable([]),
+                writable: new WritableStream<Uint8Array>({
+                    write: (chunk) => {
+                        chunks.push(chunk);
+                    },
+                    close: () => {
+                        receivedStreams.push(concat(chunks));
+                    },
+                }),
+            });
+        }
+
+        close() {
+            this.#closed.resolve();
+        }
+    }
+
+    globalThis.WebTransport =
+        MockupWebTransport as unknown as typeof WebTransport;
+
+    return {
+        maxDatagramSize: MAX_DATAGRAM_SIZE,
+        connections,
+        receivedStreams,
+        receivedDatagrams,
+        sendDatagram: (data: Uint8Array) => datagrams?.enqueue(data),
+        /**
+         * Opens a bidirectional stream and splits the data into
+         * multiple chunks
+         */
+        sendStream: (data: Uint8Array) =>
+            streams?.enqueue({
+                readable: createReadable([
+                    data.slice(0, data.length / 2),
+                    data.slice(data.length / 2),
+                ]),
+                writable: new WritableStream(),
+            }),
+        [Symbol.dispose]: () => {
+            globalThis.WebTransport = originalWebTransport;
+        },
+    };
+};
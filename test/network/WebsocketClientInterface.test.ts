@@ -96,3 +96,41 @@ Deno.test("websocket block retrieval", async () => {
     assert(incoming_block.length === block.length);
     assertEquals(incoming_block, block);
 });
+
+Deno.test("websocket reconnect after server restart", async () => {
+    // FIXME: temporarily disabled because Deno.serve is not yet supported for node.js/dnt
+    if (isNodeOrBun) {
+        console.warn(
+            "Crypto tests are currently disabled in Node.js or Bun environments.",
+        );
+        return;
+    }
+    const port = 8486;
+    const mockupServer = createMockupServer(port);
+
+    const runtime = new Runtime({ endpoint: "@unyt" }, {
+        allow_unsigned_blocks: true,
+    });
+    const connection = runtime.comHub.createInterface("websocket-client", {
+        address: `ws://localhost:${port}/`,
+        reconnect: { initial_delay: 50, jitter: 0 },
+    });
+    const server = await mockupServer;
+    await connection;
+    await server[Symbol.asyncDispose]();
+
+    // the client reconnects to the restarted server
+    await using restartedServer = await createMockupServer(port);
+
+    const block = runtime._runtime._create_block(
+        new Uint8Array([0x01, 0x02, 0x03, 0x04]),
+        ["@unyt"],
+    );
+    restartedServer.send(block);
+    await sleep(10);
+    await runtime.comHub._update();
+
+    const blocks = runtime.comHub._drain_incoming_blocks();
+    assertEquals(blocks.length, 1);
+    assertEquals(blocks[0], block);
+});
@@ -1,4 +1,5 @@
 import { assert } from "@std/assert/assert";
+import { assertEquals, assertRejects } from "@std/assert";
 import { Runtime } from "../../src/runtime/runtime.ts";
 import * as uuid from "@std/uuid";
 import { isNodeOrBun } from "../is-node.ts";
@@ -85,3 +86,219 @@ Deno.test("send data between two runtimes", async () => {
     await runtimeA._stop();
     await runtimeB._stop();
 });
+
+Deno.test("list and close server sockets", async () => {
+    // FIXME: temporarily disabled because Deno.serve is not yet supported for node.js/dnt
+    if (isNodeOrBun) {
+        console.warn(
+            "Crypto tests are currently disabled in Node.js or Bun environments.",
+        );
+        return;
+    }
+
+    const PORT = 8083;
+    const runtimeA = new Runtime({ endpoint: "@test_a" });
+    const serverInterface = await runtimeA.comHub.createInterface<
+        WebSockerServerDenoInterfaceImpl
+    >(
+        "websocket-server",
+        { port: PORT, secure: false },
+    );
+    const connected: string[] = [];
+    const disconnected: [string, number][] = [];
+    serverInterface.impl.setOnConnect((socketUUID) =>
+        connected.push(socketUUID)
+    );
+    serverInterface.impl.setOnDisconnect((socketUUID, closeInfo) =>
+        disconnected.push([socketUUID, closeInfo.code])
+    );
+
+    const runtimeB = new Runtime({ endpoint: "@test_b" });
+    const clientInterface = await runtimeB.comHub.createInterface<
+        WebSocketClientInterfaceImpl
+    >(
+        "websocket-client",
+        {
+            address: `ws://localhost:${PORT}`,
+            reconnect: { enabled: false },
+        },
+    );
+    await sleep(100);
+
+    const sockets = serverInterface.impl.getSockets();
+    assertEquals(sockets.length, 1);
+    assertEquals(connected, [sockets[0].socket_uuid]);
+
+    serverInterface.impl.closeSocket(sockets[0].socket_uuid, 4000, "kicked");
+    await sleep(100);
+
+    assertEquals(serverInterface.impl.getSockets().length, 0);
+    assertEquals(disconnected, [[sockets[0].socket_uuid, 4000]]);
+    assertEquals(clientInterface.impl.getCloseInfo()?.reason, "kicked");
+
+    await serverInterface.close();
+    await clientInterface.close();
+});
+
+async function generateEndpointKey() {
+    const keyPair = await crypto.subtle.generateKey(
+        { name: "Ed25519" },
+        true,
+        ["sign", "verify"],
+    ) as CryptoKeyPair;
+    return {
+        publicKey: new Uint8Array(
+            await crypto.subtle.exportKey("spki", keyPair.publicKey),
+        ),
+        privateKey: Array.from(
+            new Uint8Array(
+                await crypto.subtle.exportKey("pkcs8", keyPair.privateKey),
+            ),
+        ),
+    };
+}
+
+Deno.test("handshake with endpoint key", async () => {
+    // FIXME: temporarily disabled because Deno.serve is not yet supported for node.js/dnt
+    if (isNodeOrBun) {
+        console.warn(
+            "Crypto tests are currently disabled in Node.js or Bun environments.",
+        );
+        return;
+    }
+
+    const PORT = 8084;
+    const key = await generateEndpointKey();
+    const runtimeA = new Runtime({ endpoint: "@test_a" });
+    const serverInterface = await runtimeA.comHub.createInterface<
+        WebSockerServerDenoInterfaceImpl
+    >(
+        "websocket-server",
+        {
+            port: PORT,
+            secure: false,
+            handshake: true,
+            resolveKey: (endpoint) =>
+                endpoint === "@test_b" ? key.publicKey : undefined,
+        },
+    );
+
+    const runtimeB = new Runtime({ endpoint: "@test_b" });
+    const clientInterface = await runtimeB.comHub.createInterface<
+        WebSocketClientInterfaceImpl
+    >(
+        "websocket-client",
+        {
+            address: `ws://localhost:${PORT}`,
+            handshake: { endpoint: "@test_b", private_key: key.privateKey },
+        },
+    );
+
+    const sockets = serverInterface.impl.getSockets();
+    assertEquals(sockets.length, 1);
+    assertEquals(sockets[0].endpoint, "@test_b");
+
+    await serverInterface.close();
+    await clientInterface.close();
+});
+
+Deno.test("handshake with invalid endpoint key", async () => {
+    // FIXME: temporarily disabled because Deno.serve is not yet supported for node.js/dnt
+    if (isNodeOrBun) {
+        console.warn(
+            "Crypto tests are currently disabled in Node.js or Bun environments.",
+        );
+        return;
+    }
+
+    const PORT = 8085;
+    const key = await generateEndpointKey();
+    const otherKey = await generateEndpointKey();
+    const runtimeA = new Runtime({ endpoint: "@test_a" });
+    const serverInterface = await runtimeA.comHub.createInterface<
+        WebSockerServerDenoInterfaceImpl
+    >(
+        "websocket-server",
+        {
+            port: PORT,
+            secure: false,
+            handshake: true,
+            resolveKey: () => key.publicKey,
+        },
+    );
+
+    const runtimeB = new Runtime({ endpoint: "@test_b" });
+    await assertRejects(
+        async () =>
+            await runtimeB.comHub.createInterface("websocket-client", {
+                address: `ws://localhost:${PORT}`,
+                handshake: {
+                    endpoint: "@test_b",
+                    private_key: otherKey.privateKey,
+                },
+            }),
+    );
+    assertEquals(serverInterface.impl.getSockets().length, 0);
+
+    await serverInterface.close();
+});
+
+Deno.test("handshake signed for another server", async () => {
+    // FIXME: temporarily disabled because Deno.serve is not yet supported for node.js/dnt
+    if (isNodeOrBun) {
+        console.warn(
+            "Crypto tests are currently disabled in Node.js or Bun environments.",
+        );
+        return;
+    }
+
+    const PORT = 8089;
+    const key = await generateEndpointKey();
+    const runtimeA = new Runtime({ endpoint: "@test_a" });
+    // the client connects via localhost, so its signature is for
+    // another origin than the one of the server
+    const serverInterface = await runtimeA.comHub.createInterface<
+        WebSockerServerDenoInterfaceImpl
+    >(
+        "websocket-server",
+        {
+            port: PORT,
+            secure: false,
+            handshake: true,
+            handshake_origin: "wss://example.com",
+            resolveKey: () => key.publicKey,
+        },
+    );
+
+    const runtimeB = new Runtime({ endpoint: "@test_b" });
+    await assertRejects(
+        async () =>
+            await runtimeB.comHub.createInterface("websocket-client", {
+                address: `ws://localhost:${PORT}`,
+                handshake: {
+                    endpoint: "@test_b",
+                    private_key: key.privateKey,
+                },
+            }),
+    );
+    assertEquals(serverInterface.impl.getSockets().length, 0);
+
+    await serverInterface.close();
+});
+
+Deno.test("handshake credentials for another endpoint", async () => {
+    const key = await generateEndpointKey();
+    const runtime = new Runtime({ endpoint: "@test_b" });
+    await assertRejects(
+        async () =>
+            await runtime.comHub.createInterface("websocket-client", {
+                address: "ws://localhost:8090",
+                handshake: {
+                    endpoint: "@test_c",
+                    private_key: key.privateKey,
+                },
+            }),
+        Error,
+        "does not match the runtime endpoint",
+    );
+});